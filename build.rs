@@ -0,0 +1,19 @@
+// The profiler walks frame pointers, so it is only enabled when the build
+// keeps them. `frame_pointers` is set when the rustflags force them on.
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(frame_pointers)");
+    println!("cargo:rerun-if-env-changed=CARGO_ENCODED_RUSTFLAGS");
+
+    let flags = std::env::var("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
+
+    let forced = flags.split('\x1f').any(|flag| {
+        flag.strip_prefix("-C")
+            .unwrap_or(flag)
+            .strip_prefix("force-frame-pointers")
+            .is_some_and(|value| matches!(value, "" | "=yes" | "=y" | "=on" | "=true"))
+    });
+
+    if forced {
+        println!("cargo:rustc-cfg=frame_pointers");
+    }
+}
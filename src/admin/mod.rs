@@ -6,7 +6,23 @@ use std::sync::Arc;
 
 /// The HTTP admin server.
 pub async fn http(config: Config, ratelimit: Option<Arc<Ratelimiter>>) {
-    let admin = filters::admin(ratelimit);
+    // pre-format the labels for the prometheus exposition, escaping the values
+    // as required by the text format
+    let labels: Vec<String> = config
+        .general()
+        .labels()
+        .iter()
+        .map(|(key, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+
+            format!("{key}=\"{value}\"")
+        })
+        .collect();
+
+    let admin = filters::admin(ratelimit, Arc::new(labels));
 
     let addr = config
         .general()
@@ -26,8 +42,9 @@ mod filters {
     /// The combined set of admin endpoint filters
     pub fn admin(
         ratelimit: Option<Arc<Ratelimiter>>,
+        labels: Arc<Vec<String>>,
     ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-        prometheus_stats()
+        prometheus_stats(labels)
             .or(human_stats())
             .or(json_stats())
             .or(update_ratelimit(ratelimit))
@@ -38,9 +55,11 @@ mod filters {
     ///
     /// GET /metrics
     pub fn prometheus_stats(
+        labels: Arc<Vec<String>>,
     ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
         warp::path!("metrics")
             .and(warp::get())
+            .and(warp::any().map(move || labels.clone()))
             .and_then(handlers::prometheus_stats)
     }
 
@@ -115,7 +134,8 @@ pub mod handlers {
     /// Serves Prometheus / OpenMetrics text format metrics. All metrics have
     /// type information, some have descriptions as well. Percentiles read from
     /// heatmaps are exposed with a `percentile` label where the value
-    /// corresponds to the percentile in the range of 0.0 - 100.0.
+    /// corresponds to the percentile in the range of 0.0 - 100.0. Any labels
+    /// from the config are attached to every metric.
     ///
     /// See: https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
     ///
@@ -129,9 +149,23 @@ pub mod handlers {
     /// # TYPE some_distribution{percentile="50.0"} gauge
     /// some_distribution{percentile="50.0"} 0
     /// ```
-    pub async fn prometheus_stats() -> Result<impl warp::Reply, Infallible> {
+    pub async fn prometheus_stats(
+        labels: Arc<Vec<String>>,
+    ) -> Result<impl warp::Reply, Infallible> {
         let mut data = Vec::new();
 
+        // labels which are attached to every metric
+        let base_labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", labels.join(","))
+        };
+        let percentile_labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{},", labels.join(","))
+        };
+
         let metrics_snapshot = METRICS_SNAPSHOT.read().await;
 
         let timestamp = metrics_snapshot
@@ -151,19 +185,21 @@ pub mod handlers {
                 Some(Value::Counter(value)) => {
                     if let Some(description) = metric.description() {
                         data.push(format!(
-                            "# TYPE {name} counter\n# HELP {name} {description}\n{name} {value}"
+                            "# TYPE {name} counter\n# HELP {name} {description}\n{name}{base_labels} {value}"
                         ));
                     } else {
-                        data.push(format!("# TYPE {name} counter\n{name} {value}"));
+                        data.push(format!(
+                            "# TYPE {name} counter\n{name}{base_labels} {value}"
+                        ));
                     }
                 }
                 Some(Value::Gauge(value)) => {
                     if let Some(description) = metric.description() {
                         data.push(format!(
-                            "# TYPE {name} gauge\n# HELP {name} {description}\n{name} {value}"
+                            "# TYPE {name} gauge\n# HELP {name} {description}\n{name}{base_labels} {value}"
                         ));
                     } else {
-                        data.push(format!("# TYPE {name} gauge\n{name} {value}"));
+                        data.push(format!("# TYPE {name} gauge\n{name}{base_labels} {value}"));
                     }
                 }
                 Some(Value::Other(_)) => {
@@ -172,12 +208,12 @@ pub mod handlers {
                     for (_label, percentile, value) in percentiles {
                         if let Some(description) = metric.description() {
                             data.push(format!(
-                                "# TYPE {name} gauge\n# HELP {name} {description}\n{name}{{percentile=\"{:02}\"}} {value} {timestamp}",
+                                "# TYPE {name} gauge\n# HELP {name} {description}\n{name}{{{percentile_labels}percentile=\"{:02}\"}} {value} {timestamp}",
                                 percentile,
                             ));
                         } else {
                             data.push(format!(
-                                "# TYPE {name} gauge\n{name}{{percentile=\"{:02}\"}} {value} {timestamp}",
+                                "# TYPE {name} gauge\n{name}{{{percentile_labels}percentile=\"{:02}\"}} {value} {timestamp}",
                                 percentile,
                             ));
                         }
@@ -32,8 +32,21 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
     let mut session_requests = 0;
     let mut session_start = Instant::now();
 
+    let mut reconnect = false;
+    let mut lifetime = ConnectionLifetime::new(&config);
+    let stats = ConnectionStats::new(&endpoint);
+
+    start_jitter(&config).await;
+
     while RUNNING.load(Ordering::Relaxed) {
+        lifetime.recycle(&mut session);
+
         if session.is_none() {
+            if reconnect {
+                reconnect_ratelimit().await;
+            }
+            reconnect = true;
+
             if session_requests != 0 {
                 let stop = Instant::now();
                 let lifecycle_ns = (stop - session_start).as_nanos() as u64;
@@ -73,6 +86,8 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
             session_start = Instant::now();
             session_requests = 0;
+            lifetime.opened();
+            stats.connected();
             CONNECT_CURR.increment();
             SESSION.increment();
 
@@ -87,16 +102,19 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
         let mut s = session.take().unwrap();
 
-        let work_item = work_receiver
-            .recv()
-            .await
-            .map_err(|_| Error::new(ErrorKind::Other, "channel closed"))?;
+        let (work_item, _in_flight) = recv_work(&work_receiver, &config).await?;
+        let histograms = ResponseHistograms::new(&work_item);
+        let mut context = error_context(&config, &work_item);
+        let correlation = correlation_id(&config, &mut context);
 
         REQUEST.increment();
 
         // compose request into buffer
+        let build = Instant::now();
         let request = match &work_item {
-            WorkItem::Request { request, sequence } => match request {
+            WorkItem::Request {
+                request, sequence, ..
+            } => match request {
                 ClientRequest::Get(r) => {
                     let key = unsafe { std::str::from_utf8_unchecked(&r.key) };
                     let url: Uri = if config.tls().is_none() {
@@ -106,20 +124,26 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                     };
                     let authority = url.authority().unwrap().clone();
 
-                    Request::builder()
+                    let mut builder = Request::builder()
                         .uri(url)
                         .header(hyper::header::HOST, authority.as_str())
                         .header(
                             hyper::header::USER_AGENT,
                             &format!("rpc-perf/5.0.0-alpha (request; seq:{sequence})"),
-                        )
+                        );
+
+                    if let Some((header, id)) = &correlation {
+                        builder = builder.header(*header, id.as_str());
+                    }
+
+                    builder
                         .body(Empty::<Bytes>::new())
                         .expect("failed to build request")
                 }
-                _ => {
+                other => {
                     // skip any requests that aren't supported and preserve the
                     // session for reuse
-                    REQUEST_UNSUPPORTED.increment();
+                    unsupported(&config, other);
                     session = Some(s);
                     continue;
                 }
@@ -135,9 +159,12 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
         };
 
         REQUEST_OK.increment();
+        lifetime.request();
+        stats.request();
 
         // send request
         let start = Instant::now();
+        let _ = REQUEST_BUILD_LATENCY.increment(start.duration_since(build).as_nanos() as u64);
         let response = timeout(
             config.client().unwrap().request_timeout(),
             s.send_request(request),
@@ -145,6 +172,20 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
         .await;
         let stop = Instant::now();
 
+        let latency = stop.duration_since(start).as_nanos() as u64;
+
+        processing_delay(&config).await;
+        think_time(&config, latency).await;
+
+        log_access(
+            &context,
+            &response
+                .as_ref()
+                .map_err(|_| ResponseError::Timeout)
+                .and_then(|response| response.as_ref().map_err(|_| ResponseError::Exception)),
+            latency,
+        );
+
         match response {
             Ok(Ok(response)) => {
                 // validate response
@@ -165,10 +206,16 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                 }
 
                 RESPONSE_OK.increment();
+                stats.ok(latency);
 
-                let latency = stop.duration_since(start).as_nanos() as u64;
+                histograms.record_success(&config, latency);
 
-                let _ = RESPONSE_LATENCY.increment(latency);
+                if let Some(name) = config.client().unwrap().server_latency_header() {
+                    record_server_latency(
+                        &config,
+                        response.headers().get(name).map(|value| value.as_bytes()),
+                    );
+                }
 
                 if let Some(header) = response
                     .headers()
@@ -191,10 +238,13 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                     session = Some(s);
                 }
             }
-            Ok(Err(_e)) => {
+            Ok(Err(e)) => {
+                log_error(&context, e);
+
                 // an actual error was returned, do the necessary bookkeeping
                 // and allow the session to be dropped
                 RESPONSE_EX.increment();
+                let _ = RESPONSE_EX_LATENCY.increment(latency);
 
                 // record execption
                 match work_item {
@@ -216,9 +266,12 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                 CONNECT_CURR.decrement();
             }
             Err(_) => {
+                log_error(&context, ResponseError::Timeout);
+
                 // increment timeout related stats and allow the session to be
                 // dropped
                 RESPONSE_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency);
                 SESSION_CLOSED_CLIENT.increment();
                 CONNECT_CURR.decrement();
             }
@@ -78,8 +78,15 @@ async fn pool_manager(endpoint: String, config: Config, queue: Queue<SendRequest
     let connector = Connector::new(&config).expect("failed to init connector");
     let mut sender = None;
 
+    let mut reconnect = false;
+
     while RUNNING.load(Ordering::Relaxed) {
         if sender.is_none() {
+            if reconnect {
+                reconnect_ratelimit().await;
+            }
+            reconnect = true;
+
             CONNECT.increment();
             let stream = match timeout(
                 config.client().unwrap().connect_timeout(),
@@ -145,6 +152,9 @@ async fn task(
 ) -> Result<()> {
     // let connector = Connector::new(&config)?;
     let mut sender = None;
+    let stats = ConnectionStats::new(&endpoint);
+
+    start_jitter(&config).await;
 
     while RUNNING.load(Ordering::Relaxed) {
         if sender.is_none() {
@@ -153,20 +163,24 @@ async fn task(
                 .await
                 .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
             sender = Some(s);
+            stats.connected();
         }
 
         let mut s = sender.take().unwrap();
 
-        let work_item = work_receiver
-            .recv()
-            .await
-            .map_err(|_| Error::new(ErrorKind::Other, "channel closed"))?;
+        let (work_item, _in_flight) = recv_work(&work_receiver, &config).await?;
+        let histograms = ResponseHistograms::new(&work_item);
+        let mut context = error_context(&config, &work_item);
+        let correlation = correlation_id(&config, &mut context);
 
         REQUEST.increment();
 
         // compose request into buffer
+        let build = Instant::now();
         let request = match &work_item {
-            WorkItem::Request { request, sequence } => match request {
+            WorkItem::Request {
+                request, sequence, ..
+            } => match request {
                 ClientRequest::Get(r) => {
                     let key = unsafe { std::str::from_utf8_unchecked(&r.key) };
                     let url: Uri = if config.tls().is_none() {
@@ -176,18 +190,24 @@ async fn task(
                     };
                     let authority = url.authority().unwrap().clone();
 
-                    Request::builder()
+                    let mut builder = Request::builder()
                         .uri(url)
                         .header(hyper::header::HOST, authority.as_str())
                         .header(
                             hyper::header::USER_AGENT,
                             &format!("rpc-perf/5.0.0-alpha (request; seq:{sequence})"),
-                        )
+                        );
+
+                    if let Some((header, id)) = &correlation {
+                        builder = builder.header(*header, id.as_str());
+                    }
+
+                    builder
                         .body(Empty::<Bytes>::new())
                         .expect("failed to build request")
                 }
-                _ => {
-                    REQUEST_UNSUPPORTED.increment();
+                other => {
+                    unsupported(&config, other);
                     sender = Some(s);
                     continue;
                 }
@@ -203,13 +223,29 @@ async fn task(
 
         // send request
         let start = Instant::now();
+        let _ = REQUEST_BUILD_LATENCY.increment(start.duration_since(build).as_nanos() as u64);
         let response = timeout(
             config.client().unwrap().request_timeout(),
             s.send_request(request),
         )
         .await;
+        stats.request();
         let stop = Instant::now();
 
+        let latency = stop.duration_since(start).as_nanos() as u64;
+
+        processing_delay(&config).await;
+        think_time(&config, latency).await;
+
+        log_access(
+            &context,
+            &response
+                .as_ref()
+                .map_err(|_| ResponseError::Timeout)
+                .and_then(|response| response.as_ref().map_err(|_| ResponseError::Exception)),
+            latency,
+        );
+
         match response {
             Ok(Ok(response)) => {
                 // validate response
@@ -230,10 +266,16 @@ async fn task(
                 }
 
                 RESPONSE_OK.increment();
+                stats.ok(latency);
 
-                let latency = stop.duration_since(start).as_nanos() as u64;
+                histograms.record_success(&config, latency);
 
-                let _ = RESPONSE_LATENCY.increment(latency);
+                if let Some(name) = config.client().unwrap().server_latency_header() {
+                    record_server_latency(
+                        &config,
+                        response.headers().get(name).map(|value| value.as_bytes()),
+                    );
+                }
 
                 if let Some(header) = response
                     .headers()
@@ -244,7 +286,9 @@ async fn task(
                     }
                 }
             }
-            Ok(Err(_e)) => {
+            Ok(Err(e)) => {
+                log_error(&context, e);
+
                 // record execption
                 match work_item {
                     WorkItem::Request { request, .. } => match request {
@@ -265,7 +309,10 @@ async fn task(
                 continue;
             }
             Err(_) => {
+                log_error(&context, ResponseError::Timeout);
+
                 RESPONSE_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency);
                 SESSION_CLOSED_CLIENT.increment();
                 continue;
             }
@@ -22,6 +22,15 @@ pub fn validate_response(response: Response) -> std::result::Result<(), ()> {
             } else {
                 RESPONSE_HIT.increment();
                 GET_KEY_HIT.increment();
+
+                let bytes: usize = values
+                    .values()
+                    .iter()
+                    .filter_map(|value| value.value())
+                    .map(|value| value.len())
+                    .sum();
+
+                BYTES_READ.add(bytes as u64);
             }
             Ok(())
         }
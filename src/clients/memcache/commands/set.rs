@@ -5,6 +5,12 @@ use super::*;
 impl From<&workload::client::Set> for RequestWithValidator {
     fn from(other: &workload::client::Set) -> Self {
         SET.increment();
+        if other.value.is_empty() {
+            SET_EMPTY.increment();
+        }
+        let key_len = other.key.len();
+        let value_len = other.value.len();
+        let logical_len = other.logical_len;
         RequestWithValidator {
             request: Request::set(
                 (*other.key).to_owned().into_boxed_slice(),
@@ -13,15 +19,23 @@ impl From<&workload::client::Set> for RequestWithValidator {
                 Ttl::none(),
                 false,
             ),
-            validator: Box::new(validate_response),
+            validator: Box::new(move |response| {
+                validate_response(response, key_len, value_len, logical_len)
+            }),
         }
     }
 }
 
-pub fn validate_response(response: Response) -> std::result::Result<(), ()> {
+pub fn validate_response(
+    response: Response,
+    key_len: usize,
+    value_len: usize,
+    logical_len: usize,
+) -> std::result::Result<(), ()> {
     match response {
         Response::Stored(_) => {
             SET_STORED.increment();
+            record_set_bytes(key_len, value_len, logical_len);
             Ok(())
         }
         Response::NotStored(_) => {
@@ -34,3 +48,24 @@ pub fn validate_response(response: Response) -> std::result::Result<(), ()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_length_value() {
+        let empty = SET_EMPTY.value();
+
+        let set = workload::client::Set {
+            key: b"key".to_vec().into(),
+            value: Vec::new(),
+            ttl: None,
+            logical_len: 0,
+        };
+
+        let _request = RequestWithValidator::from(&set);
+
+        assert!(SET_EMPTY.value() > empty);
+    }
+}
@@ -40,8 +40,25 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
     let mut read_buffer = Buffer::new(client_config.read_buffer_size());
     let mut write_buffer = Buffer::new(client_config.write_buffer_size());
 
+    let mut reconnect = false;
+    let mut breaker = CircuitBreaker::new(&config);
+    let mut lifetime = ConnectionLifetime::new(&config);
+    let mut written_keys = WrittenKeys::new(&config);
+    let stats = ConnectionStats::new(&endpoint);
+
+    start_jitter(&config).await;
+
     while RUNNING.load(Ordering::Relaxed) {
+        breaker.wait().await;
+
+        lifetime.recycle(&mut stream);
+
         if stream.is_none() {
+            if reconnect {
+                reconnect_ratelimit().await;
+            }
+            reconnect = true;
+
             CONNECT.increment();
             stream = match timeout(
                 client_config.connect_timeout(),
@@ -52,15 +69,19 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                 Ok(Ok(s)) => {
                     CONNECT_OK.increment();
                     CONNECT_CURR.increment();
+                    lifetime.opened();
+                    stats.connected();
                     Some(s)
                 }
                 Ok(Err(_)) => {
                     CONNECT_EX.increment();
+                    breaker.record(false);
                     sleep(Duration::from_millis(100)).await;
                     continue;
                 }
                 Err(_) => {
                     CONNECT_TIMEOUT.increment();
+                    breaker.record(false);
                     sleep(Duration::from_millis(100)).await;
                     continue;
                 }
@@ -69,10 +90,9 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
         let mut s = stream.take().unwrap();
 
-        let work_item = work_receiver
-            .recv()
-            .await
-            .map_err(|_| Error::new(ErrorKind::Other, "channel closed"))?;
+        let (work_item, _in_flight) = recv_work(&work_receiver, &config).await?;
+        let histograms = ResponseHistograms::new(&work_item);
+        let context = error_context(&config, &work_item);
 
         REQUEST.increment();
 
@@ -82,10 +102,14 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
             continue;
         }
 
+        let build = Instant::now();
         let request = RequestWithValidator::try_from(&work_item);
 
         // skip unsupported work items
         if request.is_err() {
+            if let WorkItem::Request { request, .. } = &work_item {
+                unsupported(&config, request);
+            }
             stream = Some(s);
             continue;
         }
@@ -94,10 +118,23 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
         // compose request
         REQUEST_OK.increment();
+        lifetime.request();
+        stats.request();
         request.request.compose(&mut write_buffer);
 
         // send request
         let start = Instant::now();
+        let _ = REQUEST_BUILD_LATENCY.increment(start.duration_since(build).as_nanos() as u64);
+
+        // the lock is held until the response has been read
+        let _lock = match &work_item {
+            WorkItem::Request {
+                request: ClientRequest::Set(r),
+                ..
+            } => key_lock(&config, &r.key).await,
+            _ => None,
+        };
+
         s.write_all(write_buffer.borrow()).await?;
 
         // clear the buffers
@@ -105,6 +142,7 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
         read_buffer.clear();
 
         // read until response or timeout
+        let mut response_len = 0;
         let response = loop {
             let remaining_time = client_config
                 .request_timeout()
@@ -129,6 +167,8 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                             let consumed = resp.consumed();
                             let resp = resp.into_inner();
 
+                            response_len = consumed;
+
                             read_buffer.advance(consumed);
 
                             break Ok(resp);
@@ -152,20 +192,56 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
         let stop = Instant::now();
 
+        let latency_ns = stop.duration_since(start).as_nanos() as u64;
+
+        processing_delay(&config).await;
+        think_time(&config, latency_ns).await;
+
+        if let Err(e) = &response {
+            log_error(&context, e);
+        }
+
+        log_access(&context, &response, latency_ns);
+
         match response {
             Ok(response) => {
-                let latency_ns = stop.duration_since(start).as_nanos() as u64;
+                let size = value_size(&work_item, &response, response_len);
+                let stored = matches!(response, Response::Stored(_));
 
                 // check if the response is valid
                 if (request.validator)(response).is_err() {
                     // increment error stats, connection will be dropped
+                    log_error(&context, ResponseError::Exception);
                     RESPONSE_EX.increment();
+                    let _ = RESPONSE_EX_LATENCY.increment(latency_ns);
+                    breaker.record(false);
                     CONNECT_CURR.increment();
                 } else {
                     // increment success stats and latency
                     RESPONSE_OK.increment();
+                    stats.ok(latency_ns);
+                    breaker.record(true);
+
+                    histograms.record_success(&config, latency_ns);
+
+                    if let Some(size) = size {
+                        SizeBuckets::record(size, latency_ns);
+                    }
 
-                    let _ = RESPONSE_LATENCY.increment(latency_ns);
+                    // an add which is not stored found the key present, and a
+                    // replace which is not stored found it absent
+                    if let WorkItem::Request { request, .. } = &work_item {
+                        match request {
+                            ClientRequest::Set(r) if stored => written_keys.written(r.key.clone()),
+                            ClientRequest::Add(r) => written_keys.present(r.key.clone()),
+                            ClientRequest::Replace(r) if stored => {
+                                written_keys.present(r.key.clone())
+                            }
+                            ClientRequest::Replace(r) => written_keys.removed(&r.key),
+                            ClientRequest::Delete(r) => written_keys.removed(&r.key),
+                            _ => {}
+                        }
+                    }
 
                     // preserve the connection for the next request
                     stream = Some(s);
@@ -177,11 +253,22 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
                 // increment error stats and allow connection to be dropped
                 RESPONSE_EX.increment();
+                let _ = RESPONSE_EX_LATENCY.increment(latency_ns);
+                breaker.record(false);
                 CONNECT_CURR.decrement();
             }
             Err(ResponseError::Timeout) => {
                 // increment error stats and allow connection to be dropped
                 RESPONSE_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency_ns);
+                breaker.record(false);
+                CONNECT_CURR.decrement();
+            }
+            Err(ResponseError::Unauthorized) => {
+                // increment error stats and allow connection to be dropped
+                RESPONSE_EX.increment();
+                let _ = RESPONSE_EX_LATENCY.increment(latency_ns);
+                breaker.record(false);
                 CONNECT_CURR.decrement();
             }
             Err(ResponseError::Ratelimited) | Err(ResponseError::BackendTimeout) => {
@@ -193,6 +280,34 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
     Ok(())
 }
 
+/// Returns the value size used to bucket the latency of a request. Writes use
+/// the length of the value sent and hits use the length of the response, as
+/// the value is not separated from the response here. Misses are not bucketed.
+fn value_size(work_item: &WorkItem, response: &Response, response_len: usize) -> Option<usize> {
+    match work_item {
+        WorkItem::Request {
+            request: ClientRequest::Set(r),
+            ..
+        } => Some(r.value.len()),
+        WorkItem::Request {
+            request: ClientRequest::Add(r),
+            ..
+        } => Some(r.value.len()),
+        WorkItem::Request {
+            request: ClientRequest::Replace(r),
+            ..
+        } => Some(r.value.len()),
+        WorkItem::Request {
+            request: ClientRequest::Get(_),
+            ..
+        } => match response {
+            Response::Values(values) if !values.values().is_empty() => Some(response_len),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 impl From<&workload::client::Delete> for Request {
     fn from(other: &workload::client::Delete) -> Self {
         DELETE.increment();
@@ -1,35 +1,106 @@
 use crate::workload::ClientRequest;
 use crate::workload::ClientWorkItem as WorkItem;
+use crate::workload::{BACKGROUND_ACTIVE, BACKGROUND_ENABLED, ISOLATION_ENABLED};
 use crate::*;
+use config::{CompatibilityCheck, ProcessingDelay, Verb};
 
 use ::momento::{MomentoError, MomentoErrorCode};
-use async_channel::Receiver;
+use async_channel::{Receiver, TryRecvError};
+use hyper::header::HeaderName;
+use metriken::AtomicHistogram;
+use rand::Rng;
+use ratelimit::Ratelimiter;
 use tokio::io::*;
 use tokio::runtime::Runtime;
 use tokio::time::{timeout, Duration};
 
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
 use std::io::{Error, ErrorKind, Result};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Mutex, OnceLock};
 use std::time::Instant;
+use tokio::sync::watch;
 
 mod http1;
 mod http2;
 mod memcache;
 mod momento;
+mod null;
 mod ping;
 mod redis;
 
+/// Limits the rate of reconnects across all client tasks, if configured.
+static RECONNECT_RATELIMIT: OnceLock<Ratelimiter> = OnceLock::new();
+
+/// The result of a get, which is `true` if the key was found.
+type GetResult = std::result::Result<bool, ResponseError>;
+
+/// The gets which are currently in flight, by key. Used to coalesce concurrent
+/// gets for the same key into a single backend request.
+static IN_FLIGHT_GETS: Lazy<Mutex<HashMap<Arc<[u8]>, watch::Receiver<Option<GetResult>>>>> =
+    Lazy::new(Default::default);
+
+// the number of shards in the keyed mutex used to serialize sets for each key
+const KEY_LOCK_SHARDS: usize = 1024;
+
+/// A sharded mutex used to serialize concurrent sets for the same key. Keys
+/// which hash to the same shard are also serialized with each other.
+static KEY_LOCKS: Lazy<Vec<tokio::sync::Mutex<()>>> = Lazy::new(|| {
+    (0..KEY_LOCK_SHARDS)
+        .map(|_| tokio::sync::Mutex::new(()))
+        .collect()
+});
+
 pub fn launch_clients(config: &Config, work_receiver: Receiver<WorkItem>) -> Option<Runtime> {
     debug!("Launching clients...");
 
     config.client()?;
 
+    // each trial and attempt launches its own clients, which may stop on an
+    // error again and report their own connections and error rollups. The
+    // written keys are kept, as the keys remain in the backend.
+    STOPPED_ON_ERROR.store(false, Ordering::Relaxed);
+    CONNECTIONS.lock().unwrap().clear();
+    ENDPOINT_LATENCY.lock().unwrap().clear();
+    ERROR_ROLLUP.lock().unwrap().clear();
+
+    if let Some(rate) = config.client().unwrap().max_reconnect_rate() {
+        let rate = rate.get();
+        let amount = (rate as f64 / 1_000_000.0).ceil() as u64;
+
+        // even though we might not have nanosecond level clock resolution,
+        // by using a nanosecond level duration, we achieve more accurate
+        // ratelimits.
+        let interval = Duration::from_nanos(1_000_000_000 / (rate / amount));
+
+        let _ = RECONNECT_RATELIMIT.set(
+            Ratelimiter::builder(amount, interval)
+                .max_tokens(amount)
+                .build()
+                .expect("failed to initialize reconnect ratelimiter"),
+        );
+    }
+
     // spawn the request drivers on their own runtime
     let mut client_rt = Builder::new_multi_thread()
         .enable_all()
         .worker_threads(config.client().unwrap().threads())
+        .on_thread_start(thread_start)
         .build()
         .expect("failed to initialize tokio runtime");
 
+    if let Some(interval) = config.debug().error_rollup_interval() {
+        if interval.is_zero() {
+            eprintln!("debug `error_rollup_interval` must be greater than zero");
+            std::process::exit(2);
+        }
+
+        client_rt.spawn(error_rollups(interval));
+    }
+
     match config.general().protocol() {
         Protocol::Http1 => {
             clients::http1::launch_tasks(&mut client_rt, config.clone(), work_receiver)
@@ -43,6 +114,9 @@ pub fn launch_clients(config: &Config, work_receiver: Receiver<WorkItem>) -> Opt
         Protocol::Momento => {
             clients::momento::launch_tasks(&mut client_rt, config.clone(), work_receiver)
         }
+        Protocol::Null => {
+            clients::null::launch_tasks(&mut client_rt, config.clone(), work_receiver)
+        }
         Protocol::Ping => {
             clients::ping::launch_tasks(&mut client_rt, config.clone(), work_receiver)
         }
@@ -62,6 +136,1092 @@ pub fn launch_clients(config: &Config, work_receiver: Receiver<WorkItem>) -> Opt
     Some(client_rt)
 }
 
+/// Returns the number of client tasks which will be launched for the config.
+/// Each task has at most one outstanding request.
+pub fn client_tasks(config: &Config) -> usize {
+    let Some(client_config) = config.client() else {
+        return 0;
+    };
+
+    let endpoints = config.target().endpoints().len();
+
+    match config.general().protocol() {
+        Protocol::Momento | Protocol::Null => {
+            client_config.poolsize() * client_config.concurrency()
+        }
+        Protocol::Http2 => endpoints * client_config.poolsize() * client_config.concurrency(),
+        _ => endpoints * client_config.poolsize(),
+    }
+}
+
+/// Waits until a reconnect is allowed by the maximum reconnect rate. Should
+/// only be called before re-establishing a connection, the initial connection
+/// for each task is not limited.
+async fn reconnect_ratelimit() {
+    if let Some(ratelimiter) = RECONNECT_RATELIMIT.get() {
+        let mut throttled = false;
+
+        while let Err(delay) = ratelimiter.try_wait() {
+            if !throttled {
+                RECONNECT_THROTTLED.increment();
+                throttled = true;
+            }
+
+            sleep(delay).await;
+        }
+    }
+}
+
+/// Delays the start of a client task by a random phase offset, if configured,
+/// so that the tasks do not issue their requests in lockstep.
+async fn start_jitter(config: &Config) {
+    if let Some(jitter) = config.client().unwrap().start_jitter() {
+        let offset = rand::thread_rng().gen_range(0.0..1.0);
+        sleep(jitter.mul_f64(offset)).await;
+    }
+}
+
+/// Receive the next work item, recording the time spent waiting for work. If
+/// an idle backoff is configured, an empty queue is polled with a jittered
+/// delay rather than awaiting the next item.
+async fn recv_work(
+    work_receiver: &Receiver<WorkItem>,
+    config: &Config,
+) -> Result<(WorkItem, InFlightBytes)> {
+    let start = Instant::now();
+
+    let work_item = if let Some(backoff) = config.client().unwrap().idle_backoff() {
+        loop {
+            match work_receiver.try_recv() {
+                Ok(work_item) => break work_item,
+                Err(TryRecvError::Empty) => {
+                    // jitter the backoff by +/- 50% to spread out the tasks
+                    let jitter = rand::thread_rng().gen_range(0.5..1.5);
+                    sleep(backoff.mul_f64(jitter)).await;
+                }
+                Err(TryRecvError::Closed) => {
+                    return Err(channel_closed(config));
+                }
+            }
+        }
+    } else {
+        work_receiver
+            .recv()
+            .await
+            .map_err(|_| channel_closed(config))?
+    };
+
+    IDLE_TIME.add(start.elapsed().as_nanos() as u64);
+
+    let in_flight = dequeued(&work_item);
+
+    Ok((work_item, in_flight))
+}
+
+/// Accounts for a work item once it has been taken from the queue.
+fn dequeued(work_item: &WorkItem) -> InFlightBytes {
+    count_operation(work_item);
+
+    InFlightBytes(work_item.buffered_len())
+}
+
+/// The payload bytes of a request which has been taken from the queue. They
+/// remain part of the buffered bytes until the request completes and this is
+/// dropped.
+struct InFlightBytes(usize);
+
+impl Drop for InFlightBytes {
+    fn drop(&mut self) {
+        workload::release_buffered(self.0);
+    }
+}
+
+/// Counts the request by its operation family, using the same families as the
+/// per-operation latencies.
+fn count_operation(work_item: &WorkItem) {
+    if let WorkItem::Request { request, .. } = work_item {
+        match request {
+            ClientRequest::Get(_) | ClientRequest::ChunkedGet(_) => {
+                REQUEST_GET.increment();
+            }
+            ClientRequest::Set(_)
+            | ClientRequest::Add(_)
+            | ClientRequest::Replace(_)
+            | ClientRequest::ChunkedSet(_) => {
+                REQUEST_SET.increment();
+            }
+            ClientRequest::Delete(_) | ClientRequest::ChunkedDelete(_) => {
+                REQUEST_DELETE.increment();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Issues the get, unless get coalescing is enabled and a get for the same key
+/// is already in flight. In that case no request is sent and the result of the
+/// in-flight get is shared.
+async fn coalesce_get<F>(config: &Config, key: &Arc<[u8]>, get: F) -> GetResult
+where
+    F: Future<Output = GetResult>,
+{
+    if !config.client().unwrap().coalesce_gets() {
+        return get.await;
+    }
+
+    let leader = {
+        let mut in_flight = IN_FLIGHT_GETS.lock().unwrap();
+
+        match in_flight.get(key) {
+            Some(receiver) => Err(receiver.clone()),
+            None => {
+                let (sender, receiver) = watch::channel(None);
+                in_flight.insert(key.clone(), receiver);
+                Ok(sender)
+            }
+        }
+    };
+
+    match leader {
+        Ok(sender) => {
+            let in_flight = InFlightGet { key };
+
+            let result = get.await;
+
+            drop(in_flight);
+            let _ = sender.send(Some(result));
+
+            result
+        }
+        Err(mut receiver) => {
+            COALESCED.increment();
+
+            // the follower is still a get, even though it sends no request
+            GET.increment();
+
+            let result = match receiver.wait_for(|result| result.is_some()).await {
+                Ok(result) => (*result).unwrap_or(Err(ResponseError::Exception)),
+                // the in-flight get was dropped before it completed
+                Err(_) => Err(ResponseError::Exception),
+            };
+
+            match result {
+                Ok(_) => GET_OK.increment(),
+                Err(ResponseError::Timeout) => GET_TIMEOUT.increment(),
+                Err(_) => GET_EX.increment(),
+            };
+
+            result
+        }
+    }
+}
+
+/// Records a get which was served from the client-side cache. No request is
+/// sent to the backend, but the get still completes as a hit. Its latency is
+/// not recorded.
+fn client_cache_get() -> std::result::Result<(), ResponseError> {
+    GET.increment();
+    GET_OK.increment();
+    GET_KEY_HIT.increment();
+    RESPONSE_HIT.increment();
+    Ok(())
+}
+
+/// Removes the in-flight entry of a coalesced get once the leading get
+/// completes or is cancelled, so that later gets for the key are not coalesced
+/// onto a get which will never complete.
+struct InFlightGet<'a> {
+    key: &'a Arc<[u8]>,
+}
+
+impl Drop for InFlightGet<'_> {
+    fn drop(&mut self) {
+        IN_FLIGHT_GETS.lock().unwrap().remove(self.key);
+    }
+}
+
+/// Acquires the lock for the key if per-key serialization of sets is enabled.
+/// The lock is held until the returned guard is dropped.
+async fn key_lock(config: &Config, key: &[u8]) -> Option<tokio::sync::MutexGuard<'static, ()>> {
+    if !config.client().unwrap().per_key_serialize() {
+        return None;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    let lock = &KEY_LOCKS[hasher.finish() as usize % KEY_LOCK_SHARDS];
+
+    match lock.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(_) => {
+            KEY_LOCK_CONTENDED.increment();
+            Some(lock.lock().await)
+        }
+    }
+}
+
+/// Simulates client-side processing of a response, such as deserialization or
+/// application logic, before the task issues its next request. A spin delay
+/// occupies the runtime thread, while a sleep yields it to other tasks.
+async fn processing_delay(config: &Config) {
+    let client_config = config.client().unwrap();
+
+    let Some(delay) = client_config.processing_delay() else {
+        return;
+    };
+
+    match client_config.processing_delay_kind() {
+        ProcessingDelay::Sleep => sleep(delay).await,
+        ProcessingDelay::Spin => {
+            let start = Instant::now();
+
+            while start.elapsed() < delay {
+                std::hint::spin_loop();
+            }
+        }
+    }
+}
+
+/// Waits for a time proportional to the latency of the response, if configured,
+/// before the task issues its next request. The wait is jittered to avoid the
+/// tasks synchronizing, so a slow backend receives fewer requests from
+/// each task, much like a well-behaved client backing off.
+async fn think_time(config: &Config, latency_ns: u64) {
+    let client_config = config.client().unwrap();
+
+    let Some(factor) = client_config.think_time_factor() else {
+        return;
+    };
+
+    let jitter = rand::thread_rng().gen_range(0.5..1.5);
+
+    let mut think = Duration::from_nanos(latency_ns).mul_f64(factor * jitter);
+
+    if let Some(max) = client_config.think_time_max() {
+        think = std::cmp::min(think, max);
+    }
+
+    sleep(think).await;
+}
+
+/// Records the latency of a successful response. Latencies beyond the range of
+/// the histogram are counted as overflows rather than being dropped silently.
+fn record_response_latency(latency_ns: u64) {
+    if RESPONSE_LATENCY.increment(latency_ns).is_err() {
+        LATENCY_OVERFLOW.increment();
+    }
+}
+
+/// The histograms which the latency of a successful response is recorded in
+/// alongside the combined response latency. These are selected before the
+/// work item is consumed by sending the request.
+struct ResponseHistograms([Option<&'static AtomicHistogram>; 4]);
+
+impl ResponseHistograms {
+    fn new(work_item: &WorkItem) -> Self {
+        Self([
+            operation_latency(work_item),
+            background_latency(work_item),
+            burst_latency(work_item),
+            isolation_latency(work_item),
+        ])
+    }
+
+    /// Records the latency of a successful response.
+    fn record_success(&self, config: &Config, latency_ns: u64) {
+        record_response_latency(latency_ns);
+
+        for histogram in self.0.iter().flatten() {
+            let _ = histogram.increment(latency_ns);
+        }
+
+        record_near_timeout(config, latency_ns);
+    }
+}
+
+/// Returns the per-operation latency histogram for the work item, if its
+/// operation family has one. These are recorded alongside the combined
+/// response latency.
+fn operation_latency(work_item: &WorkItem) -> Option<&'static AtomicHistogram> {
+    match work_item {
+        WorkItem::Request { request, .. } => match request {
+            ClientRequest::Get(_) | ClientRequest::ChunkedGet(_) => Some(&*GET_LATENCY),
+            ClientRequest::Set(_)
+            | ClientRequest::Add(_)
+            | ClientRequest::Replace(_)
+            | ClientRequest::ChunkedSet(_) => Some(&*SET_LATENCY),
+            ClientRequest::Delete(_) | ClientRequest::ChunkedDelete(_) => Some(&*DELETE_LATENCY),
+            ClientRequest::ChurnWrite(_) => Some(&*CHURN_LATENCY),
+            ClientRequest::DeleteIf(_) => Some(&*DELETE_IF_LATENCY),
+            ClientRequest::Touch(_) => Some(&*TOUCH_LATENCY),
+            _ => None,
+        },
+        WorkItem::Reconnect => None,
+    }
+}
+
+/// Returns the histogram for the first request of a burst, after the idle
+/// period of the burst duty cycle.
+fn burst_latency(work_item: &WorkItem) -> Option<&'static AtomicHistogram> {
+    match work_item {
+        WorkItem::Request {
+            burst_start: true, ..
+        } => Some(&*BURST_START_LATENCY),
+        _ => None,
+    }
+}
+
+/// Returns the histogram which separates the latency of the commands which are
+/// isolated from the latency of all other requests. This is `None` unless a
+/// command is isolated.
+fn isolation_latency(work_item: &WorkItem) -> Option<&'static AtomicHistogram> {
+    if !ISOLATION_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    match work_item {
+        WorkItem::Request { isolated: true, .. } => Some(&*ISOLATED_LATENCY),
+        WorkItem::Request { .. } => Some(&*NON_ISOLATED_LATENCY),
+        WorkItem::Reconnect => None,
+    }
+}
+
+/// Returns the histogram which separates the latency of background requests
+/// from foreground requests issued while the background load is on or off.
+/// This is `None` unless a background keyspace is configured.
+fn background_latency(work_item: &WorkItem) -> Option<&'static AtomicHistogram> {
+    if !BACKGROUND_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    match work_item {
+        WorkItem::Request {
+            background: true, ..
+        } => Some(&*BACKGROUND_LATENCY),
+        WorkItem::Request { .. } if BACKGROUND_ACTIVE.load(Ordering::Relaxed) => {
+            Some(&*FOREGROUND_LATENCY_BACKGROUND_ON)
+        }
+        WorkItem::Request { .. } => Some(&*FOREGROUND_LATENCY_BACKGROUND_OFF),
+        WorkItem::Reconnect => None,
+    }
+}
+
+/// Returns `true` if the client for the protocol implements the verb. Requests
+/// for any other verb are counted as unsupported.
+fn supports_verb(protocol: Protocol, verb: Verb) -> bool {
+    match protocol {
+        Protocol::Http1 | Protocol::Http2 => verb == Verb::Get,
+        Protocol::Memcache => matches!(
+            verb,
+            Verb::Add | Verb::Delete | Verb::Get | Verb::Replace | Verb::Set
+        ),
+        Protocol::Momento => !matches!(
+            verb,
+            Verb::Add | Verb::HashExists | Verb::Ping | Verb::Replace
+        ),
+        Protocol::Null => true,
+        Protocol::Ping => verb == Verb::Ping,
+        Protocol::Resp => !matches!(verb, Verb::ListRemove | Verb::TtlCheck),
+        Protocol::Blabber | Protocol::Kafka => false,
+    }
+}
+
+/// Checks the verbs of the workload against the protocol and, where the
+/// backend reports its version, against the version which introduced the
+/// commands they use. This catches a workload which would only produce
+/// unsupported requests before the run begins.
+pub fn check_compatibility(config: &Config, runtime: &Runtime) {
+    let Some(client_config) = config.client() else {
+        return;
+    };
+
+    let mode = client_config.compatibility_check();
+
+    if mode == CompatibilityCheck::Off {
+        return;
+    }
+
+    let protocol = config.general().protocol();
+
+    let verbs: std::collections::BTreeSet<Verb> = config
+        .workload()
+        .keyspaces()
+        .iter()
+        .flat_map(|keyspace| keyspace.commands().iter().map(|command| command.verb()))
+        .collect();
+
+    let mut problems = Vec::new();
+
+    for verb in &verbs {
+        if !supports_verb(protocol, *verb) {
+            problems.push(format!(
+                "verb: {verb:?} is not supported by the {protocol:?} protocol"
+            ));
+        }
+    }
+
+    if matches!(protocol, Protocol::Resp) {
+        match runtime.block_on(clients::redis::server_version(config)) {
+            Some(version) => {
+                output!(
+                    "Compatibility: backend version: {}.{}.{}",
+                    version.0,
+                    version.1,
+                    version.2
+                );
+
+                for verb in &verbs {
+                    if let Some(required) = clients::redis::required_version(*verb) {
+                        if version < required {
+                            problems.push(format!(
+                                "verb: {verb:?} requires a backend version >= {}.{}.{}",
+                                required.0, required.1, required.2
+                            ));
+                        }
+                    }
+                }
+            }
+            None => problems.push("could not determine the backend version".to_string()),
+        }
+    }
+
+    if problems.is_empty() {
+        output!("Compatibility: all operations are supported");
+        return;
+    }
+
+    for problem in &problems {
+        output!("WARNING: {problem}");
+        warn!("{problem}");
+    }
+
+    if mode == CompatibilityCheck::Strict {
+        eprintln!("the workload is not compatible with the backend");
+        std::process::exit(2);
+    }
+}
+
+/// Records a request for an operation which the protocol does not support.
+/// The request is skipped unless `strict_operations` is set, in which case the
+/// workload does not match the backend and the run is aborted.
+fn unsupported(config: &Config, request: &ClientRequest) {
+    REQUEST_UNSUPPORTED.increment();
+
+    if config.client().unwrap().strict_operations() {
+        eprintln!(
+            "the workload generated a `{}` request, which is not supported by the {:?} protocol",
+            request.name(),
+            config.general().protocol()
+        );
+        std::process::exit(2);
+    }
+}
+
+/// Counts a successful response as a near timeout if its latency was within
+/// the configured band below the request timeout.
+fn record_near_timeout(config: &Config, latency_ns: u64) {
+    let threshold = config.client().unwrap().near_timeout_threshold();
+
+    if latency_ns >= threshold.as_nanos() as u64 {
+        RESPONSE_NEAR_TIMEOUT.increment();
+    }
+}
+
+/// A per-task circuit breaker. After the configured number of consecutive
+/// failures the breaker opens and the task pauses for the cooldown. The next
+/// request acts as a probe, a single failure will re-open the breaker while a
+/// success closes it.
+struct CircuitBreaker {
+    threshold: Option<u32>,
+    cooldown: Duration,
+    failures: u32,
+}
+
+impl CircuitBreaker {
+    fn new(config: &Config) -> Self {
+        let client_config = config.client().unwrap();
+
+        Self {
+            threshold: client_config.circuit_breaker_threshold(),
+            cooldown: client_config.circuit_breaker_cooldown(),
+            failures: 0,
+        }
+    }
+
+    /// Waits out the cooldown if the breaker is open.
+    async fn wait(&mut self) {
+        let Some(threshold) = self.threshold else {
+            return;
+        };
+
+        if self.failures < threshold {
+            return;
+        }
+
+        CIRCUIT_OPEN.increment();
+
+        let start = Instant::now();
+        sleep(self.cooldown).await;
+        CIRCUIT_OPEN_TIME.add(start.elapsed().as_nanos() as u64);
+
+        // half-open, the next failure will open the breaker again
+        self.failures = threshold - 1;
+    }
+
+    fn record(&mut self, success: bool) {
+        if success {
+            self.failures = 0;
+        } else {
+            self.failures = self.failures.saturating_add(1);
+        }
+    }
+}
+
+/// The counters for every connection, in the order the tasks were started.
+static CONNECTIONS: Lazy<Mutex<Vec<Arc<ConnectionStats>>>> = Lazy::new(Default::default);
+
+/// The response latencies for each endpoint, shared by all of the connections
+/// to the endpoint.
+static ENDPOINT_LATENCY: Lazy<Mutex<HashMap<String, Arc<AtomicHistogram>>>> =
+    Lazy::new(Default::default);
+
+/// Counts the requests and successful responses for the connection owned by a
+/// client task. The connection is identified by its endpoint and task, and the
+/// counts carry over when the task reconnects.
+struct ConnectionStats {
+    endpoint: String,
+    id: usize,
+    connects: AtomicU64,
+    requests: AtomicU64,
+    ok: AtomicU64,
+    latency: Arc<AtomicHistogram>,
+}
+
+impl ConnectionStats {
+    fn new(endpoint: &str) -> Arc<Self> {
+        let latency = ENDPOINT_LATENCY
+            .lock()
+            .unwrap()
+            .entry(endpoint.to_string())
+            .or_insert_with(|| {
+                Arc::new(AtomicHistogram::new(
+                    histogram_grouping_power(),
+                    histogram_max_value_power(),
+                ))
+            })
+            .clone();
+
+        let mut connections = CONNECTIONS.lock().unwrap();
+
+        let stats = Arc::new(Self {
+            endpoint: endpoint.to_string(),
+            id: connections.len(),
+            connects: AtomicU64::new(0),
+            requests: AtomicU64::new(0),
+            ok: AtomicU64::new(0),
+            latency,
+        });
+
+        connections.push(stats.clone());
+
+        stats
+    }
+
+    fn connected(&self) {
+        self.connects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn ok(&self, latency_ns: u64) {
+        self.ok.fetch_add(1, Ordering::Relaxed);
+        let _ = self.latency.increment(latency_ns);
+    }
+}
+
+/// Returns the endpoint, id, connects, requests, and errors for every
+/// connection. Requests without a successful response are counted as errors.
+pub fn connection_stats() -> Vec<(String, usize, u64, u64, u64)> {
+    CONNECTIONS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|stats| {
+            let requests = stats.requests.load(Ordering::Relaxed);
+            let ok = stats.ok.load(Ordering::Relaxed);
+
+            (
+                stats.endpoint.clone(),
+                stats.id,
+                stats.connects.load(Ordering::Relaxed),
+                requests,
+                requests.saturating_sub(ok),
+            )
+        })
+        .collect()
+}
+
+/// Returns the requests, errors, and response latencies for every endpoint,
+/// combining all of the connections to each endpoint.
+pub fn endpoint_stats() -> Vec<(String, u64, u64, Option<histogram::Histogram>)> {
+    let mut totals: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+
+    for (endpoint, _, _, requests, errors) in connection_stats() {
+        let total = totals.entry(endpoint).or_default();
+        total.0 += requests;
+        total.1 += errors;
+    }
+
+    let latency = ENDPOINT_LATENCY.lock().unwrap();
+
+    totals
+        .into_iter()
+        .map(|(endpoint, (requests, errors))| {
+            let histogram = latency.get(&endpoint).and_then(|h| h.load());
+            (endpoint, requests, errors, histogram)
+        })
+        .collect()
+}
+
+/// A per-task LRU cache of the keys which the task has recently read, which
+/// models a client-side read cache. Only the presence of a key is tracked.
+struct ClientCache {
+    capacity: usize,
+    populate_on_set: bool,
+    // the last use of each key, and the keys ordered by their last use
+    keys: HashMap<Arc<[u8]>, u64>,
+    order: BTreeMap<u64, Arc<[u8]>>,
+    tick: u64,
+}
+
+impl ClientCache {
+    fn new(config: &Config) -> Self {
+        let client_config = config.client().unwrap();
+
+        Self {
+            capacity: client_config.client_cache_size().unwrap_or(0),
+            populate_on_set: client_config.client_cache_populate_on_set(),
+            keys: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Returns true if the key is in the cache, marking it as recently used.
+    /// The outcome is counted when the cache is enabled.
+    fn get(&mut self, key: &Arc<[u8]>) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+
+        if self.keys.contains_key(key) {
+            CLIENT_CACHE_HIT.increment();
+            self.insert(key.clone());
+            true
+        } else {
+            CLIENT_CACHE_MISS.increment();
+            false
+        }
+    }
+
+    /// Adds the key to the cache, evicting the least recently used key if the
+    /// cache is full.
+    fn insert(&mut self, key: Arc<[u8]>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.tick += 1;
+
+        if let Some(previous) = self.keys.insert(key.clone(), self.tick) {
+            self.order.remove(&previous);
+        } else if self.keys.len() > self.capacity {
+            if let Some((_, evicted)) = self.order.pop_first() {
+                self.keys.remove(&evicted);
+            }
+        }
+
+        self.order.insert(self.tick, key);
+    }
+
+    /// Records a successful write to the key, which either populates the cache
+    /// or invalidates the key.
+    fn written(&mut self, key: Arc<[u8]>) {
+        if self.populate_on_set {
+            self.insert(key);
+        } else {
+            self.invalidate(&key);
+        }
+    }
+
+    fn invalidate(&mut self, key: &Arc<[u8]>) {
+        if let Some(previous) = self.keys.remove(key) {
+            self.order.remove(&previous);
+        }
+    }
+}
+
+// the keys which have been written by any task, shared so that there is one
+// entry per key in the keyspaces rather than one per key for every task. They
+// carry over between trials, as the keys remain in the backend.
+static WRITTEN_KEYS: Lazy<Mutex<HashSet<Arc<[u8]>>>> = Lazy::new(Default::default);
+
+/// The keys which have been successfully written, used to classify each set
+/// as creating or overwriting a key when the backend does not report it.
+struct WrittenKeys {
+    enabled: bool,
+}
+
+impl WrittenKeys {
+    fn new(config: &Config) -> Self {
+        Self {
+            enabled: config.client().unwrap().classify_writes(),
+        }
+    }
+
+    /// Records a successful set of the key and counts whether it created the
+    /// key.
+    fn written(&mut self, key: Arc<[u8]>) {
+        if !self.enabled {
+            return;
+        }
+
+        if WRITTEN_KEYS.lock().unwrap().insert(key) {
+            SET_CREATED.increment();
+        } else {
+            SET_OVERWRITTEN.increment();
+        }
+    }
+
+    /// Records that the key is present without counting a set, such as after
+    /// an add or a successful replace.
+    fn present(&mut self, key: Arc<[u8]>) {
+        if self.enabled {
+            WRITTEN_KEYS.lock().unwrap().insert(key);
+        }
+    }
+
+    /// Records that the key is absent, such as after a delete, so that the
+    /// next set creates it.
+    fn removed(&mut self, key: &Arc<[u8]>) {
+        if self.enabled {
+            WRITTEN_KEYS.lock().unwrap().remove(key);
+        }
+    }
+}
+
+/// Tracks the age and request count of a task's connection so that it can be
+/// recycled once it exceeds the configured limits.
+struct ConnectionLifetime {
+    max_age: Option<Duration>,
+    max_requests: Option<u64>,
+    opened: Instant,
+    requests: u64,
+}
+
+impl ConnectionLifetime {
+    fn new(config: &Config) -> Self {
+        let client_config = config.client().unwrap();
+
+        Self {
+            max_age: client_config.max_connection_age(),
+            max_requests: client_config.max_requests_per_connection(),
+            opened: Instant::now(),
+            requests: 0,
+        }
+    }
+
+    /// Resets the lifetime for a newly opened connection.
+    fn opened(&mut self) {
+        self.opened = Instant::now();
+        self.requests = 0;
+    }
+
+    /// Counts a request sent on the current connection.
+    fn request(&mut self) {
+        self.requests += 1;
+    }
+
+    /// Drops the connection if it has reached its maximum age or request
+    /// count, causing the task to reconnect.
+    fn recycle<T>(&self, connection: &mut Option<T>) {
+        if connection.is_none() {
+            return;
+        }
+
+        let expired = self.max_age.is_some_and(|age| self.opened.elapsed() >= age)
+            || self.max_requests.is_some_and(|max| self.requests >= max);
+
+        if expired {
+            *connection = None;
+            CONNECT_CURR.decrement();
+            CONNECT_RECYCLE.increment();
+        }
+    }
+}
+
+/// Records that a task's work queue has closed. The queue is expected to close
+/// when the run ends, but if it closes while the run is ongoing the task exits
+/// and the run continues with fewer tasks. If too many tasks exit this way, the
+/// run is stopped.
+fn channel_closed(config: &Config) -> Error {
+    if RUNNING.load(Ordering::Relaxed) {
+        CHANNEL_CLOSED.increment();
+        warn!("client task exiting, the work queue closed unexpectedly");
+
+        let tasks = client_tasks(config) as f64;
+        let closed = CHANNEL_CLOSED.value() as f64;
+        let max = config.client().unwrap().max_closed_fraction();
+
+        if tasks > 0.0 && closed / tasks > max {
+            output!(
+                "Stopping: {} of {} client tasks exited because the work queue closed unexpectedly",
+                closed,
+                tasks
+            );
+            RUNNING.store(false, Ordering::Relaxed);
+        }
+    }
+
+    Error::new(ErrorKind::Other, "channel closed")
+}
+
+/// Returns a synthetic error if error injection is enabled in the debug config
+/// and this request has been selected to fail. This is only intended for
+/// validating the metrics and reporting pipeline.
+fn injected_error(config: &Config, work_item: &WorkItem) -> Option<ResponseError> {
+    let debug = config.debug();
+
+    if !debug.inject_errors() || !matches!(work_item, WorkItem::Request { .. }) {
+        return None;
+    }
+
+    let sample: f64 = rand::thread_rng().gen();
+
+    let error = if sample < debug.inject_timeout_rate() {
+        ResponseError::Timeout
+    } else if sample < debug.inject_timeout_rate() + debug.inject_exception_rate() {
+        ResponseError::Exception
+    } else {
+        return None;
+    };
+
+    INJECTED_ERROR.increment();
+
+    Some(error)
+}
+
+// set once the first failed request has been logged when stopping on errors
+static STOPPED_ON_ERROR: AtomicBool = AtomicBool::new(false);
+
+// the length of the error message prefix used to group failures in a rollup
+const ERROR_PREFIX_LEN: usize = 48;
+
+/// The number of failed requests since the last rollup, by operation and error
+/// message prefix.
+static ERROR_ROLLUP: Lazy<Mutex<HashMap<(&'static str, String), u64>>> =
+    Lazy::new(Default::default);
+
+/// A description of a request, captured before it is sent so that it can be
+/// logged if the request fails or if its key is watched.
+struct ErrorContext {
+    operation: &'static str,
+    description: String,
+    stop: bool,
+    rollup: bool,
+    /// The correlation ID sent with the request, if any.
+    correlation_id: Option<String>,
+    /// Whether the key is one of the `watch_keys`, so that the request is
+    /// logged whatever its result.
+    watched: bool,
+    watch_latency: bool,
+}
+
+/// Returns the context used to log the request if it fails. This is `None`
+/// unless `stop_on_error` or `error_rollup_interval` is set in the debug
+/// config, a `correlation_id_header` is set in the client config, or the key
+/// of the request is watched.
+fn error_context(config: &Config, work_item: &WorkItem) -> Option<ErrorContext> {
+    let stop = config.debug().stop_on_error();
+    let rollup = config.debug().error_rollup_interval().is_some();
+    let correlated = config
+        .client()
+        .is_some_and(|client| client.correlation_id_header().is_some());
+
+    if !stop && !rollup && !correlated && !config.debug().watching() {
+        return None;
+    }
+
+    match work_item {
+        WorkItem::Request {
+            request, sequence, ..
+        } => {
+            let watched = request
+                .key()
+                .is_some_and(|key| config.debug().is_watched(key));
+
+            if !stop && !rollup && !correlated && !watched {
+                return None;
+            }
+
+            let key = request
+                .key()
+                .map(|key| String::from_utf8_lossy(key).into_owned())
+                .unwrap_or_default();
+
+            Some(ErrorContext {
+                operation: request.name(),
+                description: format!(
+                    "operation: {} key: {key:?} sequence: {sequence}",
+                    request.name()
+                ),
+                stop,
+                rollup,
+                correlation_id: None,
+                watched,
+                watch_latency: config.debug().watch_latency(),
+            })
+        }
+        WorkItem::Reconnect => None,
+    }
+}
+
+/// Logs the result of a request for a watched key. This is logged for every
+/// request on the key, whatever its result.
+fn log_access<T, E: std::fmt::Debug>(
+    context: &Option<ErrorContext>,
+    result: &std::result::Result<T, E>,
+    latency_ns: u64,
+) {
+    let Some(context) = context.as_ref().filter(|context| context.watched) else {
+        return;
+    };
+
+    let mut message = format!("watched key: {}", context.description);
+
+    if let Some(id) = &context.correlation_id {
+        message.push_str(&format!(" correlation id: {id}"));
+    }
+
+    match result {
+        Ok(_) => message.push_str(" result: ok"),
+        Err(e) => message.push_str(&format!(" result: {e:?}")),
+    }
+
+    if context.watch_latency {
+        message.push_str(&format!(" latency: {latency_ns}ns"));
+    }
+
+    info!("{message}");
+}
+
+/// Logs a failed request. The failure is counted towards the next error
+/// rollup, if enabled. FOR DEBUGGING ONLY: with `stop_on_error`, the run is
+/// stopped on the first failed request, logging the request and the error. Any
+/// requests which fail while the run is stopping are not logged.
+fn log_error(context: &Option<ErrorContext>, error: impl std::fmt::Debug) {
+    let Some(context) = context else {
+        return;
+    };
+
+    if context.rollup {
+        let message: String = format!("{error:?}")
+            .chars()
+            .take(ERROR_PREFIX_LEN)
+            .collect();
+
+        *ERROR_ROLLUP
+            .lock()
+            .unwrap()
+            .entry((context.operation, message))
+            .or_insert(0) += 1;
+    }
+
+    // failures are logged individually only when they are not rolled up
+    if let (Some(id), false) = (&context.correlation_id, context.rollup) {
+        warn!(
+            "request failed: {} correlation id: {id} error: {error:?}",
+            context.description
+        );
+    }
+
+    if !context.stop || STOPPED_ON_ERROR.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    match &context.correlation_id {
+        Some(id) => output!(
+            "Stopping: request failed: {} correlation id: {id} error: {error:?}",
+            context.description
+        ),
+        None => output!(
+            "Stopping: request failed: {} error: {error:?}",
+            context.description
+        ),
+    }
+    RUNNING.store(false, Ordering::Relaxed);
+}
+
+/// Returns a new correlation ID and the header which carries it, if a
+/// `correlation_id_header` is set in the client config. The ID is recorded in
+/// the error context so that it is logged if the request fails.
+fn correlation_id<'a>(
+    config: &'a Config,
+    context: &mut Option<ErrorContext>,
+) -> Option<(&'a HeaderName, String)> {
+    let header = config.client()?.correlation_id_header()?;
+
+    let id = format!("{:016x}", rand::thread_rng().gen::<u64>());
+
+    if let Some(context) = context {
+        context.correlation_id = Some(id.clone());
+    }
+
+    Some((header, id))
+}
+
+/// Logs the failed requests counted since the previous rollup each interval,
+/// most frequent first, until the test ends. The failures of the final,
+/// partial interval are logged once the test has ended.
+async fn error_rollups(interval: Duration) {
+    let mut previous = Instant::now();
+
+    loop {
+        // the end of the test is checked regularly so that the final rollup
+        // is logged before the clients are shut down
+        tokio::time::sleep(std::cmp::min(interval, Duration::from_millis(100))).await;
+
+        let running = RUNNING.load(Ordering::Relaxed);
+
+        if running && previous.elapsed() < interval {
+            continue;
+        }
+
+        let elapsed = previous.elapsed();
+        previous = Instant::now();
+
+        let mut errors: Vec<_> = std::mem::take(&mut *ERROR_ROLLUP.lock().unwrap())
+            .into_iter()
+            .collect();
+
+        errors.sort_by(|a, b| b.1.cmp(&a.1));
+
+        for ((operation, message), count) in errors {
+            output!(
+                "Errors: {count} {operation} {message} in last {:.1}s",
+                elapsed.as_secs_f64()
+            );
+        }
+
+        if !running {
+            break;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub enum ResponseError {
     /// Some exception while reading the response
     Exception,
@@ -71,6 +1231,8 @@ pub enum ResponseError {
     Ratelimited,
     /// Some backends may have their own timeout
     BackendTimeout,
+    /// The backend rejected the credentials, which may have expired
+    Unauthorized,
 }
 
 impl From<MomentoError> for ResponseError {
@@ -78,7 +1240,60 @@ impl From<MomentoError> for ResponseError {
         match other.error_code {
             MomentoErrorCode::LimitExceededError { .. } => ResponseError::Ratelimited,
             MomentoErrorCode::TimeoutError { .. } => ResponseError::BackendTimeout,
+            MomentoErrorCode::AuthenticationError { .. } => ResponseError::Unauthorized,
             _ => ResponseError::Exception,
         }
     }
 }
+
+/// Records the backend-reported processing time from the value of the
+/// configured response header. Both plain numbers and the `dur` parameter of a
+/// `Server-Timing` entry are understood.
+fn record_server_latency(config: &Config, value: Option<&[u8]>) {
+    let unit = config.client().unwrap().server_latency_unit();
+
+    let value = value
+        .and_then(|value| std::str::from_utf8(value).ok())
+        .map(|value| match value.split_once("dur=") {
+            Some((_, duration)) => duration.split([';', ',']).next().unwrap_or(""),
+            None => value,
+        })
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value >= 0.0);
+
+    match value {
+        Some(value) => {
+            let _ = SERVER_LATENCY.increment(unit.to_nanoseconds(value));
+        }
+        None => SERVER_LATENCY_MISSING.increment(),
+    }
+}
+
+/// Records the bytes of a successful set. The ratio of the stored bytes (key
+/// and value) to the logical payload bytes reflects the overhead of the keys
+/// and value headers.
+fn record_set_bytes(key_len: usize, value_len: usize, logical_len: usize) {
+    BYTES_WRITTEN.add(value_len as u64);
+    BYTES_STORED.add((key_len + value_len) as u64);
+    BYTES_LOGICAL.add(logical_len as u64);
+}
+
+/// Records the per-key outcomes of a multi-key operation. The operation is
+/// counted as partial when only some of its keys succeeded.
+fn record_batch(ok: usize, failed: usize) {
+    BATCH_KEY_OK.add(ok as u64);
+    BATCH_KEY_FAIL.add(failed as u64);
+
+    if ok > 0 && failed > 0 {
+        BATCH_PARTIAL.increment();
+    }
+}
+
+/// Overwrites a random region of the value with random bytes, as an update
+/// which changes part of an existing value would.
+fn mutate_value(value: &mut [u8], mutation_bytes: usize) {
+    let len = std::cmp::min(mutation_bytes, value.len());
+    let mut rng = rand::thread_rng();
+    let offset = rng.gen_range(0..=(value.len() - len));
+    rng.fill(&mut value[offset..(offset + len)]);
+}
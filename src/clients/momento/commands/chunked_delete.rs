@@ -0,0 +1,41 @@
+use super::*;
+
+/// Deletes each of the keys of a chunked value. Every chunk is attempted even
+/// after a failure so that the per-key results are complete.
+pub async fn chunked_delete(
+    client: &mut CacheClient,
+    config: &Config,
+    cache_name: &str,
+    request: workload::client::ChunkedDelete,
+) -> std::result::Result<(), ResponseError> {
+    CHUNKED_DELETE.increment();
+
+    let delete = async {
+        let mut ok = 0;
+        let mut failed = 0;
+        let mut error = None;
+
+        for chunk in 0..request.chunks {
+            let key = workload::client::chunk_key(&request.key, chunk);
+
+            match client.delete(cache_name, key).await {
+                Ok(_) => ok += 1,
+                Err(e) => {
+                    failed += 1;
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        record_batch(ok, failed);
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    };
+
+    let result = timeout(config.client().unwrap().request_timeout(), delete).await;
+
+    record_result!(result, CHUNKED_DELETE)
+}
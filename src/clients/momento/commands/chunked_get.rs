@@ -0,0 +1,67 @@
+use super::*;
+
+use ::momento::cache::GetResponse;
+
+/// Reads each of the chunks of a chunked value and checks that their total
+/// length matches the expected length. Every chunk is read, even once one is
+/// missing or fails, so that the per-key results are complete.
+pub async fn chunked_get(
+    client: &mut CacheClient,
+    config: &Config,
+    cache_name: &str,
+    request: workload::client::ChunkedGet,
+) -> std::result::Result<(), ResponseError> {
+    CHUNKED_GET.increment();
+
+    let read = async {
+        let mut len = 0;
+        let mut found = 0;
+        let mut error = None;
+
+        for chunk in 0..request.chunks {
+            CHUNK_READ.increment();
+
+            let key = workload::client::chunk_key(&request.key, chunk);
+
+            match client.get(cache_name, &*key).await {
+                Ok(r @ GetResponse::Hit { .. }) => {
+                    found += 1;
+                    len += Vec::<u8>::try_from(r).map(|v| v.len()).unwrap_or(0);
+                }
+                Ok(GetResponse::Miss) => {}
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        record_batch(found, request.chunks - found);
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok::<_, MomentoError>((found == request.chunks).then_some(len)),
+        }
+    };
+
+    let result = timeout(config.client().unwrap().request_timeout(), read).await;
+
+    match &result {
+        Ok(Ok(Some(len))) => {
+            BYTES_READ.add(*len as u64);
+            if request
+                .expected_len
+                .is_some_and(|expected| expected != *len)
+            {
+                SIZE_MISMATCH.increment();
+            }
+            RESPONSE_HIT.increment();
+        }
+        Ok(Ok(None)) => {
+            RESPONSE_MISS.increment();
+            CHUNKED_GET_MISS.increment();
+        }
+        _ => {}
+    }
+
+    record_result!(result, CHUNKED_GET)
+}
@@ -0,0 +1,56 @@
+use super::*;
+
+use ::momento::cache::SetRequest;
+
+/// Writes each chunk of the value to its own key. Every chunk is attempted
+/// even after a failure so that the per-key results are complete, which lets
+/// a partially written value be told apart from one which was not written.
+pub async fn chunked_set(
+    client: &mut CacheClient,
+    config: &Config,
+    cache_name: &str,
+    request: workload::client::ChunkedSet,
+) -> std::result::Result<(), ResponseError> {
+    CHUNKED_SET.increment();
+
+    let write = async {
+        let mut ok = 0;
+        let mut failed = 0;
+        let mut error = None;
+
+        for (chunk, value) in request.value.chunks(request.chunk_size).enumerate() {
+            CHUNK_WRITE.increment();
+
+            let key = workload::client::chunk_key(&request.key, chunk);
+
+            let mut r = SetRequest::new(cache_name, key, value.to_vec());
+
+            if let Some(ttl) = request.ttl {
+                r = r.ttl(ttl);
+            }
+
+            match client.send_request(r).await {
+                Ok(_) => ok += 1,
+                Err(e) => {
+                    failed += 1;
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        record_batch(ok, failed);
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    };
+
+    let result = timeout(config.client().unwrap().request_timeout(), write).await;
+
+    if let Ok(Ok(_)) = &result {
+        BYTES_WRITTEN.add(request.value.len() as u64);
+    }
+
+    record_result!(result, CHUNKED_SET)
+}
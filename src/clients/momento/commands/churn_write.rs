@@ -0,0 +1,46 @@
+use super::*;
+
+use ::momento::cache::SetRequest;
+
+/// Sets a key-value pair in the cache and then immediately deletes the key.
+/// The operation succeeds only if both the write and the delete succeed.
+pub async fn churn_write(
+    client: &mut CacheClient,
+    config: &Config,
+    cache_name: &str,
+    request: workload::client::ChurnWrite,
+) -> std::result::Result<(), ResponseError> {
+    CHURN.increment();
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let bytes = request.value.len() as u64;
+
+    let mut r = SetRequest::new(cache_name, &*request.key, request.value);
+
+    if let Some(ttl) = request.ttl {
+        r = r.ttl(ttl);
+    }
+
+    match timeout(request_timeout, client.send_request(r)).await {
+        Ok(Ok(_)) => {
+            BYTES_WRITTEN.add(bytes);
+        }
+        Ok(Err(e)) => {
+            CHURN_EX.increment();
+            return Err(e.into());
+        }
+        Err(_) => {
+            CHURN_TIMEOUT.increment();
+            return Err(ResponseError::Timeout);
+        }
+    }
+
+    let result = timeout(
+        request_timeout,
+        client.delete(cache_name, (*request.key).to_owned()),
+    )
+    .await;
+
+    record_result!(result, CHURN)
+}
@@ -0,0 +1,65 @@
+use super::*;
+
+use ::momento::cache::GetResponse;
+
+/// Removes a key from the cache only if its current value matches the
+/// expected value. Momento has no conditional delete, so this is emulated with
+/// a get followed by a delete and is not atomic.
+pub async fn delete_if(
+    client: &mut CacheClient,
+    config: &Config,
+    cache_name: &str,
+    request: workload::client::DeleteIf,
+) -> std::result::Result<(), ResponseError> {
+    DELETE_IF.increment();
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let value = match timeout(request_timeout, client.get(cache_name, &*request.key)).await {
+        Ok(Ok(r)) => match r {
+            GetResponse::Hit { .. } => Vec::<u8>::try_from(r).unwrap_or_default(),
+            GetResponse::Miss => {
+                DELETE_IF_MISS.increment();
+                DELETE_IF_OK.increment();
+                return Ok(());
+            }
+        },
+        Ok(Err(e)) => {
+            DELETE_IF_EX.increment();
+            return Err(e.into());
+        }
+        Err(_) => {
+            DELETE_IF_TIMEOUT.increment();
+            return Err(ResponseError::Timeout);
+        }
+    };
+
+    BYTES_READ.add(value.len() as u64);
+
+    if !request.condition.matches(&value) {
+        DELETE_IF_MISMATCH.increment();
+        DELETE_IF_OK.increment();
+        return Ok(());
+    }
+
+    match timeout(
+        request_timeout,
+        client.delete(cache_name, (*request.key).to_owned()),
+    )
+    .await
+    {
+        Ok(Ok(_)) => {
+            DELETE_IF_MATCH.increment();
+            DELETE_IF_OK.increment();
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            DELETE_IF_EX.increment();
+            Err(e.into())
+        }
+        Err(_) => {
+            DELETE_IF_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
@@ -1,16 +1,20 @@
 use super::*;
 
-use ::momento::cache::GetResponse;
+use ::momento::cache::{GetResponse, SetRequest};
 
-/// Retrieve a key-value pair from the cache.
+/// Retrieve a key-value pair from the cache. If a shadow cache is configured,
+/// the get is mirrored to it and the results are compared.
 pub async fn get(
     client: &mut CacheClient,
     config: &Config,
     cache_name: &str,
+    shadow: Option<&Shadow>,
     request: workload::client::Get,
-) -> std::result::Result<(), ResponseError> {
+) -> std::result::Result<bool, ResponseError> {
     GET.increment();
 
+    let start = Instant::now();
+
     match timeout(
         config.client().unwrap().request_timeout(),
         client.get(cache_name, &*request.key),
@@ -19,16 +23,66 @@ pub async fn get(
     {
         Ok(Ok(r)) => match r {
             GetResponse::Hit { .. } => {
+                let _ = GET_HIT_LATENCY.increment(start.elapsed().as_nanos() as u64);
+                let value = Vec::<u8>::try_from(r).ok();
+                if let Some(value) = value.as_ref() {
+                    BYTES_READ.add(value.len() as u64);
+                    SizeBuckets::record(value.len(), start.elapsed().as_nanos() as u64);
+
+                    if request.expected_len.is_some_and(|len| len != value.len()) {
+                        SIZE_MISMATCH.increment();
+                    }
+                    if let Some(check) = &request.sequence {
+                        check.observe(value);
+                    }
+                    if let Some(hmac) = &request.hmac {
+                        hmac.verify(&request.key, value);
+                    }
+                    if let Some(record) = &request.record {
+                        record.verify(value);
+                    }
+                }
+                if let Some(shadow) = shadow {
+                    shadow_get(config, shadow, request.key.clone(), value);
+                }
                 GET_OK.increment();
                 RESPONSE_HIT.increment();
                 GET_KEY_HIT.increment();
-                Ok(())
+                Ok(true)
             }
             GetResponse::Miss => {
+                let _ = GET_MISS_LATENCY.increment(start.elapsed().as_nanos() as u64);
                 GET_OK.increment();
                 RESPONSE_MISS.increment();
                 GET_KEY_MISS.increment();
-                Ok(())
+
+                if let Some(shadow) = shadow {
+                    shadow_get(config, shadow, request.key.clone(), None);
+                }
+
+                if let Some(populate) = request.populate {
+                    POPULATE.increment();
+
+                    let mut r =
+                        SetRequest::new(cache_name, &*request.key, populate.value(&request.key));
+
+                    if let Some(ttl) = populate.ttl {
+                        r = r.ttl(ttl);
+                    }
+
+                    match timeout(
+                        config.client().unwrap().request_timeout(),
+                        client.send_request(r),
+                    )
+                    .await
+                    {
+                        Ok(Ok(_)) => POPULATE_OK.increment(),
+                        Ok(Err(_)) => POPULATE_EX.increment(),
+                        Err(_) => POPULATE_TIMEOUT.increment(),
+                    }
+                }
+
+                Ok(false)
             }
         },
         Ok(Err(e)) => {
@@ -41,3 +95,36 @@ pub async fn get(
         }
     }
 }
+
+/// Sends a get to the shadow cache in the background and compares the result
+/// with the value returned by the primary, `None` indicating a miss.
+fn shadow_get(config: &Config, shadow: &Shadow, key: Arc<[u8]>, expected: Option<Vec<u8>>) {
+    let request_timeout = config.client().unwrap().request_timeout();
+    let mut client = shadow.client.clone();
+    let cache_name = shadow.cache_name.clone();
+
+    SHADOW.increment();
+
+    tokio::spawn(async move {
+        match timeout(request_timeout, client.get(&*cache_name, &*key)).await {
+            Ok(Ok(r)) => {
+                SHADOW_OK.increment();
+
+                let value = match r {
+                    GetResponse::Hit { .. } => Vec::<u8>::try_from(r).ok(),
+                    GetResponse::Miss => None,
+                };
+
+                if value != expected {
+                    SHADOW_MISMATCH.increment();
+                }
+            }
+            Ok(Err(_)) => {
+                SHADOW_EX.increment();
+            }
+            Err(_) => {
+                SHADOW_TIMEOUT.increment();
+            }
+        }
+    });
+}
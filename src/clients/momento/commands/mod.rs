@@ -2,7 +2,12 @@ use super::*;
 
 use paste::paste;
 
+mod chunked_delete;
+mod chunked_get;
+mod chunked_set;
+mod churn_write;
 mod delete;
+mod delete_if;
 mod get;
 mod hash_delete;
 mod hash_get;
@@ -26,8 +31,16 @@ mod sorted_set_range;
 mod sorted_set_rank;
 mod sorted_set_remove;
 mod sorted_set_score;
+mod touch;
+mod ttl_check;
+mod update;
 
+pub use chunked_delete::*;
+pub use chunked_get::*;
+pub use chunked_set::*;
+pub use churn_write::*;
 pub use delete::*;
+pub use delete_if::*;
 pub use get::*;
 pub use hash_delete::*;
 pub use hash_get::*;
@@ -51,6 +64,9 @@ pub use sorted_set_range::*;
 pub use sorted_set_rank::*;
 pub use sorted_set_remove::*;
 pub use sorted_set_score::*;
+pub use touch::*;
+pub use ttl_check::*;
+pub use update::*;
 
 #[macro_export]
 #[rustfmt::skip]
@@ -11,17 +11,31 @@ pub async fn set(
 ) -> std::result::Result<(), ResponseError> {
     SET.increment();
 
+    if request.value.is_empty() {
+        SET_EMPTY.increment();
+    }
+
     let mut r = SetRequest::new(cache_name, &*request.key, &*request.value);
 
     if let Some(ttl) = request.ttl {
         r = r.ttl(ttl);
     }
 
+    let key_len = request.key.len();
+    let value_len = request.value.len();
+
+    let start = Instant::now();
+
     let result = timeout(
         config.client().unwrap().request_timeout(),
         client.send_request(r),
     )
     .await;
 
+    if let Ok(Ok(_)) = &result {
+        record_set_bytes(key_len, value_len, request.logical_len);
+        SizeBuckets::record(value_len, start.elapsed().as_nanos() as u64);
+    }
+
     record_result!(result, SET, SET_STORED)
 }
@@ -0,0 +1,40 @@
+use super::*;
+
+use ::momento::cache::UpdateTtlResponse;
+
+/// Replaces the ttl of a key without reading or rewriting its value.
+pub async fn touch(
+    client: &mut CacheClient,
+    config: &Config,
+    cache_name: &str,
+    request: workload::client::Touch,
+) -> std::result::Result<(), ResponseError> {
+    TOUCH.increment();
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    match timeout(
+        request_timeout,
+        client.update_ttl(cache_name, &*request.key, request.ttl),
+    )
+    .await
+    {
+        Ok(Ok(UpdateTtlResponse::Set)) => {
+            TOUCH_OK.increment();
+            Ok(())
+        }
+        Ok(Ok(UpdateTtlResponse::Miss)) => {
+            TOUCH_MISS.increment();
+            TOUCH_OK.increment();
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            TOUCH_EX.increment();
+            Err(e.into())
+        }
+        Err(_) => {
+            TOUCH_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use super::*;
+
+use ::momento::cache::{GetResponse, SetRequest};
+use tokio::sync::Semaphore;
+
+/// The maximum number of expiry reads which may be waiting at once across all
+/// client tasks. Checks beyond this are skipped so a long ttl at a high
+/// request rate cannot pile up an unbounded number of sleeping tasks.
+const MAX_PENDING_CHECKS: usize = 65536;
+
+static PENDING_CHECKS: Semaphore = Semaphore::const_new(MAX_PENDING_CHECKS);
+
+/// Sets a key-value pair with a ttl and then, after waiting for the ttl to
+/// elapse, checks that the key has expired. The read is performed in the
+/// background so the task is free to continue sending requests.
+pub async fn ttl_check(
+    client: &mut CacheClient,
+    config: &Config,
+    cache_name: &str,
+    request: workload::client::TtlCheck,
+) -> std::result::Result<(), ResponseError> {
+    TTL_CHECK.increment();
+
+    let r = SetRequest::new(cache_name, &*request.key, request.value).ttl(request.ttl);
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let result = timeout(request_timeout, client.send_request(r)).await;
+
+    if let Ok(Ok(_)) = &result {
+        if let Ok(permit) = PENDING_CHECKS.try_acquire() {
+            let mut client = client.clone();
+            let cache_name = cache_name.to_string();
+            let key = request.key;
+            let wait = request.wait;
+
+            tokio::spawn(async move {
+                let _permit = permit;
+
+                sleep(wait).await;
+
+                match timeout(request_timeout, client.get(&cache_name, &*key)).await {
+                    Ok(Ok(GetResponse::Miss)) => {
+                        TTL_EXPIRED_OK.increment();
+                    }
+                    Ok(Ok(GetResponse::Hit { .. })) => {
+                        TTL_EXPIRED_FAIL.increment();
+                    }
+                    _ => {
+                        TTL_EXPIRED_ERROR.increment();
+                    }
+                }
+            });
+        } else {
+            TTL_EXPIRED_SKIPPED.increment();
+        }
+    }
+
+    record_result!(result, TTL_CHECK)
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+use ::momento::cache::{GetResponse, SetRequest};
+
+/// Reads the value for a key, overwrites part of it, and writes it back. Keys
+/// which are not found are not written.
+pub async fn update(
+    client: &mut CacheClient,
+    config: &Config,
+    cache_name: &str,
+    request: workload::client::Update,
+) -> std::result::Result<(), ResponseError> {
+    UPDATE.increment();
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let mut value = match timeout(request_timeout, client.get(cache_name, &*request.key)).await {
+        Ok(Ok(r @ GetResponse::Hit { .. })) => match Vec::<u8>::try_from(r) {
+            Ok(value) => value,
+            Err(_) => {
+                UPDATE_EX.increment();
+                return Err(ResponseError::Exception);
+            }
+        },
+        Ok(Ok(GetResponse::Miss)) => {
+            UPDATE_MISS.increment();
+            UPDATE_OK.increment();
+            return Ok(());
+        }
+        Ok(Err(e)) => {
+            UPDATE_EX.increment();
+            return Err(e.into());
+        }
+        Err(_) => {
+            UPDATE_TIMEOUT.increment();
+            return Err(ResponseError::Timeout);
+        }
+    };
+
+    BYTES_READ.add(value.len() as u64);
+
+    mutate_value(&mut value, request.mutation_bytes);
+
+    let bytes = value.len() as u64;
+
+    let mut r = SetRequest::new(cache_name, &*request.key, value);
+
+    if let Some(ttl) = request.ttl {
+        r = r.ttl(ttl);
+    }
+
+    let result = timeout(request_timeout, client.send_request(r)).await;
+
+    if let Ok(Ok(_)) = &result {
+        BYTES_WRITTEN.add(bytes);
+    }
+
+    record_result!(result, UPDATE)
+}
@@ -11,31 +11,72 @@ use commands::*;
 pub fn launch_tasks(runtime: &mut Runtime, config: Config, work_receiver: Receiver<WorkItem>) {
     debug!("launching momento protocol tasks");
 
-    for _ in 0..config.client().unwrap().poolsize() {
+    let api_key_env = config.client().unwrap().api_key_env();
+
+    // a key file holds a single key, so it can't stand in for several
+    // accounts
+    if let Some(path) = config.client().unwrap().api_key_file() {
+        if api_key_env.len() > 1 {
+            eprintln!("`api_key_file` cannot be used with more than one `api_key_env`: {path}");
+            std::process::exit(1);
+        }
+    }
+
+    let shadow = config.target().shadow_cache_name().map(|cache_name| {
+        let key_env = config
+            .target()
+            .shadow_api_key_env()
+            .unwrap_or(&api_key_env[0]);
+
+        let _guard = runtime.enter();
+
+        let client =
+            match CredentialProvider::from_env_var(key_env.to_string()).and_then(build_client) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("could not create shadow cache client: {e}");
+                    std::process::exit(1);
+                }
+            };
+
+        Shadow {
+            client,
+            cache_name: cache_name.into(),
+        }
+    });
+
+    if config.target().create_caches() {
+        create_caches(runtime, &config, &api_key_env[0], shadow.as_ref());
+    }
+
+    for pool_id in 0..config.client().unwrap().poolsize() {
+        // select the credential for this member of the pool
+        let key_env = &api_key_env[pool_id % api_key_env.len()];
+
+        let account = AccountMetrics::register(key_env);
+
         let client = {
             let _guard = runtime.enter();
 
             // initialize the Momento cache client
-            if std::env::var("MOMENTO_API_KEY").is_err() {
-                eprintln!("environment variable `MOMENTO_API_KEY` is not set");
+            if config.client().unwrap().api_key_file().is_none() && std::env::var(key_env).is_err()
+            {
+                eprintln!("environment variable `{key_env}` is not set");
                 std::process::exit(1);
             }
 
-            let credential_provider =
-                match CredentialProvider::from_env_var("MOMENTO_API_KEY".to_string()) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        eprintln!("MOMENTO_API_KEY key should be valid: {e}");
-                        std::process::exit(1);
+            let credential_provider = match credentials(&config, key_env) {
+                Ok(v) => v,
+                Err(e) => {
+                    match config.client().unwrap().api_key_file() {
+                        Some(path) => eprintln!("key in {path} should be valid: {e}"),
+                        None => eprintln!("{key_env} key should be valid: {e}"),
                     }
-                };
+                    std::process::exit(1);
+                }
+            };
 
-            match CacheClient::builder()
-                .default_ttl(Duration::from_secs(900))
-                .configuration(LowLatency::v1())
-                .credential_provider(credential_provider)
-                .build()
-            {
+            match build_client(credential_provider) {
                 Ok(c) => c,
                 Err(e) => {
                     eprintln!("could not create cache client: {}", e);
@@ -44,153 +85,592 @@ pub fn launch_tasks(runtime: &mut Runtime, config: Config, work_receiver: Receiv
             }
         };
 
+        prewarm(runtime, &config, &client);
+
         CONNECT.increment();
         CONNECT_CURR.increment();
 
+        let shared = Arc::new(tokio::sync::Mutex::new(SharedCredentials {
+            client: client.clone(),
+            generation: 0,
+            refreshed: None,
+        }));
+
         // create one task per channel
         for _ in 0..config.client().unwrap().concurrency() {
-            runtime.spawn(task(config.clone(), client.clone(), work_receiver.clone()));
+            runtime.spawn(task(
+                config.clone(),
+                client.clone(),
+                shared.clone(),
+                work_receiver.clone(),
+                account.clone(),
+                shadow.clone(),
+            ));
+        }
+    }
+
+    if let Some(interval) = config.client().unwrap().cold_probe_interval() {
+        runtime.spawn(cold_probe(config.clone(), api_key_env[0].clone(), interval));
+    }
+}
+
+/// A secondary cache which receives a copy of each get for comparison.
+#[derive(Clone)]
+pub struct Shadow {
+    client: CacheClient,
+    cache_name: Arc<str>,
+}
+
+/// Build a cache client with the standard configuration.
+fn build_client(
+    credential_provider: CredentialProvider,
+) -> std::result::Result<CacheClient, MomentoError> {
+    CacheClient::builder()
+        .default_ttl(Duration::from_secs(900))
+        .configuration(LowLatency::v1())
+        .credential_provider(credential_provider)
+        .build()
+}
+
+/// Returns the credentials for a client, which are read from the
+/// `api_key_file` if one is configured or from the environment variable.
+fn credentials(
+    config: &Config,
+    key_env: &str,
+) -> std::result::Result<CredentialProvider, MomentoError> {
+    let Some(path) = config.client().unwrap().api_key_file() else {
+        return CredentialProvider::from_env_var(key_env.to_string());
+    };
+
+    match std::fs::read_to_string(path) {
+        Ok(key) => CredentialProvider::from_string(key.trim().to_string()),
+        Err(e) => {
+            eprintln!("failed to read api key file: {path}\n{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Reads a fresh API key by running the `api_key_refresh_command`, or else by
+/// re-reading the `api_key_file`. Returns `None` if neither is configured.
+async fn fresh_api_key(config: &Config) -> Option<std::result::Result<String, String>> {
+    let client_config = config.client().unwrap();
+
+    if let Some(command) = client_config.api_key_refresh_command() {
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()
+            .await;
+
+        return Some(match output {
+            Ok(output) if output.status.success() => {
+                Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+            }
+            Ok(output) => Err(format!("refresh command failed: {}", output.status)),
+            Err(e) => Err(format!("could not run refresh command: {e}")),
+        });
+    }
+
+    let path = client_config.api_key_file()?;
+
+    Some(
+        tokio::fs::read_to_string(path)
+            .await
+            .map(|key| key.trim().to_string())
+            .map_err(|e| format!("could not read api key file: {path}: {e}")),
+    )
+}
+
+/// The client shared by the tasks of one member of the pool, so that rejected
+/// credentials are refreshed once for the pool rather than by every task.
+struct SharedCredentials {
+    client: CacheClient,
+    // incremented each time the client is rebuilt with fresh credentials
+    generation: u64,
+    refreshed: Option<Instant>,
+}
+
+/// Rebuilds the client with fresh credentials after the backend rejected the
+/// current ones. If another task of the pool has already refreshed them, its
+/// client is used instead. The client is left unchanged if no refresh is
+/// configured or the new credentials could not be loaded.
+async fn refresh_credentials(
+    config: &Config,
+    shared: &tokio::sync::Mutex<SharedCredentials>,
+    client: &mut CacheClient,
+    generation: &mut u64,
+) {
+    // the lock is held during the refresh, so the other tasks wait for it
+    // rather than also refreshing
+    let mut shared = shared.lock().await;
+
+    if shared.generation != *generation {
+        *client = shared.client.clone();
+        *generation = shared.generation;
+        return;
+    }
+
+    // many in-flight requests will fail together when a token expires, so
+    // the credentials are refreshed at most once per second
+    if shared
+        .refreshed
+        .is_some_and(|t| t.elapsed() < Duration::from_secs(1))
+    {
+        return;
+    }
+
+    let Some(key) = fresh_api_key(config).await else {
+        return;
+    };
+
+    shared.refreshed = Some(Instant::now());
+
+    CREDENTIAL_REFRESH.increment();
+
+    let result = key.and_then(|key| {
+        CredentialProvider::from_string(key)
+            .and_then(build_client)
+            .map_err(|e| e.to_string())
+    });
+
+    match result {
+        Ok(c) => {
+            CREDENTIAL_REFRESH_OK.increment();
+            shared.client = c.clone();
+            shared.generation += 1;
+            *client = c;
+            *generation = shared.generation;
+        }
+        Err(e) => {
+            CREDENTIAL_REFRESH_EX.increment();
+            error!("failed to refresh credentials: {e}");
+        }
+    }
+}
+
+/// Creates the cache and the shadow cache and waits for each to become ready.
+/// At most `max_concurrent_creates` caches are provisioned at the same time so
+/// that the control plane is not overwhelmed.
+fn create_caches(runtime: &mut Runtime, config: &Config, key_env: &str, shadow: Option<&Shadow>) {
+    let cache_name = config.target().cache_name().unwrap_or_else(|| {
+        eprintln!("cache name is not specified in the `target` section");
+        std::process::exit(1);
+    });
+
+    let client = {
+        let _guard = runtime.enter();
+
+        match credentials(config, key_env).and_then(build_client) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("could not create cache client: {e}");
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let mut caches = vec![(client, cache_name.to_string())];
+
+    if let Some(shadow) = shadow {
+        caches.push((shadow.client.clone(), shadow.cache_name.to_string()));
+    }
+
+    let ready_timeout = config.target().cache_ready_timeout();
+    let request_timeout = config.client().unwrap().request_timeout();
+    let permits = Arc::new(tokio::sync::Semaphore::new(
+        config.target().max_concurrent_creates(),
+    ));
+
+    let results = runtime.block_on(futures::future::join_all(caches.into_iter().map(
+        |(client, cache_name)| {
+            let permits = permits.clone();
+
+            async move {
+                let _permit = permits.acquire().await.unwrap();
+                create_cache(client, &cache_name, ready_timeout, request_timeout).await
+            }
+        },
+    )));
+
+    for result in results {
+        if let Err(e) = result {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Creates the cache if it does not already exist and then polls it with gets
+/// until one succeeds, as a new cache may not serve requests immediately.
+async fn create_cache(
+    mut client: CacheClient,
+    cache_name: &str,
+    ready_timeout: Duration,
+    request_timeout: Duration,
+) -> std::result::Result<(), String> {
+    let start = Instant::now();
+
+    if let Err(e) = client.create_cache(cache_name).await {
+        return Err(format!("could not create cache: {cache_name}\n{e}"));
+    }
+
+    while start.elapsed() < ready_timeout {
+        let ready = timeout(request_timeout, client.get(cache_name, "rpc-perf-ready")).await;
+
+        if let Ok(Ok(_)) = ready {
+            output!(
+                "Cache Ready: {cache_name} in {:.2} s",
+                start.elapsed().as_secs_f64()
+            );
+
+            return Ok(());
+        }
+
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    Err(format!(
+        "cache was not ready within {} s: {cache_name}",
+        ready_timeout.as_secs()
+    ))
+}
+
+/// Periodically builds a new client, issues a single get, and then tears the
+/// client down. This measures cold-connection latency separately from the
+/// steady-state workload.
+async fn cold_probe(config: Config, key_env: String, interval: Duration) {
+    let cache_name = config.target().cache_name().unwrap_or_else(|| {
+        eprintln!("cache name is not specified in the `target` section");
+        std::process::exit(1);
+    });
+
+    while RUNNING.load(Ordering::Relaxed) {
+        sleep(interval).await;
+
+        COLD_PROBE.increment();
+
+        let start = Instant::now();
+
+        let mut client = match credentials(&config, &key_env).and_then(build_client) {
+            Ok(c) => c,
+            Err(_) => {
+                COLD_PROBE_EX.increment();
+                continue;
+            }
+        };
+
+        match timeout(
+            config.client().unwrap().request_timeout(),
+            client.get(cache_name, "rpc-perf-cold-probe"),
+        )
+        .await
+        {
+            Ok(Ok(_)) => {
+                COLD_PROBE_OK.increment();
+                let _ = COLD_PROBE_LATENCY.increment(start.elapsed().as_nanos() as u64);
+            }
+            Ok(Err(_)) => {
+                COLD_PROBE_EX.increment();
+            }
+            Err(_) => {
+                COLD_PROBE_TIMEOUT.increment();
+            }
         }
     }
 }
 
+/// Issue throwaway requests on a newly created client so that it is warm before
+/// it begins to process the workload. These do not count towards any metrics.
+fn prewarm(runtime: &mut Runtime, config: &Config, client: &CacheClient) {
+    let requests = config.client().unwrap().prewarm_requests_per_connection();
+
+    if requests == 0 {
+        return;
+    }
+
+    let cache_name = config.target().cache_name().unwrap_or_else(|| {
+        eprintln!("cache name is not specified in the `target` section");
+        std::process::exit(1);
+    });
+
+    let request_timeout = config.client().unwrap().request_timeout();
+    let mut client = client.clone();
+
+    let ok = runtime.block_on(async move {
+        let mut ok = 0;
+
+        for _ in 0..requests {
+            if let Ok(Ok(_)) =
+                timeout(request_timeout, client.get(cache_name, "rpc-perf-prewarm")).await
+            {
+                ok += 1;
+            }
+        }
+
+        ok
+    });
+
+    debug!("prewarm completed: {ok} of {requests} requests were successful");
+}
+
 async fn task(
     config: Config,
     // cache_name: String,
     mut client: CacheClient,
+    shared: Arc<tokio::sync::Mutex<SharedCredentials>>,
     work_receiver: Receiver<WorkItem>,
+    account: Arc<AccountMetrics>,
+    shadow: Option<Shadow>,
 ) -> Result<()> {
     let cache_name = config.target().cache_name().unwrap_or_else(|| {
         eprintln!("cache name is not specified in the `target` section");
         std::process::exit(1);
     });
 
+    let mut breaker = CircuitBreaker::new(&config);
+    let mut client_cache = ClientCache::new(&config);
+    let mut written_keys = WrittenKeys::new(&config);
+
+    // there are no endpoints, so the connection is identified by its account,
+    // which determines the region it is served from
+    let stats = ConnectionStats::new(account.name());
+    stats.connected();
+
+    // the generation of the shared credentials which this task's client uses
+    let mut generation = 0;
+
+    start_jitter(&config).await;
+
     while RUNNING.load(Ordering::Relaxed) {
-        let work_item = work_receiver
-            .recv()
-            .await
-            .map_err(|_| Error::new(ErrorKind::Other, "channel closed"))?;
+        breaker.wait().await;
+
+        let (work_item, _in_flight) = recv_work(&work_receiver, &config).await?;
+        // the request is serialized as it is sent, so the build latency only
+        // covers the preparation before it is dispatched
+        let build = Instant::now();
+        let histograms = ResponseHistograms::new(&work_item);
+        let context = error_context(&config, &work_item);
 
         REQUEST.increment();
+        let injected = injected_error(&config, &work_item);
+        let mut cached = false;
         let start = Instant::now();
-        let result = match work_item {
-            WorkItem::Request { request, .. } => match request {
-                /*
-                 * KEY-VALUE
-                 */
-                ClientRequest::Get(r) => get(&mut client, &config, cache_name, r).await,
-                ClientRequest::Set(r) => set(&mut client, &config, cache_name, r).await,
-                ClientRequest::Delete(r) => delete(&mut client, &config, cache_name, r).await,
-
-                /*
-                 * HASHES (DICTIONARIES)
-                 */
-                ClientRequest::HashDelete(r) => {
-                    hash_delete(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::HashGet(r) => hash_get(&mut client, &config, cache_name, r).await,
-                ClientRequest::HashGetAll(r) => {
-                    hash_get_all(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::HashIncrement(r) => {
-                    hash_increment(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::HashSet(r) => hash_set(&mut client, &config, cache_name, r).await,
-
-                /*
-                 * SETS
-                 */
-                ClientRequest::SetAdd(r) => set_add(&mut client, &config, cache_name, r).await,
-                ClientRequest::SetMembers(r) => {
-                    set_members(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::SetRemove(r) => {
-                    set_remove(&mut client, &config, cache_name, r).await
-                }
+        let _ = REQUEST_BUILD_LATENCY.increment(start.duration_since(build).as_nanos() as u64);
 
-                /*
-                 * LISTS
-                 */
-                ClientRequest::ListPushFront(r) => {
-                    list_push_front(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::ListPushBack(r) => {
-                    list_push_back(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::ListFetch(r) => {
-                    list_fetch(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::ListLength(r) => {
-                    list_length(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::ListPopFront(r) => {
-                    list_pop_front(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::ListPopBack(r) => {
-                    list_pop_back(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::ListRemove(r) => {
-                    list_remove(&mut client, &config, cache_name, r).await
-                }
+        // injected errors and gets served from the client-side cache are
+        // never sent
+        let result = if let Some(error) = injected {
+            Err(error)
+        } else {
+            match work_item {
+                WorkItem::Request { request, .. } => match request {
+                    /*
+                     * KEY-VALUE
+                     */
+                    ClientRequest::Get(r) if client_cache.get(&r.key) => {
+                        cached = true;
+                        client_cache_get()
+                    }
+                    ClientRequest::Get(r) => {
+                        let key = r.key.clone();
+                        let get = get(&mut client, &config, cache_name, shadow.as_ref(), r);
+                        let result = coalesce_get(&config, &key, get).await;
+                        // only keys which were found are cached
+                        if matches!(result, Ok(true)) {
+                            client_cache.insert(key);
+                        }
+                        result.map(|_| ())
+                    }
+                    ClientRequest::Set(r) => {
+                        let _lock = key_lock(&config, &r.key).await;
+                        let key = r.key.clone();
+                        let result = set(&mut client, &config, cache_name, r).await;
+                        if result.is_ok() {
+                            client_cache.written(key.clone());
+                            written_keys.written(key);
+                        }
+                        result
+                    }
+                    ClientRequest::Delete(r) => {
+                        client_cache.invalidate(&r.key);
+                        written_keys.removed(&r.key);
+                        delete(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::DeleteIf(r) => {
+                        client_cache.invalidate(&r.key);
+                        delete_if(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ChunkedGet(r) => {
+                        chunked_get(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ChunkedSet(r) => {
+                        chunked_set(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ChunkedDelete(r) => {
+                        chunked_delete(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::TtlCheck(r) => {
+                        ttl_check(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::Update(r) => {
+                        client_cache.invalidate(&r.key);
+                        update(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ChurnWrite(r) => {
+                        client_cache.invalidate(&r.key);
+                        churn_write(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::Touch(r) => touch(&mut client, &config, cache_name, r).await,
 
-                /*
-                 * SORTED SETS
-                 */
-                ClientRequest::SortedSetAdd(r) => {
-                    sorted_set_add(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::SortedSetIncrement(r) => {
-                    sorted_set_increment(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::SortedSetRange(r) => {
-                    sorted_set_range(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::SortedSetRank(r) => {
-                    sorted_set_rank(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::SortedSetRemove(r) => {
-                    sorted_set_remove(&mut client, &config, cache_name, r).await
-                }
-                ClientRequest::SortedSetScore(r) => {
-                    sorted_set_score(&mut client, &config, cache_name, r).await
-                }
+                    /*
+                     * HASHES (DICTIONARIES)
+                     */
+                    ClientRequest::HashDelete(r) => {
+                        hash_delete(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::HashGet(r) => {
+                        hash_get(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::HashGetAll(r) => {
+                        hash_get_all(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::HashIncrement(r) => {
+                        hash_increment(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::HashSet(r) => {
+                        hash_set(&mut client, &config, cache_name, r).await
+                    }
 
-                /*
-                 * UNSUPPORTED
-                 */
-                _ => {
-                    REQUEST_UNSUPPORTED.increment();
+                    /*
+                     * SETS
+                     */
+                    ClientRequest::SetAdd(r) => set_add(&mut client, &config, cache_name, r).await,
+                    ClientRequest::SetMembers(r) => {
+                        set_members(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::SetRemove(r) => {
+                        set_remove(&mut client, &config, cache_name, r).await
+                    }
+
+                    /*
+                     * LISTS
+                     */
+                    ClientRequest::ListPushFront(r) => {
+                        list_push_front(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ListPushBack(r) => {
+                        list_push_back(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ListFetch(r) => {
+                        list_fetch(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ListLength(r) => {
+                        list_length(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ListPopFront(r) => {
+                        list_pop_front(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ListPopBack(r) => {
+                        list_pop_back(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::ListRemove(r) => {
+                        list_remove(&mut client, &config, cache_name, r).await
+                    }
+
+                    /*
+                     * SORTED SETS
+                     */
+                    ClientRequest::SortedSetAdd(r) => {
+                        sorted_set_add(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::SortedSetIncrement(r) => {
+                        sorted_set_increment(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::SortedSetRange(r) => {
+                        sorted_set_range(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::SortedSetRank(r) => {
+                        sorted_set_rank(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::SortedSetRemove(r) => {
+                        sorted_set_remove(&mut client, &config, cache_name, r).await
+                    }
+                    ClientRequest::SortedSetScore(r) => {
+                        sorted_set_score(&mut client, &config, cache_name, r).await
+                    }
+
+                    /*
+                     * UNSUPPORTED
+                     */
+                    other => {
+                        unsupported(&config, &other);
+                        continue;
+                    }
+                },
+                WorkItem::Reconnect => {
                     continue;
                 }
-            },
-            WorkItem::Reconnect => {
-                continue;
             }
         };
 
-        REQUEST_OK.increment();
+        if injected.is_none() && !cached {
+            REQUEST_OK.increment();
+            stats.request();
+        }
 
         let stop = Instant::now();
 
+        let latency = stop.duration_since(start).as_nanos() as u64;
+
+        processing_delay(&config).await;
+        think_time(&config, latency).await;
+
+        breaker.record(result.is_ok());
+
+        if let Err(e) = &result {
+            log_error(&context, e);
+        }
+
+        log_access(&context, &result, latency);
+
         match result {
             Ok(_) => {
                 RESPONSE_OK.increment();
 
-                let latency = stop.duration_since(start).as_nanos() as u64;
-
-                let _ = RESPONSE_LATENCY.increment(latency);
+                // a client cache hit has no meaningful latency, so it would
+                // only pull the percentiles down
+                if !cached {
+                    account.ok();
+                    stats.ok(latency);
+                    histograms.record_success(&config, latency);
+                }
             }
             Err(ResponseError::Exception) => {
                 RESPONSE_EX.increment();
+                let _ = RESPONSE_EX_LATENCY.increment(latency);
+                account.ex();
             }
             Err(ResponseError::Timeout) => {
                 RESPONSE_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency);
+                account.timeout();
             }
             Err(ResponseError::Ratelimited) => {
                 RESPONSE_RATELIMITED.increment();
+                account.ratelimited();
             }
             Err(ResponseError::BackendTimeout) => {
                 RESPONSE_BACKEND_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency);
+                account.timeout();
+            }
+            Err(ResponseError::Unauthorized) => {
+                RESPONSE_EX.increment();
+                let _ = RESPONSE_EX_LATENCY.increment(latency);
+                account.ex();
+
+                refresh_credentials(&config, &shared, &mut client, &mut generation).await;
             }
         }
     }
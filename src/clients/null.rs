@@ -0,0 +1,114 @@
+use super::*;
+
+/// Launch tasks which complete every request immediately without sending it
+/// anywhere. The measured latency is then only the overhead of the client
+/// itself, which shows whether the harness can drive the target rate. As with
+/// the Momento client, the number of tasks is the poolsize multiplied by the
+/// concurrency and no endpoints are used.
+pub fn launch_tasks(runtime: &mut Runtime, config: Config, work_receiver: Receiver<WorkItem>) {
+    debug!("launching null protocol tasks");
+
+    for _ in 0..config.client().unwrap().poolsize() {
+        CONNECT.increment();
+        CONNECT_OK.increment();
+        CONNECT_CURR.increment();
+
+        for _ in 0..config.client().unwrap().concurrency() {
+            runtime.spawn(task(config.clone(), work_receiver.clone()));
+        }
+    }
+}
+
+async fn task(config: Config, work_receiver: Receiver<WorkItem>) -> Result<()> {
+    let mut breaker = CircuitBreaker::new(&config);
+
+    start_jitter(&config).await;
+
+    while RUNNING.load(Ordering::Relaxed) {
+        breaker.wait().await;
+
+        // the latency covers the dequeue when work is already queued, but
+        // not any time spent waiting for work to arrive
+        let mut start = Instant::now();
+
+        let (work_item, _in_flight) = match work_receiver.try_recv() {
+            Ok(work_item) => {
+                let in_flight = dequeued(&work_item);
+                (work_item, in_flight)
+            }
+            Err(_) => {
+                let received = recv_work(&work_receiver, &config).await?;
+                start = Instant::now();
+                received
+            }
+        };
+
+        let build = Instant::now();
+        let histograms = ResponseHistograms::new(&work_item);
+        let context = error_context(&config, &work_item);
+
+        REQUEST.increment();
+        let injected = injected_error(&config, &work_item);
+        let dispatch = Instant::now();
+        let _ = REQUEST_BUILD_LATENCY.increment(dispatch.duration_since(build).as_nanos() as u64);
+
+        // injected errors are never sent
+        let result = if let Some(error) = injected {
+            Err(error)
+        } else {
+            match work_item {
+                WorkItem::Request { .. } => Ok(()),
+                WorkItem::Reconnect => {
+                    REQUEST_RECONNECT.increment();
+                    continue;
+                }
+            }
+        };
+
+        if injected.is_none() {
+            REQUEST_OK.increment();
+        }
+
+        let stop = Instant::now();
+
+        let latency = stop.duration_since(start).as_nanos() as u64;
+
+        processing_delay(&config).await;
+        think_time(&config, latency).await;
+
+        // a latency can't include the time spent recording it, so the
+        // recording is measured separately
+        let recording = Instant::now();
+
+        breaker.record(result.is_ok());
+
+        if let Err(e) = &result {
+            log_error(&context, e);
+        }
+
+        log_access(&context, &result, latency);
+
+        match result {
+            Ok(_) => {
+                RESPONSE_OK.increment();
+
+                histograms.record_success(&config, latency);
+            }
+            Err(ResponseError::Exception) | Err(ResponseError::Unauthorized) => {
+                RESPONSE_EX.increment();
+                let _ = RESPONSE_EX_LATENCY.increment(latency);
+            }
+            Err(ResponseError::Timeout) | Err(ResponseError::BackendTimeout) => {
+                RESPONSE_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency);
+            }
+            Err(ResponseError::Ratelimited) => {
+                RESPONSE_RATELIMITED.increment();
+            }
+        }
+
+        let _ = RESPONSE_RECORD_LATENCY.increment(recording.elapsed().as_nanos() as u64);
+    }
+
+    Ok(())
+}
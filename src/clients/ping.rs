@@ -35,8 +35,21 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
     let mut read_buffer = Buffer::new(client_config.read_buffer_size());
     let mut write_buffer = Buffer::new(client_config.write_buffer_size());
 
+    let mut reconnect = false;
+    let mut lifetime = ConnectionLifetime::new(&config);
+    let stats = ConnectionStats::new(&endpoint);
+
+    start_jitter(&config).await;
+
     while RUNNING.load(Ordering::Relaxed) {
+        lifetime.recycle(&mut stream);
+
         if stream.is_none() {
+            if reconnect {
+                reconnect_ratelimit().await;
+            }
+            reconnect = true;
+
             CONNECT.increment();
             stream = match timeout(
                 client_config.connect_timeout(),
@@ -47,6 +60,8 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                 Ok(Ok(s)) => {
                     CONNECT_OK.increment();
                     CONNECT_CURR.increment();
+                    lifetime.opened();
+                    stats.connected();
                     Some(s)
                 }
                 Ok(Err(_)) => {
@@ -64,10 +79,9 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
         let mut s = stream.take().unwrap();
 
-        let work_item = work_receiver
-            .recv()
-            .await
-            .map_err(|_| Error::new(ErrorKind::Other, "channel closed"))?;
+        let (work_item, _in_flight) = recv_work(&work_receiver, &config).await?;
+        let build = Instant::now();
+        let context = error_context(&config, &work_item);
 
         REQUEST.increment();
 
@@ -77,8 +91,8 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                 ClientRequest::Ping(_) => {
                     Request::Ping.compose(&mut write_buffer);
                 }
-                _ => {
-                    REQUEST_UNSUPPORTED.increment();
+                other => {
+                    unsupported(&config, other);
                     stream = Some(s);
                     continue;
                 }
@@ -90,9 +104,12 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
         }
 
         REQUEST_OK.increment();
+        lifetime.request();
+        stats.request();
 
         // send request
         let start = Instant::now();
+        let _ = REQUEST_BUILD_LATENCY.increment(start.duration_since(build).as_nanos() as u64);
         s.write_all(write_buffer.borrow()).await?;
         write_buffer.clear();
 
@@ -143,6 +160,17 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
         let stop = Instant::now();
 
+        let latency = stop.duration_since(start).as_nanos() as u64;
+
+        processing_delay(&config).await;
+        think_time(&config, latency).await;
+
+        if let Err(e) = &response {
+            log_error(&context, e);
+        }
+
+        log_access(&context, &response, latency);
+
         match response {
             Ok(response) => {
                 // validate response
@@ -168,10 +196,10 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                 stream = Some(s);
 
                 RESPONSE_OK.increment();
+                stats.ok(latency);
 
-                let latency = stop.duration_since(start).as_nanos() as u64;
-
-                let _ = RESPONSE_LATENCY.increment(latency);
+                record_response_latency(latency);
+                record_near_timeout(&config, latency);
             }
             Err(ResponseError::Exception) => {
                 // record execption
@@ -195,6 +223,12 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
             }
             Err(ResponseError::Timeout) => {
                 RESPONSE_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency);
+                CONNECT_CURR.sub(1);
+            }
+            Err(ResponseError::Unauthorized) => {
+                RESPONSE_EX.increment();
+                let _ = RESPONSE_EX_LATENCY.increment(latency);
                 CONNECT_CURR.sub(1);
             }
             Err(ResponseError::Ratelimited) | Err(ResponseError::BackendTimeout) => {
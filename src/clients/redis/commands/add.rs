@@ -44,6 +44,7 @@ pub async fn add(
                 ADD_NOT_STORED.increment();
             } else {
                 ADD_STORED.increment();
+                BYTES_WRITTEN.add(value.len() as u64);
             }
             Ok(())
         }
@@ -0,0 +1,36 @@
+use super::*;
+
+/// Deletes all of the keys of a chunked value with a single `DEL`.
+pub async fn chunked_delete(
+    connection: &mut Connection<net::Stream>,
+    config: &Config,
+    request: workload::client::ChunkedDelete,
+) -> std::result::Result<(), ResponseError> {
+    CHUNKED_DELETE.increment();
+
+    let mut command = ::redis::cmd("DEL");
+
+    for chunk in 0..request.chunks {
+        command.arg(workload::client::chunk_key(&request.key, chunk));
+    }
+
+    match timeout(
+        config.client().unwrap().request_timeout(),
+        command.query_async::<_, ()>(connection),
+    )
+    .await
+    {
+        Ok(Ok(())) => {
+            CHUNKED_DELETE_OK.increment();
+            Ok(())
+        }
+        Ok(Err(_)) => {
+            CHUNKED_DELETE_EX.increment();
+            Err(ResponseError::Exception)
+        }
+        Err(_) => {
+            CHUNKED_DELETE_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use super::*;
+
+/// Reads all of the chunks of a chunked value with a single `MGET` and checks
+/// that their total length matches the expected length.
+pub async fn chunked_get(
+    connection: &mut Connection<net::Stream>,
+    config: &Config,
+    request: workload::client::ChunkedGet,
+) -> std::result::Result<(), ResponseError> {
+    CHUNKED_GET.increment();
+
+    let mut command = ::redis::cmd("MGET");
+
+    for chunk in 0..request.chunks {
+        command.arg(workload::client::chunk_key(&request.key, chunk));
+    }
+
+    CHUNK_READ.add(request.chunks as u64);
+
+    match timeout(
+        config.client().unwrap().request_timeout(),
+        command.query_async::<_, Vec<Option<Vec<u8>>>>(connection),
+    )
+    .await
+    {
+        Ok(Ok(chunks)) => {
+            CHUNKED_GET_OK.increment();
+
+            let found = chunks.iter().filter(|chunk| chunk.is_some()).count();
+            record_batch(found, chunks.len() - found);
+
+            if chunks.iter().any(|chunk| chunk.is_none()) {
+                RESPONSE_MISS.increment();
+                CHUNKED_GET_MISS.increment();
+                return Ok(());
+            }
+
+            let len: usize = chunks.iter().flatten().map(|chunk| chunk.len()).sum();
+
+            BYTES_READ.add(len as u64);
+            if request.expected_len.is_some_and(|expected| expected != len) {
+                SIZE_MISMATCH.increment();
+            }
+            RESPONSE_HIT.increment();
+            Ok(())
+        }
+        Ok(Err(_)) => {
+            CHUNKED_GET_EX.increment();
+            Err(ResponseError::Exception)
+        }
+        Err(_) => {
+            CHUNKED_GET_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use super::*;
+
+/// Writes each chunk of the value to its own key using a pipeline of `SET`s.
+/// The pipeline only returns the first error, so the per-key results are
+/// recorded when every write succeeded and the failed case can't be broken
+/// down by key.
+pub async fn chunked_set(
+    connection: &mut Connection<net::Stream>,
+    config: &Config,
+    request: workload::client::ChunkedSet,
+) -> std::result::Result<(), ResponseError> {
+    CHUNKED_SET.increment();
+
+    let mut pipeline = ::redis::pipe();
+
+    for (chunk, value) in request.value.chunks(request.chunk_size).enumerate() {
+        let command = pipeline
+            .cmd("SET")
+            .arg(workload::client::chunk_key(&request.key, chunk))
+            .arg(value);
+
+        if let Some(ttl) = request.ttl {
+            if ttl.subsec_nanos() == 0 {
+                command.arg("EX").arg(ttl.as_secs());
+            } else {
+                command.arg("PX").arg(ttl.as_millis() as u64);
+            }
+        }
+
+        command.ignore();
+
+        CHUNK_WRITE.increment();
+    }
+
+    match timeout(
+        config.client().unwrap().request_timeout(),
+        pipeline.query_async::<_, ()>(connection),
+    )
+    .await
+    {
+        Ok(Ok(())) => {
+            CHUNKED_SET_OK.increment();
+            record_batch(request.value.chunks(request.chunk_size).count(), 0);
+            BYTES_WRITTEN.add(request.value.len() as u64);
+            Ok(())
+        }
+        Ok(Err(_)) => {
+            CHUNKED_SET_EX.increment();
+            Err(ResponseError::Exception)
+        }
+        Err(_) => {
+            CHUNKED_SET_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::*;
+
+/// Sets a key-value pair in the cache and then immediately deletes the key.
+/// The operation succeeds only if both the write and the delete succeed.
+pub async fn churn_write(
+    connection: &mut Connection<net::Stream>,
+    config: &Config,
+    request: workload::client::ChurnWrite,
+) -> std::result::Result<(), ResponseError> {
+    CHURN.increment();
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let mut command = ::redis::cmd("SET");
+    command.arg(&*request.key).arg(&request.value);
+
+    if let Some(ttl) = request.ttl {
+        if ttl.subsec_nanos() == 0 {
+            command.arg("EX").arg(ttl.as_secs());
+        } else {
+            command.arg("PX").arg(ttl.as_millis() as u64);
+        }
+    }
+
+    match timeout(request_timeout, command.query_async::<_, ()>(connection)).await {
+        Ok(Ok(())) => {
+            BYTES_WRITTEN.add(request.value.len() as u64);
+        }
+        Ok(Err(_)) => {
+            CHURN_EX.increment();
+            return Err(ResponseError::Exception);
+        }
+        Err(_) => {
+            CHURN_TIMEOUT.increment();
+            return Err(ResponseError::Timeout);
+        }
+    }
+
+    match timeout(request_timeout, connection.del::<&[u8], ()>(&request.key)).await {
+        Ok(Ok(_)) => {
+            CHURN_OK.increment();
+            Ok(())
+        }
+        Ok(Err(_)) => {
+            CHURN_EX.increment();
+            Err(ResponseError::Exception)
+        }
+        Err(_) => {
+            CHURN_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
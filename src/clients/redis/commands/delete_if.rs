@@ -0,0 +1,60 @@
+use super::*;
+
+/// Removes a key from the cache only if its current value matches the
+/// expected value. Redis has no conditional delete, so this is emulated with a
+/// GET followed by a DEL and is not atomic.
+pub async fn delete_if(
+    connection: &mut Connection<net::Stream>,
+    config: &Config,
+    request: workload::client::DeleteIf,
+) -> std::result::Result<(), ResponseError> {
+    DELETE_IF.increment();
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let value = match timeout(
+        request_timeout,
+        connection.get::<&[u8], Option<Vec<u8>>>(&request.key),
+    )
+    .await
+    {
+        Ok(Ok(Some(value))) => value,
+        Ok(Ok(None)) => {
+            DELETE_IF_MISS.increment();
+            DELETE_IF_OK.increment();
+            return Ok(());
+        }
+        Ok(Err(_)) => {
+            DELETE_IF_EX.increment();
+            return Err(ResponseError::Exception);
+        }
+        Err(_) => {
+            DELETE_IF_TIMEOUT.increment();
+            return Err(ResponseError::Timeout);
+        }
+    };
+
+    BYTES_READ.add(value.len() as u64);
+
+    if !request.condition.matches(&value) {
+        DELETE_IF_MISMATCH.increment();
+        DELETE_IF_OK.increment();
+        return Ok(());
+    }
+
+    match timeout(request_timeout, connection.del::<&[u8], u64>(&request.key)).await {
+        Ok(Ok(_)) => {
+            DELETE_IF_MATCH.increment();
+            DELETE_IF_OK.increment();
+            Ok(())
+        }
+        Ok(Err(_)) => {
+            DELETE_IF_EX.increment();
+            Err(ResponseError::Exception)
+        }
+        Err(_) => {
+            DELETE_IF_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
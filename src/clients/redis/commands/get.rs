@@ -5,8 +5,11 @@ pub async fn get(
     connection: &mut Connection<net::Stream>,
     config: &Config,
     request: workload::client::Get,
-) -> std::result::Result<(), ResponseError> {
+) -> std::result::Result<bool, ResponseError> {
     GET.increment();
+
+    let start = Instant::now();
+
     match timeout(
         config.client().unwrap().request_timeout(),
         connection.get::<&[u8], Option<Vec<u8>>>(&request.key),
@@ -14,14 +17,57 @@ pub async fn get(
     .await
     {
         Ok(Ok(None)) => {
+            let _ = GET_MISS_LATENCY.increment(start.elapsed().as_nanos() as u64);
             RESPONSE_MISS.increment();
             GET_KEY_MISS.increment();
-            Ok(())
+
+            if let Some(populate) = request.populate {
+                POPULATE.increment();
+
+                let mut command = ::redis::cmd("SET");
+                command.arg(&*request.key).arg(populate.value(&request.key));
+
+                if let Some(ttl) = populate.ttl {
+                    if ttl.subsec_nanos() == 0 {
+                        command.arg("EX").arg(ttl.as_secs());
+                    } else {
+                        command.arg("PX").arg(ttl.as_millis() as u64);
+                    }
+                }
+
+                match timeout(
+                    config.client().unwrap().request_timeout(),
+                    command.query_async::<_, ()>(connection),
+                )
+                .await
+                {
+                    Ok(Ok(_)) => POPULATE_OK.increment(),
+                    Ok(Err(_)) => POPULATE_EX.increment(),
+                    Err(_) => POPULATE_TIMEOUT.increment(),
+                }
+            }
+
+            Ok(false)
         }
-        Ok(Ok(Some(_))) => {
+        Ok(Ok(Some(value))) => {
+            let _ = GET_HIT_LATENCY.increment(start.elapsed().as_nanos() as u64);
+            BYTES_READ.add(value.len() as u64);
+            SizeBuckets::record(value.len(), start.elapsed().as_nanos() as u64);
+            if request.expected_len.is_some_and(|len| len != value.len()) {
+                SIZE_MISMATCH.increment();
+            }
+            if let Some(check) = &request.sequence {
+                check.observe(&value);
+            }
+            if let Some(hmac) = &request.hmac {
+                hmac.verify(&request.key, &value);
+            }
+            if let Some(record) = &request.record {
+                record.verify(&value);
+            }
             RESPONSE_HIT.increment();
             GET_KEY_HIT.increment();
-            Ok(())
+            Ok(true)
         }
         Ok(Err(_)) => {
             GET_EX.increment();
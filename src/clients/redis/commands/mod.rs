@@ -1,7 +1,12 @@
 use super::*;
 
 mod add;
+mod chunked_delete;
+mod chunked_get;
+mod chunked_set;
+mod churn_write;
 mod delete;
+mod delete_if;
 mod get;
 mod hash_delete;
 mod hash_exists;
@@ -27,9 +32,16 @@ mod sorted_set_range;
 mod sorted_set_rank;
 mod sorted_set_remove;
 mod sorted_set_score;
+mod touch;
+mod update;
 
 pub use add::*;
+pub use chunked_delete::*;
+pub use chunked_get::*;
+pub use chunked_set::*;
+pub use churn_write::*;
 pub use delete::*;
+pub use delete_if::*;
 pub use get::*;
 pub use hash_delete::*;
 pub use hash_exists::*;
@@ -55,3 +67,5 @@ pub use sorted_set_range::*;
 pub use sorted_set_rank::*;
 pub use sorted_set_remove::*;
 pub use sorted_set_score::*;
+pub use touch::*;
+pub use update::*;
@@ -44,6 +44,7 @@ pub async fn replace(
                 REPLACE_NOT_STORED.increment();
             } else {
                 REPLACE_STORED.increment();
+                BYTES_WRITTEN.add(value.len() as u64);
             }
             Ok(())
         }
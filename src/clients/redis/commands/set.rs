@@ -8,6 +8,10 @@ pub async fn set(
 ) -> std::result::Result<(), ResponseError> {
     SET.increment();
 
+    if request.value.is_empty() {
+        SET_EMPTY.increment();
+    }
+
     let key = &*request.key;
     let value = &*request.value;
 
@@ -31,14 +35,44 @@ pub async fn set(
         }
     }
 
-    match timeout(
-        config.client().unwrap().request_timeout(),
-        command.query_async(connection),
-    )
-    .await
-    {
-        Ok(Ok(())) => {
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let start = Instant::now();
+
+    // an `EXISTS` is pipelined ahead of the set to find whether it created the
+    // key. Unlike the `GET` option of `SET`, it does not return the previous
+    // value and works on servers older than Redis 6.2.
+    let result = if config.client().unwrap().classify_writes() {
+        let mut pipeline = ::redis::pipe();
+
+        pipeline
+            .cmd("EXISTS")
+            .arg(key)
+            .add_command(command.clone())
+            .ignore();
+
+        timeout(
+            request_timeout,
+            pipeline.query_async::<_, (bool,)>(connection),
+        )
+        .await
+        .map(|result| result.map(|(existed,)| Some(existed)))
+    } else {
+        timeout(request_timeout, command.query_async::<_, ()>(connection))
+            .await
+            .map(|result| result.map(|_| None))
+    };
+
+    match result {
+        Ok(Ok(previous)) => {
+            match previous {
+                Some(true) => SET_OVERWRITTEN.increment(),
+                Some(false) => SET_CREATED.increment(),
+                None => {}
+            }
             SET_STORED.increment();
+            record_set_bytes(key.len(), value.len(), request.logical_len);
+            SizeBuckets::record(value.len(), start.elapsed().as_nanos() as u64);
             Ok(())
         }
         Ok(Err(_)) => {
@@ -0,0 +1,43 @@
+use super::*;
+
+/// Replaces the ttl of a key without reading or rewriting its value.
+pub async fn touch(
+    connection: &mut Connection<net::Stream>,
+    config: &Config,
+    request: workload::client::Touch,
+) -> std::result::Result<(), ResponseError> {
+    TOUCH.increment();
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let command = if request.ttl.subsec_nanos() == 0 {
+        let mut command = ::redis::cmd("EXPIRE");
+        command.arg(&*request.key).arg(request.ttl.as_secs());
+        command
+    } else {
+        let mut command = ::redis::cmd("PEXPIRE");
+        command
+            .arg(&*request.key)
+            .arg(request.ttl.as_millis() as u64);
+        command
+    };
+
+    match timeout(request_timeout, command.query_async::<_, u64>(connection)).await {
+        Ok(Ok(touched)) => {
+            // the reply is 0 when the key does not exist
+            if touched == 0 {
+                TOUCH_MISS.increment();
+            }
+            TOUCH_OK.increment();
+            Ok(())
+        }
+        Ok(Err(_)) => {
+            TOUCH_EX.increment();
+            Err(ResponseError::Exception)
+        }
+        Err(_) => {
+            TOUCH_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use super::*;
+
+/// Reads the value for a key, overwrites part of it, and writes it back. Keys
+/// which are not found are not written.
+pub async fn update(
+    connection: &mut Connection<net::Stream>,
+    config: &Config,
+    request: workload::client::Update,
+) -> std::result::Result<(), ResponseError> {
+    UPDATE.increment();
+
+    let request_timeout = config.client().unwrap().request_timeout();
+
+    let mut value = match timeout(
+        request_timeout,
+        connection.get::<&[u8], Option<Vec<u8>>>(&request.key),
+    )
+    .await
+    {
+        Ok(Ok(Some(value))) => value,
+        Ok(Ok(None)) => {
+            UPDATE_MISS.increment();
+            UPDATE_OK.increment();
+            return Ok(());
+        }
+        Ok(Err(_)) => {
+            UPDATE_EX.increment();
+            return Err(ResponseError::Exception);
+        }
+        Err(_) => {
+            UPDATE_TIMEOUT.increment();
+            return Err(ResponseError::Timeout);
+        }
+    };
+
+    BYTES_READ.add(value.len() as u64);
+
+    mutate_value(&mut value, request.mutation_bytes);
+
+    let mut command = ::redis::cmd("SET");
+    command.arg(&*request.key).arg(&value);
+
+    if let Some(ttl) = request.ttl {
+        if ttl.subsec_nanos() == 0 {
+            command.arg("EX").arg(ttl.as_secs());
+        } else {
+            command.arg("PX").arg(ttl.as_millis() as u64);
+        }
+    }
+
+    match timeout(request_timeout, command.query_async::<_, ()>(connection)).await {
+        Ok(Ok(())) => {
+            UPDATE_OK.increment();
+            BYTES_WRITTEN.add(value.len() as u64);
+            Ok(())
+        }
+        Ok(Err(_)) => {
+            UPDATE_EX.increment();
+            Err(ResponseError::Exception)
+        }
+        Err(_) => {
+            UPDATE_TIMEOUT.increment();
+            Err(ResponseError::Timeout)
+        }
+    }
+}
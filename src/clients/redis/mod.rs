@@ -25,6 +25,72 @@ pub fn launch_tasks(runtime: &mut Runtime, config: Config, work_receiver: Receiv
     }
 }
 
+/// Returns the version of the backend at the first endpoint, as reported by
+/// `INFO server`, or `None` if it could not be determined.
+pub async fn server_version(config: &Config) -> Option<(u32, u32, u32)> {
+    let endpoint = config.target().endpoints().first()?;
+    let connector = Connector::new(config).ok()?;
+    let client_config = config.client().unwrap();
+
+    let stream = timeout(client_config.connect_timeout(), connector.connect(endpoint))
+        .await
+        .ok()?
+        .ok()?;
+
+    let redis_connection_info = RedisConnectionInfo {
+        db: 0,
+        username: None,
+        password: None,
+    };
+
+    let mut connection = ::redis::aio::Connection::new(&redis_connection_info, stream)
+        .await
+        .ok()?;
+
+    let mut command = ::redis::cmd("INFO");
+    command.arg("server");
+
+    let info = timeout(
+        client_config.request_timeout(),
+        command.query_async::<_, String>(&mut connection),
+    )
+    .await
+    .ok()?
+    .ok()?;
+
+    let version = info
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("redis_version:"))?;
+
+    let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+
+    Some((
+        parts.next()??,
+        parts.next().flatten().unwrap_or(0),
+        parts.next().flatten().unwrap_or(0),
+    ))
+}
+
+/// Returns the earliest backend version which supports the commands used for
+/// the verb, if the commands have not always been available.
+pub fn required_version(verb: Verb) -> Option<(u32, u32, u32)> {
+    match verb {
+        // `SET` with the `EX`, `PX`, `NX`, and `XX` options
+        Verb::Add | Verb::ChurnWrite | Verb::Replace | Verb::Set | Verb::Update => Some((2, 6, 12)),
+        // `PEXPIRE`
+        Verb::Touch => Some((2, 6, 0)),
+        // variadic `HDEL`, `LPUSH`, `RPUSH`, `SADD`, `SREM`, `ZADD`, and `ZREM`
+        Verb::HashDelete
+        | Verb::ListPushBack
+        | Verb::ListPushFront
+        | Verb::SetAdd
+        | Verb::SetRemove
+        | Verb::SortedSetAdd
+        | Verb::SortedSetRemove => Some((2, 4, 0)),
+        _ => None,
+    }
+}
+
 #[allow(dead_code)]
 #[allow(clippy::slow_vector_initialization)]
 async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Config) -> Result<()> {
@@ -39,8 +105,25 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
 
     let mut connection = None;
 
+    let mut reconnect = false;
+    let mut breaker = CircuitBreaker::new(&config);
+    let mut client_cache = ClientCache::new(&config);
+    let mut lifetime = ConnectionLifetime::new(&config);
+    let stats = ConnectionStats::new(&endpoint);
+
+    start_jitter(&config).await;
+
     while RUNNING.load(Ordering::Relaxed) {
+        breaker.wait().await;
+
+        lifetime.recycle(&mut connection);
+
         if connection.is_none() {
+            if reconnect {
+                reconnect_ratelimit().await;
+            }
+            reconnect = true;
+
             CONNECT.increment();
             connection = match timeout(
                 config.client().unwrap().connect_timeout(),
@@ -52,9 +135,12 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                     CONNECT_OK.increment();
                     CONNECT_CURR.increment();
                     if let Ok(c) = ::redis::aio::Connection::new(&redis_connection_info, c).await {
+                        lifetime.opened();
+                        stats.connected();
                         Some(c)
                     } else {
                         CONNECT_EX.increment();
+                        breaker.record(false);
                         sleep(Duration::from_millis(100)).await;
                         continue;
                     }
@@ -62,12 +148,14 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
                 Ok(Err(e)) => {
                     trace!("error connecting: {e}");
                     CONNECT_EX.increment();
+                    breaker.record(false);
                     sleep(Duration::from_millis(100)).await;
                     continue;
                 }
                 Err(_) => {
                     trace!("connect timeout");
                     CONNECT_TIMEOUT.increment();
+                    breaker.record(false);
                     sleep(Duration::from_millis(100)).await;
                     continue;
                 }
@@ -75,106 +163,185 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
         }
 
         let mut con = connection.take().unwrap();
-        let work_item = work_receiver
-            .recv()
-            .await
-            .map_err(|_| Error::new(ErrorKind::Other, "channel closed"))?;
+        let (work_item, _in_flight) = recv_work(&work_receiver, &config).await?;
+        // the request is serialized as it is sent, so the build latency only
+        // covers the preparation before it is dispatched
+        let build = Instant::now();
+        let histograms = ResponseHistograms::new(&work_item);
+        let context = error_context(&config, &work_item);
 
         REQUEST.increment();
+        let injected = injected_error(&config, &work_item);
+        let mut cached = false;
         let start = Instant::now();
-        let result = match work_item {
-            WorkItem::Request { request, .. } => match request {
-                /*
-                 * PING
-                 */
-                ClientRequest::Ping(r) => ping(&mut con, &config, r).await,
-
-                /*
-                 * KEY-VALUE
-                 */
-                ClientRequest::Add(r) => add(&mut con, &config, r).await,
-                ClientRequest::Delete(r) => delete(&mut con, &config, r).await,
-                ClientRequest::Get(r) => get(&mut con, &config, r).await,
-                ClientRequest::Replace(r) => replace(&mut con, &config, r).await,
-                ClientRequest::Set(r) => set(&mut con, &config, r).await,
-
-                /*
-                 * HASHES (DICTIONARIES)
-                 */
-                ClientRequest::HashDelete(r) => hash_delete(&mut con, &config, r).await,
-                ClientRequest::HashExists(r) => hash_exists(&mut con, &config, r).await,
-                ClientRequest::HashIncrement(r) => hash_increment(&mut con, &config, r).await,
-                // transparently issues either a `hget` or `hmget`
-                ClientRequest::HashGet(r) => hash_get(&mut con, &config, r).await,
-                ClientRequest::HashGetAll(r) => hash_get_all(&mut con, &config, r).await,
-                ClientRequest::HashSet(r) => hash_set(&mut con, &config, r).await,
-
-                /*
-                 * LISTS
-                 */
-                // To truncate, we must fuse an LTRIM at the end of the LPUSH
-                ClientRequest::ListPushFront(r) => list_push_front(&mut con, &config, r).await,
-                // To truncate, we must fuse an RTRIM at the end of the RPUSH
-                ClientRequest::ListPushBack(r) => list_push_back(&mut con, &config, r).await,
-                ClientRequest::ListFetch(r) => list_fetch(&mut con, &config, r).await,
-                ClientRequest::ListLength(r) => list_length(&mut con, &config, r).await,
-                ClientRequest::ListPopFront(r) => list_pop_front(&mut con, &config, r).await,
-                ClientRequest::ListPopBack(r) => list_pop_back(&mut con, &config, r).await,
-
-                /*
-                 * SETS
-                 */
-                ClientRequest::SetAdd(r) => set_add(&mut con, &config, r).await,
-                ClientRequest::SetMembers(r) => set_members(&mut con, &config, r).await,
-                ClientRequest::SetRemove(r) => set_remove(&mut con, &config, r).await,
-
-                /*
-                 * SORTED SETS
-                 */
-                ClientRequest::SortedSetAdd(r) => sorted_set_add(&mut con, &config, r).await,
-                ClientRequest::SortedSetRange(r) => sorted_set_range(&mut con, &config, r).await,
-                ClientRequest::SortedSetIncrement(r) => {
-                    sorted_set_increment(&mut con, &config, r).await
-                }
-                ClientRequest::SortedSetRemove(r) => sorted_set_remove(&mut con, &config, r).await,
-                ClientRequest::SortedSetScore(r) => sorted_set_score(&mut con, &config, r).await,
-                ClientRequest::SortedSetRank(r) => sorted_set_rank(&mut con, &config, r).await,
-
-                /*
-                 * UNSUPPORTED
-                 */
-                _ => {
-                    REQUEST_UNSUPPORTED.increment();
-                    connection = Some(con);
+        let _ = REQUEST_BUILD_LATENCY.increment(start.duration_since(build).as_nanos() as u64);
+
+        // injected errors and gets served from the client-side cache are
+        // never sent
+        let result = if let Some(error) = injected {
+            Err(error)
+        } else {
+            match work_item {
+                WorkItem::Request { request, .. } => match request {
+                    /*
+                     * PING
+                     */
+                    ClientRequest::Ping(r) => ping(&mut con, &config, r).await,
+
+                    /*
+                     * KEY-VALUE
+                     */
+                    ClientRequest::Add(r) => add(&mut con, &config, r).await,
+                    ClientRequest::ChunkedGet(r) => chunked_get(&mut con, &config, r).await,
+                    ClientRequest::ChunkedSet(r) => chunked_set(&mut con, &config, r).await,
+                    ClientRequest::ChunkedDelete(r) => chunked_delete(&mut con, &config, r).await,
+                    ClientRequest::Delete(r) => {
+                        client_cache.invalidate(&r.key);
+                        delete(&mut con, &config, r).await
+                    }
+                    ClientRequest::DeleteIf(r) => {
+                        client_cache.invalidate(&r.key);
+                        delete_if(&mut con, &config, r).await
+                    }
+                    ClientRequest::Get(r) if client_cache.get(&r.key) => {
+                        cached = true;
+                        client_cache_get()
+                    }
+                    ClientRequest::Get(r) => {
+                        let key = r.key.clone();
+                        let result = coalesce_get(&config, &key, get(&mut con, &config, r)).await;
+                        // only keys which were found are cached
+                        if matches!(result, Ok(true)) {
+                            client_cache.insert(key);
+                        }
+                        result.map(|_| ())
+                    }
+                    ClientRequest::Replace(r) => replace(&mut con, &config, r).await,
+                    ClientRequest::Set(r) => {
+                        let _lock = key_lock(&config, &r.key).await;
+                        let key = r.key.clone();
+                        let result = set(&mut con, &config, r).await;
+                        if result.is_ok() {
+                            client_cache.written(key);
+                        }
+                        result
+                    }
+                    ClientRequest::Update(r) => {
+                        client_cache.invalidate(&r.key);
+                        update(&mut con, &config, r).await
+                    }
+                    ClientRequest::ChurnWrite(r) => {
+                        client_cache.invalidate(&r.key);
+                        churn_write(&mut con, &config, r).await
+                    }
+                    ClientRequest::Touch(r) => touch(&mut con, &config, r).await,
+
+                    /*
+                     * HASHES (DICTIONARIES)
+                     */
+                    ClientRequest::HashDelete(r) => hash_delete(&mut con, &config, r).await,
+                    ClientRequest::HashExists(r) => hash_exists(&mut con, &config, r).await,
+                    ClientRequest::HashIncrement(r) => hash_increment(&mut con, &config, r).await,
+                    // transparently issues either a `hget` or `hmget`
+                    ClientRequest::HashGet(r) => hash_get(&mut con, &config, r).await,
+                    ClientRequest::HashGetAll(r) => hash_get_all(&mut con, &config, r).await,
+                    ClientRequest::HashSet(r) => hash_set(&mut con, &config, r).await,
+
+                    /*
+                     * LISTS
+                     */
+                    // To truncate, we must fuse an LTRIM at the end of the LPUSH
+                    ClientRequest::ListPushFront(r) => list_push_front(&mut con, &config, r).await,
+                    // To truncate, we must fuse an RTRIM at the end of the RPUSH
+                    ClientRequest::ListPushBack(r) => list_push_back(&mut con, &config, r).await,
+                    ClientRequest::ListFetch(r) => list_fetch(&mut con, &config, r).await,
+                    ClientRequest::ListLength(r) => list_length(&mut con, &config, r).await,
+                    ClientRequest::ListPopFront(r) => list_pop_front(&mut con, &config, r).await,
+                    ClientRequest::ListPopBack(r) => list_pop_back(&mut con, &config, r).await,
+
+                    /*
+                     * SETS
+                     */
+                    ClientRequest::SetAdd(r) => set_add(&mut con, &config, r).await,
+                    ClientRequest::SetMembers(r) => set_members(&mut con, &config, r).await,
+                    ClientRequest::SetRemove(r) => set_remove(&mut con, &config, r).await,
+
+                    /*
+                     * SORTED SETS
+                     */
+                    ClientRequest::SortedSetAdd(r) => sorted_set_add(&mut con, &config, r).await,
+                    ClientRequest::SortedSetRange(r) => {
+                        sorted_set_range(&mut con, &config, r).await
+                    }
+                    ClientRequest::SortedSetIncrement(r) => {
+                        sorted_set_increment(&mut con, &config, r).await
+                    }
+                    ClientRequest::SortedSetRemove(r) => {
+                        sorted_set_remove(&mut con, &config, r).await
+                    }
+                    ClientRequest::SortedSetScore(r) => {
+                        sorted_set_score(&mut con, &config, r).await
+                    }
+                    ClientRequest::SortedSetRank(r) => sorted_set_rank(&mut con, &config, r).await,
+
+                    /*
+                     * UNSUPPORTED
+                     */
+                    other => {
+                        unsupported(&config, &other);
+                        connection = Some(con);
+                        continue;
+                    }
+                },
+                WorkItem::Reconnect => {
+                    CONNECT_CURR.sub(1);
                     continue;
                 }
-            },
-            WorkItem::Reconnect => {
-                CONNECT_CURR.sub(1);
-                continue;
             }
         };
 
-        REQUEST_OK.increment();
+        if injected.is_none() && !cached {
+            REQUEST_OK.increment();
+            lifetime.request();
+            stats.request();
+        }
 
         let stop = Instant::now();
 
         let latency_ns = stop.duration_since(start).as_nanos() as u64;
 
+        processing_delay(&config).await;
+        think_time(&config, latency_ns).await;
+
+        breaker.record(result.is_ok());
+
+        if let Err(e) = &result {
+            log_error(&context, e);
+        }
+
+        log_access(&context, &result, latency_ns);
+
         match result {
             Ok(_) => {
                 connection = Some(con);
                 RESPONSE_OK.increment();
 
-                let _ = RESPONSE_LATENCY.increment(latency_ns);
+                // a client cache hit has no meaningful latency, so it would
+                // only pull the percentiles down
+                if !cached {
+                    stats.ok(latency_ns);
+                    histograms.record_success(&config, latency_ns);
+                }
             }
-            Err(ResponseError::Exception) => {
+            Err(ResponseError::Exception) | Err(ResponseError::Unauthorized) => {
                 CONNECT_CURR.decrement();
                 RESPONSE_EX.increment();
+                let _ = RESPONSE_EX_LATENCY.increment(latency_ns);
             }
             Err(ResponseError::Timeout) => {
                 CONNECT_CURR.decrement();
                 RESPONSE_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency_ns);
             }
             Err(ResponseError::Ratelimited) => {
                 RESPONSE_RATELIMITED.increment();
@@ -182,6 +349,7 @@ async fn task(work_receiver: Receiver<WorkItem>, endpoint: String, config: Confi
             }
             Err(ResponseError::BackendTimeout) => {
                 RESPONSE_BACKEND_TIMEOUT.increment();
+                let _ = RESPONSE_TIMEOUT_LATENCY.increment(latency_ns);
                 connection = Some(con);
             }
         }
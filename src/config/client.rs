@@ -1,6 +1,56 @@
 use super::*;
+use hyper::header::HeaderName;
 
-#[derive(Clone, Deserialize)]
+fn circuit_breaker_cooldown() -> u64 {
+    1000
+}
+
+fn near_timeout_band() -> f64 {
+    10.0
+}
+
+fn max_closed_fraction() -> f64 {
+    0.5
+}
+
+/// Timeouts (in milliseconds) above this are likely a misconfiguration and
+/// will produce a warning at startup.
+const TIMEOUT_WARNING_THRESHOLD: u64 = 10_000;
+
+/// How a client task spends its processing delay.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessingDelay {
+    #[default]
+    Sleep,
+    Spin,
+}
+
+/// The metric used to rank the endpoints in the endpoint comparison.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointRank {
+    P50,
+    #[default]
+    P99,
+    ErrorRate,
+}
+
+/// Whether the configured operations are checked against the protocol and
+/// backend before the run.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatibilityCheck {
+    #[default]
+    Off,
+    /// Warn about any operations which are not supported.
+    Warn,
+    /// Abort the run if any operations are not supported.
+    Strict,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Client {
     /// The number of connections this process will have to each endpoint.
     poolsize: usize,
@@ -11,12 +61,42 @@ pub struct Client {
     connect_timeout: u64,
     /// Request timeout
     request_timeout: u64,
+    /// An optional upper bound (in milliseconds) for the request timeout. If
+    /// the configured request timeout exceeds this, it will be clamped. This
+    /// guards against copy-paste mistakes where timeouts would never fire.
+    #[serde(default)]
+    max_request_timeout: Option<u64>,
+    /// Successful responses with a latency within this percentage below the
+    /// request timeout are counted as near timeouts. The default is 10 %.
+    #[serde(default = "near_timeout_band")]
+    near_timeout_band: f64,
+    /// The fraction (0.0 - 1.0) of client tasks which may exit because their
+    /// work queue closed unexpectedly before the run is aborted. Defaults to
+    /// 0.5.
+    #[serde(default = "max_closed_fraction")]
+    max_closed_fraction: f64,
     // number of threads for client tasks
     threads: usize,
     /// Specifies the rate at which connections should randomly reconnect. This
     /// is useful to model steady-state connect pressure on a backend.
     #[serde(default)]
     reconnect_rate: u64,
+    /// An upper bound on the rate (per second) at which connections will be
+    /// re-established across all client tasks. Reconnects beyond this rate
+    /// are delayed until they are allowed. This prevents reconnect storms
+    /// when many connections fail at once. Unlimited if not set.
+    #[serde(default)]
+    max_reconnect_rate: u64,
+    /// When set, connections are closed and re-established once they have
+    /// been open for this long (in milliseconds). This models client libraries
+    /// which rotate their connections.
+    #[serde(default)]
+    max_connection_age: Option<u64>,
+    /// When set, connections are closed and re-established once they have
+    /// sent this many requests. Connection recycling is currently used by the
+    /// Memcache, RESP, Ping, and HTTP/1.1 clients.
+    #[serde(default)]
+    max_requests_per_connection: Option<u64>,
 
     /// Specify the default sizes for the read and write buffers (in bytes).
     /// It is useful to increase the sizes if you expect to send and/or receive
@@ -30,9 +110,227 @@ pub struct Client {
     read_buffer_size: usize,
     #[serde(default = "default_buffer_size")]
     write_buffer_size: usize,
+
+    /// Names of environment variables which hold API keys. When more than one
+    /// is provided, each member of the connection pool authenticates using the
+    /// key at `index % len`, allowing a single run to act as several accounts.
+    /// Currently only used by the Momento client.
+    ///
+    /// Defaults to `MOMENTO_API_KEY`.
+    #[serde(default)]
+    api_key_env: Vec<String>,
+
+    /// The path to a file which holds the API key. When set, this is used
+    /// instead of `api_key_env`, which can then name at most one variable.
+    /// Currently only used by the Momento client.
+    #[serde(default)]
+    api_key_file: Option<String>,
+
+    /// A command which prints a fresh API key to stdout. When a request fails
+    /// because the credentials were rejected, the client is rebuilt with the
+    /// key from this command, or by re-reading `api_key_file` if no command is
+    /// set. This keeps long runs alive across token rotation. Currently only
+    /// used by the Momento client.
+    #[serde(default)]
+    api_key_refresh_command: Option<String>,
+
+    /// The number of throwaway requests each client will issue after it is
+    /// created and before it begins to process the workload. This ensures
+    /// that channels, TLS sessions, and backend routing are warm. These
+    /// requests are not included in the metrics. Currently only used by the
+    /// Momento client.
+    #[serde(default)]
+    prewarm_requests_per_connection: usize,
+
+    /// When set, client tasks poll an empty work queue with this delay (in
+    /// microseconds, jittered by +/- 50%) instead of awaiting the next item.
+    #[serde(default)]
+    idle_backoff: Option<u64>,
+
+    /// An optional cap on the bytes of request payloads (keys and values)
+    /// which are queued for the client tasks or are in flight until their
+    /// request completes. While the cap is exceeded, the workload waits for
+    /// the tasks to catch up instead of buffering more requests. This bounds
+    /// memory use when the backend stalls with large values.
+    #[serde(default)]
+    max_buffered_bytes: Option<u64>,
+
+    /// When set, each client task waits for a random delay of up to this many
+    /// milliseconds before issuing its first request. This decorrelates the
+    /// request instants of the tasks, which would otherwise tend to run in
+    /// lockstep and produce artificial micro-bursts.
+    #[serde(default)]
+    start_jitter: Option<u64>,
+
+    /// When set, each client task waits for this many microseconds after it
+    /// receives a response and before it issues its next request. This models
+    /// client-side processing which competes with I/O for the runtime.
+    #[serde(default)]
+    processing_delay: Option<u64>,
+
+    /// How the processing delay is spent, either `sleep` (the default), which
+    /// yields the runtime thread, or `spin`, which keeps it busy.
+    #[serde(default)]
+    processing_delay_kind: ProcessingDelay,
+
+    /// When set, each client task waits for this multiple of the latency of
+    /// each response before it issues its next request. The wait is jittered
+    /// between half and one and a half times this, so that the load backs off
+    /// as the backend slows down.
+    #[serde(default)]
+    think_time_factor: Option<f64>,
+
+    /// The maximum think time in microseconds. By default the think time is
+    /// not capped.
+    #[serde(default)]
+    think_time_max: Option<u64>,
+
+    /// When set, concurrent gets for the same key share a single backend
+    /// request, modeling a client-side request coalescing cache. Gets which
+    /// are coalesced are counted but do not send a request. Most useful with a
+    /// skewed key distribution. Currently used by the Momento and RESP clients.
+    #[serde(default)]
+    coalesce_gets: bool,
+
+    /// When set, each client task keeps a local LRU cache of up to this many
+    /// keys, modeling a client-side read cache. A get for a key in the local
+    /// cache is counted as a client cache hit and does not send a request, and
+    /// its latency is not recorded.
+    /// Successful gets populate the cache and writes or deletes invalidate
+    /// the key. Currently used by the Momento and RESP clients.
+    #[serde(default)]
+    client_cache_size: Option<usize>,
+
+    /// When set, successful sets populate the client cache instead of only
+    /// invalidating the key.
+    #[serde(default)]
+    client_cache_populate_on_set: bool,
+
+    /// When set, each successful set is classified as creating a new key or
+    /// overwriting an existing one. The RESP client pipelines an `EXISTS` of
+    /// the key ahead of each set, which adds a small command to the measured
+    /// set latency, and a write by another client between the two is
+    /// misclassified. The Memcache and Momento clients track the keys which
+    /// have been written, so a key written outside of this process, or which
+    /// has expired or been evicted, is misclassified.
+    #[serde(default)]
+    classify_writes: bool,
+
+    /// When set, concurrent sets for the same key are serialized so that only
+    /// one is in flight at a time, modeling a client which locks each key for
+    /// writes. The time spent waiting for the lock is included in the request
+    /// latency. By default, concurrent sets race. Currently used by the
+    /// Memcache, Momento, and RESP clients.
+    #[serde(default)]
+    per_key_serialize: bool,
+
+    /// When set, the requests and errors for each connection are reported at
+    /// the end of the test, ordered by the error rate, so that connections to
+    /// a degraded backend node stand out. Each client task counts as one
+    /// connection. The Momento client has no endpoints, so its connections
+    /// are identified by their account (see `api_key_env`).
+    #[serde(default)]
+    connection_stats: bool,
+
+    /// When set, the summary compares the endpoints side by side, with the
+    /// p50 and p99 response latency and the error rate of each, ranked from
+    /// best to worst. This is intended for runs against endpoints in several
+    /// regions. Momento accounts are compared as endpoints, as in
+    /// `connection_stats`.
+    #[serde(default)]
+    endpoint_comparison: bool,
+
+    /// The metric used to rank the endpoint comparison, one of `p50`, `p99`
+    /// (the default), or `error_rate`.
+    #[serde(default)]
+    endpoint_rank: EndpointRank,
+
+    /// When set, a probe builds a fresh client, issues a single get, and tears
+    /// the client down at this interval (in milliseconds). This measures the
+    /// cold-connection latency separately from the workload. The interval is
+    /// limited to at least one second to avoid connection storms. Currently
+    /// only used by the Momento client.
+    #[serde(default)]
+    cold_probe_interval: Option<u64>,
+
+    /// When set, a client task which sees this many consecutive failures
+    /// (errors, timeouts, or failed connects) opens its circuit breaker and
+    /// pauses for the cooldown before probing the backend with a single
+    /// request. This avoids hammering a backend which is down.
+    #[serde(default)]
+    circuit_breaker_threshold: Option<u32>,
+
+    /// The time (in milliseconds) that a task pauses once its circuit breaker
+    /// has opened. The default is one second.
+    #[serde(default = "circuit_breaker_cooldown")]
+    circuit_breaker_cooldown: u64,
+
+    /// When set, a request for an operation which the protocol does not
+    /// support aborts the run instead of being skipped. This catches a
+    /// workload which does not match the backend.
+    #[serde(default)]
+    strict_operations: bool,
+
+    /// When set to `warn` or `strict`, the operations in the workload are
+    /// checked before the run against those implemented for the protocol and,
+    /// for RESP, against the version reported by the backend. Unsupported
+    /// operations are warned about, or abort the run when `strict`.
+    #[serde(default)]
+    compatibility_check: CompatibilityCheck,
+
+    /// The name of a response header which holds the time the backend spent
+    /// processing the request. When set, the reported time is recorded in the
+    /// `server_latency` histogram and the summary shows it next to the client
+    /// latency. The header may hold a plain number or a `Server-Timing` entry
+    /// with a `dur` parameter. Currently only used by the HTTP clients.
+    #[serde(default)]
+    server_latency_header: Option<String>,
+
+    /// The unit of the server latency header. One of `ns`, `us`, or `ms`.
+    /// Defaults to `ms`.
+    #[serde(default)]
+    server_latency_unit: Option<LatencyUnit>,
+
+    /// An optional request header which carries a generated correlation ID,
+    /// such as `x-request-id`. The ID is logged with any failure of the
+    /// request so that it can be matched with the backend logs. Currently only
+    /// used by the HTTP clients.
+    #[serde(default)]
+    correlation_id_header: Option<String>,
+
+    // the header names, parsed once the config is loaded
+    #[serde(skip)]
+    server_latency_header_name: Option<HeaderName>,
+    #[serde(skip)]
+    correlation_id_header_name: Option<HeaderName>,
+}
+
+/// Parses the name of the header set by the named option, exiting if it is
+/// not a valid header name.
+fn header_name(option: &str, header: &str) -> HeaderName {
+    match HeaderName::from_bytes(header.as_bytes()) {
+        Ok(name) => name,
+        Err(_) => {
+            eprintln!("client `{option}` is not a valid header name: {header}");
+            std::process::exit(1);
+        }
+    }
 }
 
 impl Client {
+    /// Parses the header names so that the clients do not need to.
+    pub fn validate(&mut self) {
+        self.server_latency_header_name = self
+            .server_latency_header
+            .as_deref()
+            .map(|header| header_name("server_latency_header", header));
+
+        self.correlation_id_header_name = self
+            .correlation_id_header
+            .as_deref()
+            .map(|header| header_name("correlation_id_header", header));
+    }
+
     pub fn connect_timeout(&self) -> Duration {
         Duration::from_millis(self.connect_timeout)
     }
@@ -42,7 +340,51 @@ impl Client {
     }
 
     pub fn request_timeout(&self) -> Duration {
-        Duration::from_millis(self.request_timeout)
+        let timeout = match self.max_request_timeout {
+            Some(max) => std::cmp::min(self.request_timeout, max),
+            None => self.request_timeout,
+        };
+
+        Duration::from_millis(timeout)
+    }
+
+    pub fn max_closed_fraction(&self) -> f64 {
+        self.max_closed_fraction.clamp(0.0, 1.0)
+    }
+
+    /// Returns the latency above which a successful response is counted as a
+    /// near timeout.
+    pub fn near_timeout_threshold(&self) -> Duration {
+        let band = self.near_timeout_band.clamp(0.0, 100.0) / 100.0;
+        self.request_timeout().mul_f64(1.0 - band)
+    }
+
+    /// Returns `true` if the configured request timeout was clamped by the
+    /// `max_request_timeout`.
+    pub fn request_timeout_clamped(&self) -> bool {
+        self.max_request_timeout
+            .is_some_and(|max| self.request_timeout > max)
+    }
+
+    /// Returns warnings for timeouts which are suspiciously long.
+    pub fn timeout_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if self.connect_timeout > TIMEOUT_WARNING_THRESHOLD {
+            warnings.push(format!(
+                "connect_timeout of {} ms exceeds {TIMEOUT_WARNING_THRESHOLD} ms",
+                self.connect_timeout
+            ));
+        }
+
+        if self.request_timeout().as_millis() as u64 > TIMEOUT_WARNING_THRESHOLD {
+            warnings.push(format!(
+                "request_timeout of {} ms exceeds {TIMEOUT_WARNING_THRESHOLD} ms, stalls may go undetected",
+                self.request_timeout().as_millis()
+            ));
+        }
+
+        warnings
     }
 
     pub fn poolsize(&self) -> usize {
@@ -57,6 +399,141 @@ impl Client {
         NonZeroU64::new(self.reconnect_rate)
     }
 
+    pub fn api_key_env(&self) -> Vec<String> {
+        if self.api_key_env.is_empty() {
+            vec!["MOMENTO_API_KEY".to_string()]
+        } else {
+            self.api_key_env.clone()
+        }
+    }
+
+    pub fn api_key_file(&self) -> Option<&str> {
+        self.api_key_file.as_deref()
+    }
+
+    pub fn api_key_refresh_command(&self) -> Option<&str> {
+        self.api_key_refresh_command.as_deref()
+    }
+
+    pub fn max_reconnect_rate(&self) -> Option<NonZeroU64> {
+        NonZeroU64::new(self.max_reconnect_rate)
+    }
+
+    pub fn max_connection_age(&self) -> Option<Duration> {
+        self.max_connection_age
+            .filter(|age| *age > 0)
+            .map(Duration::from_millis)
+    }
+
+    pub fn max_requests_per_connection(&self) -> Option<u64> {
+        self.max_requests_per_connection
+            .filter(|requests| *requests > 0)
+    }
+
+    pub fn prewarm_requests_per_connection(&self) -> usize {
+        self.prewarm_requests_per_connection
+    }
+
+    pub fn idle_backoff(&self) -> Option<Duration> {
+        self.idle_backoff.map(Duration::from_micros)
+    }
+
+    pub fn max_buffered_bytes(&self) -> Option<u64> {
+        self.max_buffered_bytes
+    }
+
+    pub fn coalesce_gets(&self) -> bool {
+        self.coalesce_gets
+    }
+
+    pub fn client_cache_size(&self) -> Option<usize> {
+        self.client_cache_size.filter(|size| *size > 0)
+    }
+
+    pub fn client_cache_populate_on_set(&self) -> bool {
+        self.client_cache_populate_on_set
+    }
+
+    pub fn classify_writes(&self) -> bool {
+        self.classify_writes
+    }
+
+    pub fn per_key_serialize(&self) -> bool {
+        self.per_key_serialize
+    }
+
+    pub fn connection_stats(&self) -> bool {
+        self.connection_stats
+    }
+
+    pub fn endpoint_comparison(&self) -> bool {
+        self.endpoint_comparison
+    }
+
+    pub fn endpoint_rank(&self) -> EndpointRank {
+        self.endpoint_rank
+    }
+
+    pub fn strict_operations(&self) -> bool {
+        self.strict_operations
+    }
+
+    pub fn compatibility_check(&self) -> CompatibilityCheck {
+        self.compatibility_check
+    }
+
+    pub fn server_latency_header(&self) -> Option<&HeaderName> {
+        self.server_latency_header_name.as_ref()
+    }
+
+    pub fn correlation_id_header(&self) -> Option<&HeaderName> {
+        self.correlation_id_header_name.as_ref()
+    }
+
+    pub fn server_latency_unit(&self) -> LatencyUnit {
+        self.server_latency_unit
+            .unwrap_or(LatencyUnit::Milliseconds)
+    }
+
+    pub fn processing_delay(&self) -> Option<Duration> {
+        self.processing_delay
+            .filter(|delay| *delay > 0)
+            .map(Duration::from_micros)
+    }
+
+    pub fn processing_delay_kind(&self) -> ProcessingDelay {
+        self.processing_delay_kind
+    }
+
+    pub fn think_time_factor(&self) -> Option<f64> {
+        self.think_time_factor
+            .filter(|factor| factor.is_finite() && *factor > 0.0)
+    }
+
+    pub fn think_time_max(&self) -> Option<Duration> {
+        self.think_time_max.map(Duration::from_micros)
+    }
+
+    pub fn start_jitter(&self) -> Option<Duration> {
+        self.start_jitter
+            .filter(|jitter| *jitter > 0)
+            .map(Duration::from_millis)
+    }
+
+    pub fn circuit_breaker_threshold(&self) -> Option<u32> {
+        self.circuit_breaker_threshold
+            .filter(|threshold| *threshold > 0)
+    }
+
+    pub fn circuit_breaker_cooldown(&self) -> Duration {
+        Duration::from_millis(self.circuit_breaker_cooldown)
+    }
+
+    pub fn cold_probe_interval(&self) -> Option<Duration> {
+        self.cold_probe_interval
+            .map(|interval| Duration::from_millis(std::cmp::max(1000, interval)))
+    }
+
     pub fn read_buffer_size(&self) -> usize {
         // rounds the read buffer size up to the next nearest multiple of the
         // pagesize
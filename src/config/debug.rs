@@ -3,6 +3,7 @@ pub const KB: usize = 1024 * B;
 pub const MB: usize = 1024 * KB;
 pub const GB: usize = 1024 * MB;
 
+use super::*;
 use ringlog::Level;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +14,7 @@ const LOG_BACKUP: Option<String> = None;
 const LOG_MAX_SIZE: u64 = GB as u64;
 const LOG_QUEUE_DEPTH: usize = 4096;
 const LOG_SINGLE_MESSAGE_SIZE: usize = KB;
+const PROFILE_FREQUENCY: u32 = 99;
 
 // helper functions
 fn log_level() -> Level {
@@ -35,12 +37,21 @@ fn log_queue_depth() -> usize {
     LOG_QUEUE_DEPTH
 }
 
+fn watch_latency() -> bool {
+    true
+}
+
 fn log_single_message_size() -> usize {
     LOG_SINGLE_MESSAGE_SIZE
 }
 
+fn profile_frequency() -> u32 {
+    PROFILE_FREQUENCY
+}
+
 // struct definitions
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Debug {
     #[serde(with = "LevelDef")]
     #[serde(default = "log_level")]
@@ -55,6 +66,46 @@ pub struct Debug {
     log_queue_depth: usize,
     #[serde(default = "log_single_message_size")]
     log_single_message_size: usize,
+    /// FOR TESTING ONLY: the fraction of requests (0.0 - 1.0) which will fail
+    /// with a synthetic timeout instead of being sent. Error injection is only
+    /// supported by the Momento, RESP, and null clients.
+    #[serde(default)]
+    inject_timeout_rate: f64,
+    /// FOR TESTING ONLY: the fraction of requests (0.0 - 1.0) which will fail
+    /// with a synthetic exception instead of being sent.
+    #[serde(default)]
+    inject_exception_rate: f64,
+    /// FOR DEBUGGING ONLY: stop the run on the first failed request and log
+    /// the operation, key, and error. This is intended for diagnosing a new
+    /// config or backend, not for measurement.
+    #[serde(default)]
+    stop_on_error: bool,
+    /// When set, failed requests are counted by operation and the start of
+    /// the error message, and the counts are logged as a rollup every this
+    /// many seconds instead of logging individual failures. The rollup of the
+    /// final, partial interval is logged when the test ends.
+    #[serde(default)]
+    error_rollup_interval: Option<u64>,
+    /// Keys for which every operation is logged along with its result. An
+    /// entry may contain `*` wildcards to watch every matching key, eg:
+    /// `user:42:*`. This is intended for investigating a few keys which
+    /// behave pathologically and should match only a small set of keys.
+    #[serde(default)]
+    watch_keys: Vec<String>,
+    /// Whether the latency is included when logging operations on the
+    /// `watch_keys`. Enabled by default.
+    #[serde(default = "watch_latency")]
+    watch_latency: bool,
+    /// When set, a sampling profiler runs for the duration of the test and
+    /// writes folded stacks, suitable for rendering as a flamegraph, to this
+    /// file. Requires building with the `profiling` feature and with
+    /// `RUSTFLAGS="-C force-frame-pointers=yes"`.
+    #[serde(default)]
+    profile_file: Option<String>,
+    /// The sampling frequency of the profiler in samples per second of CPU
+    /// time.
+    #[serde(default = "profile_frequency")]
+    profile_frequency: u32,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -97,6 +148,69 @@ impl Debug {
     pub fn log_single_message_size(&self) -> usize {
         self.log_single_message_size
     }
+
+    pub fn inject_timeout_rate(&self) -> f64 {
+        self.inject_timeout_rate.clamp(0.0, 1.0)
+    }
+
+    pub fn inject_exception_rate(&self) -> f64 {
+        self.inject_exception_rate.clamp(0.0, 1.0)
+    }
+
+    pub fn stop_on_error(&self) -> bool {
+        self.stop_on_error
+    }
+
+    pub fn error_rollup_interval(&self) -> Option<std::time::Duration> {
+        self.error_rollup_interval
+            .map(std::time::Duration::from_secs)
+    }
+
+    /// Returns `true` if any keys are watched.
+    pub fn watching(&self) -> bool {
+        !self.watch_keys.is_empty()
+    }
+
+    /// Returns `true` if the key matches any of the `watch_keys`.
+    pub fn is_watched(&self, key: &[u8]) -> bool {
+        let key = String::from_utf8_lossy(key);
+
+        self.watch_keys
+            .iter()
+            .any(|pattern| wildcard_match(pattern, &key))
+    }
+
+    pub fn watch_latency(&self) -> bool {
+        self.watch_latency
+    }
+
+    pub fn profile_file(&self) -> Option<String> {
+        self.profile_file.clone()
+    }
+
+    pub fn profile_frequency(&self) -> u32 {
+        self.profile_frequency.clamp(1, 1000)
+    }
+
+    /// Returns `true` if synthetic errors will be injected.
+    pub fn inject_errors(&self) -> bool {
+        self.inject_timeout_rate() > 0.0 || self.inject_exception_rate() > 0.0
+    }
+
+    pub fn validate(&self, general: &General) {
+        if self.inject_errors()
+            && !matches!(
+                general.protocol(),
+                Protocol::Momento | Protocol::Resp | Protocol::Null
+            )
+        {
+            eprintln!(
+                "debug `inject_timeout_rate` and `inject_exception_rate` are not supported by the {:?} protocol",
+                general.protocol()
+            );
+            std::process::exit(1);
+        }
+    }
 }
 
 // trait implementations
@@ -109,6 +223,43 @@ impl Default for Debug {
             log_max_size: log_max_size(),
             log_queue_depth: log_queue_depth(),
             log_single_message_size: log_single_message_size(),
+            inject_timeout_rate: 0.0,
+            inject_exception_rate: 0.0,
+            stop_on_error: false,
+            profile_file: None,
+            profile_frequency: profile_frequency(),
+            error_rollup_interval: None,
+            watch_keys: Vec::new(),
+            watch_latency: watch_latency(),
         }
     }
 }
+
+/// Matches a key against a pattern in which each `*` matches any sequence of
+/// characters. A pattern without a wildcard must match the key exactly.
+fn wildcard_match(pattern: &str, key: &str) -> bool {
+    let mut parts = pattern.split('*');
+
+    // split always yields at least one part
+    let Some(mut rest) = key.strip_prefix(parts.next().unwrap()) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+
+    // the parts between wildcards are matched at their earliest position,
+    // leaving as much of the key as possible for the remaining parts
+    for part in middle {
+        let Some(index) = rest.find(part) else {
+            return false;
+        };
+
+        rest = &rest[(index + part.len())..];
+    }
+
+    rest.ends_with(last)
+}
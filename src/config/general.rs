@@ -1,21 +1,185 @@
 use super::*;
 use rand::Rng;
 use rand_xoshiro::Seed512;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha512};
+use std::collections::BTreeMap;
 
 pub fn metrics_interval() -> String {
     "100ms".into()
 }
 
-#[derive(Clone, Deserialize)]
+/// The unit used when displaying latencies. Latencies are always recorded in
+/// nanoseconds, this only changes how they are formatted for output.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum LatencyUnit {
+    #[serde(rename = "ns")]
+    Nanoseconds,
+    #[default]
+    #[serde(rename = "us")]
+    Microseconds,
+    #[serde(rename = "ms")]
+    Milliseconds,
+}
+
+impl LatencyUnit {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Nanoseconds => "ns",
+            Self::Microseconds => "us",
+            Self::Milliseconds => "ms",
+        }
+    }
+
+    /// Formats a latency, given in nanoseconds, in this unit. Microseconds are
+    /// truncated to whole numbers, as in the default output, while
+    /// milliseconds keep three fractional digits so that sub-millisecond
+    /// latencies remain visible.
+    pub fn format(&self, nanoseconds: u64) -> String {
+        match self {
+            Self::Nanoseconds => format!("{nanoseconds}"),
+            Self::Microseconds => format!("{}", nanoseconds / 1000),
+            Self::Milliseconds => format!("{:.3}", nanoseconds as f64 / 1_000_000.0),
+        }
+    }
+
+    /// Converts a latency in this unit to nanoseconds.
+    pub fn to_nanoseconds(&self, value: f64) -> u64 {
+        match self {
+            Self::Nanoseconds => value as u64,
+            Self::Microseconds => (value * 1000.0) as u64,
+            Self::Milliseconds => (value * 1_000_000.0) as u64,
+        }
+    }
+
+    /// Converts a latency in nanoseconds to this unit.
+    pub fn convert(&self, nanoseconds: u64) -> f64 {
+        match self {
+            Self::Nanoseconds => nanoseconds as f64,
+            Self::Microseconds => nanoseconds as f64 / 1000.0,
+            Self::Milliseconds => nanoseconds as f64 / 1_000_000.0,
+        }
+    }
+}
+
+/// The unit used when displaying throughput in bytes. Byte counts are always
+/// recorded in bytes, and machine readable outputs remain in bytes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum ThroughputUnit {
+    #[default]
+    #[serde(rename = "bytes")]
+    Bytes,
+    #[serde(rename = "KiB")]
+    Kibibytes,
+    #[serde(rename = "MiB")]
+    Mebibytes,
+    #[serde(rename = "GiB")]
+    Gibibytes,
+    /// Chooses the largest unit in which each value is at least one.
+    #[serde(rename = "auto")]
+    Auto,
+}
+
+impl ThroughputUnit {
+    const SCALED: [Self; 4] = [
+        Self::Bytes,
+        Self::Kibibytes,
+        Self::Mebibytes,
+        Self::Gibibytes,
+    ];
+
+    /// Returns the label for the unit, which is `None` when the unit is chosen
+    /// separately for each value.
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            Self::Bytes => Some("bytes/s"),
+            Self::Kibibytes => Some("KiB/s"),
+            Self::Mebibytes => Some("MiB/s"),
+            Self::Gibibytes => Some("GiB/s"),
+            Self::Auto => None,
+        }
+    }
+
+    /// Formats a rate, given in bytes per second, in this unit. When the unit
+    /// is chosen automatically the value includes its label.
+    pub fn format(&self, bytes_per_second: f64) -> String {
+        match self {
+            Self::Auto => {
+                let unit = Self::SCALED
+                    .iter()
+                    .rev()
+                    .find(|unit| bytes_per_second >= unit.divisor())
+                    .unwrap_or(&Self::Bytes);
+
+                format!(
+                    "{:.2} {}",
+                    bytes_per_second / unit.divisor(),
+                    unit.label().unwrap()
+                )
+            }
+            _ => format!("{:.2}", bytes_per_second / self.divisor()),
+        }
+    }
+
+    fn divisor(&self) -> f64 {
+        match self {
+            Self::Bytes | Self::Auto => 1.0,
+            Self::Kibibytes => 1024.0,
+            Self::Mebibytes => 1024.0 * 1024.0,
+            Self::Gibibytes => 1024.0 * 1024.0 * 1024.0,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct General {
     /// The protocol to be used for the test.
     protocol: Protocol,
-    /// The reporting interval in seconds.
+    /// The reporting interval in seconds. May also be given as
+    /// `report_interval`.
+    #[serde(alias = "report_interval")]
     interval: u64,
+    /// When set, the reports are aligned to wall clock boundaries which are a
+    /// multiple of the reporting interval, for example every 10 seconds past
+    /// the minute for a 10 second interval. Instances with the same interval
+    /// then report in sync, which makes it easy to correlate their metrics.
+    /// The first window is shortened to reach the boundary.
+    #[serde(default)]
+    align_reports: bool,
+    /// The interval in seconds at which the reported stats are reset. Each
+    /// report covers the time since the last reset, allowing windows which
+    /// span several reports. Rounded down to a multiple of the reporting
+    /// interval. Defaults to the reporting interval.
+    #[serde(default)]
+    reset_interval: Option<u64>,
     /// The test duration in seconds.
     duration: u64,
+    /// Optionally, a wall-clock limit in seconds for the whole process. If it
+    /// is exceeded, for example because requests are hung during shutdown, the
+    /// cumulative stats are printed and the process exits with an error. Must
+    /// be longer than the test duration.
+    #[serde(default)]
+    hard_deadline: Option<u64>,
+    /// When set, the wall clock is compared against the monotonic clock once a
+    /// second and a warning is logged if they disagree by more than this many
+    /// milliseconds, such as when the clock is stepped by NTP. Latencies use
+    /// the monotonic clock, but wall clock timestamps and scheduling may be
+    /// affected.
+    #[serde(default)]
+    clock_skew_threshold: Option<u64>,
+    /// The number of times to run the test. Each trial is a fresh run with
+    /// its own clients and, if set, its own cache name (`<cache_name>-<trial>`)
+    /// and metrics output. The mean, standard deviation, min, and max of the
+    /// key metrics across trials are reported at the end. Defaults to one.
+    #[serde(default)]
+    trials: Option<usize>,
+    /// The number of times a trial is retried if it does not receive any
+    /// successful responses, such as during a transient backend outage. This
+    /// is separate from any per-request retries. The counters used for the
+    /// assertions are cumulative across the attempts. Defaults to zero.
+    #[serde(default)]
+    run_retries: usize,
     /// Optional path to output metrics. The extension, if specified, is
     /// ignored and the metrics stored in the format specified below.
     #[serde(default)]
@@ -32,6 +196,172 @@ pub struct General {
     /// The initial seed for initializing the PRNGs. This can be any string and
     /// we will hash it to determine a corresponding seed.
     initial_seed: Option<String>,
+    /// The unit used to display latencies in the human readable output. One
+    /// of `ns`, `us`, or `ms`. Defaults to `us`.
+    #[serde(default)]
+    latency_unit: LatencyUnit,
+    /// The unit used to display the goodput in the human readable output. One
+    /// of `bytes`, `KiB`, `MiB`, `GiB`, or `auto` to choose a unit for each
+    /// value. Defaults to `bytes`.
+    #[serde(default)]
+    throughput_unit: ThroughputUnit,
+    /// When set, the human readable output includes a sparkline of the p50 and
+    /// p99 response latency over this many of the most recent reporting
+    /// intervals. Disabled when stdout is not a terminal.
+    #[serde(default)]
+    sparkline: Option<usize>,
+    /// Optional path template for writing the latency histograms at the end of
+    /// the run. Each operation family (`get`, `set`, `delete`) is written to
+    /// its own file by replacing `{op}` in the path, and the combined response
+    /// latency is written with `{op}` replaced by `all`. Each line of the file
+    /// holds the start, end, and count of one non-empty bucket in nanoseconds.
+    #[serde(default)]
+    latency_histogram_output: Option<String>,
+    /// Optional path to write a machine readable (JSON) summary of the run to
+    /// when the test completes.
+    #[serde(default)]
+    summary_output: Option<String>,
+    /// When set, the summary also includes the configuration of the run with
+    /// any overrides applied, so that the summary records what was run.
+    /// Options which are not set are written as `null`, as their defaults are
+    /// only applied when they are used. Secrets such as keys and passwords are
+    /// omitted.
+    #[serde(default)]
+    summary_config: bool,
+    /// Per-operation outcome rate assertions which are checked against the
+    /// outcomes of all trials at the end of the run, excluding preload and
+    /// retried attempts. The result of each is printed and the process exits
+    /// with an error if any are violated.
+    #[serde(default)]
+    assertions: Vec<Assertion>,
+    /// When set, errors during this many seconds at the start of the run are
+    /// excluded from the error accounting used by the `assertions`, so that
+    /// transient errors while connections are established do not fail the
+    /// run. The errors are still reported in the per-window output.
+    #[serde(default)]
+    startup_error_grace: Option<u64>,
+    /// Optional path to the JSON summary (see `summary_output`) of a previous
+    /// run. At the end of the run the throughput and response latency
+    /// percentiles are compared against it and the differences are printed.
+    #[serde(default)]
+    baseline: Option<String>,
+    /// The maximum regression, in percent, allowed for each metric when
+    /// compared against the `baseline`, by metric name (`throughput` or a
+    /// latency percentile such as `p99`). The process exits with an error if
+    /// any threshold is exceeded, or if a metric with a threshold is missing
+    /// or zero in the baseline.
+    #[serde(default)]
+    baseline_thresholds: BTreeMap<String, f64>,
+    /// When set, the summary reports the time to warm: the time at which the
+    /// hit rate of a reporting window first reached this fraction (0.0 - 1.0)
+    /// of the steady-state hit rate. The steady-state hit rate is the overall
+    /// hit rate of the last quarter of the reporting windows.
+    #[serde(default)]
+    warm_fraction: Option<f64>,
+    /// When set, the test stops early once the p99 response latency has
+    /// stabilized. The p99 is considered stable when its variation, the
+    /// difference between the largest and smallest value relative to their
+    /// mean, over the most recent `stability_windows` reporting windows is no
+    /// more than the `stability_tolerance`.
+    #[serde(default)]
+    stabilize_and_stop: bool,
+    /// The number of reporting windows considered by `stabilize_and_stop`.
+    /// Defaults to 5.
+    #[serde(default)]
+    stability_windows: Option<usize>,
+    /// The maximum variation (0.0 - 1.0) of the p99 for it to be considered
+    /// stable by `stabilize_and_stop`. Defaults to 0.05.
+    #[serde(default)]
+    stability_tolerance: Option<f64>,
+    /// Optional path to periodically checkpoint the raw counter values to, at
+    /// the reporting interval. The file is replaced on each checkpoint so that
+    /// the last known values are available if the process exits unexpectedly.
+    #[serde(default)]
+    checkpoint_file: Option<String>,
+    /// Optional StatsD (or Datadog agent) address (`HOST:PORT`). When set, the
+    /// counter deltas, gauges, and latency percentiles are sent over UDP at
+    /// each reporting interval. The labels are attached as Datadog tags.
+    #[serde(default)]
+    statsd_address: Option<String>,
+    /// The prefix for the StatsD metric names. Defaults to `rpcperf`.
+    #[serde(default)]
+    statsd_prefix: Option<String>,
+    /// Optionally stop the test once this many value bytes have been written.
+    /// Written bytes are counted by the Memcache, Momento, and RESP clients.
+    #[serde(default)]
+    max_bytes_written: Option<u64>,
+    /// Optionally stop the test once this many value bytes have been read.
+    /// Bytes read by gets are counted by the same clients as above.
+    #[serde(default)]
+    max_bytes_read: Option<u64>,
+    /// Arbitrary key-value labels (eg: run name, git sha, environment) which
+    /// are attached to the metrics output, the JSON summary, and the
+    /// Prometheus exposition. Labels may also be provided on the command line
+    /// with `--label key=value`.
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    /// The grouping power for latency histograms. Higher values increase the
+    /// precision (relative error is 2^-grouping_power) and memory use.
+    /// Defaults to 7.
+    #[serde(default)]
+    histogram_grouping_power: Option<u8>,
+    /// The max value power for latency histograms. Values up to
+    /// 2^max_value_power nanoseconds can be recorded. Lower values reduce
+    /// memory use. Defaults to 64. For example, a grouping power of 4 and a
+    /// max value power of 40 is suitable for low memory environments. Response
+    /// latencies beyond this range are counted in `response_latency/overflow`.
+    #[serde(default)]
+    histogram_max_value_power: Option<u8>,
+    /// When set, the summary includes latency percentiles for requests grouped
+    /// by their value size. Each entry is the exclusive upper bound (in bytes)
+    /// of a bucket, in increasing order, and a final bucket holds any larger
+    /// values. Sets are bucketed by the size of the value written and gets by
+    /// the size of the value read. For example, `[1024, 10240, 102400]`.
+    #[serde(default)]
+    size_buckets: Option<Vec<usize>>,
+    /// The secret for the value HMACs of keyspaces with `value_hmac` set. This
+    /// can be any string. When not set, a random key is generated for the run.
+    /// The key is logged at startup so that a later run can verify the values
+    /// written by this one.
+    #[serde(default, skip_serializing)]
+    value_hmac_key: Option<String>,
+}
+
+/// Asserts that the rate of an outcome, as a percentage of all the responses
+/// for the operation, is within the bounds. For example, an operation of `set`
+/// with an outcome of `ok` and a `min` of 99.9 requires that at least 99.9 %
+/// of sets succeed.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Assertion {
+    /// One of `get`, `set`, `delete`, or `all`.
+    operation: String,
+    /// One of `ok`, `hit`, `miss`, `ex`, `timeout`, or `ratelimited`.
+    outcome: String,
+    /// The minimum rate in percent.
+    #[serde(default)]
+    min: Option<f64>,
+    /// The maximum rate in percent.
+    #[serde(default)]
+    max: Option<f64>,
+}
+
+impl Assertion {
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    pub fn outcome(&self) -> &str {
+        &self.outcome
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
 }
 
 impl General {
@@ -43,10 +373,34 @@ impl General {
         Duration::from_secs(self.interval)
     }
 
+    pub fn align_reports(&self) -> bool {
+        self.align_reports
+    }
+
+    pub fn reset_interval(&self) -> Duration {
+        Duration::from_secs(self.reset_interval.unwrap_or(self.interval))
+    }
+
     pub fn duration(&self) -> Duration {
         Duration::from_secs(self.duration)
     }
 
+    pub fn hard_deadline(&self) -> Option<Duration> {
+        self.hard_deadline.map(Duration::from_secs)
+    }
+
+    pub fn clock_skew_threshold(&self) -> Option<Duration> {
+        self.clock_skew_threshold.map(Duration::from_millis)
+    }
+
+    pub fn trials(&self) -> usize {
+        std::cmp::max(1, self.trials.unwrap_or(1))
+    }
+
+    pub fn run_retries(&self) -> usize {
+        self.run_retries
+    }
+
     pub fn metrics_output(&self) -> Option<String> {
         self.metrics_output.clone()
     }
@@ -63,6 +417,229 @@ impl General {
         self.admin.clone()
     }
 
+    pub fn latency_unit(&self) -> LatencyUnit {
+        self.latency_unit
+    }
+
+    pub fn throughput_unit(&self) -> ThroughputUnit {
+        self.throughput_unit
+    }
+
+    pub fn sparkline(&self) -> Option<usize> {
+        self.sparkline.filter(|windows| *windows > 0)
+    }
+
+    pub fn latency_histogram_output(&self) -> Option<&str> {
+        self.latency_histogram_output.as_deref()
+    }
+
+    pub fn set_latency_histogram_output(&mut self, output: String) {
+        self.latency_histogram_output = Some(output);
+    }
+
+    pub fn assertions(&self) -> &[Assertion] {
+        &self.assertions
+    }
+
+    pub fn startup_error_grace(&self) -> Option<Duration> {
+        self.startup_error_grace
+            .filter(|grace| *grace > 0)
+            .map(Duration::from_secs)
+    }
+
+    pub fn summary_output(&self) -> Option<&str> {
+        self.summary_output.as_deref()
+    }
+
+    pub fn summary_config(&self) -> bool {
+        self.summary_config
+    }
+
+    pub fn baseline(&self) -> Option<&str> {
+        self.baseline.as_deref()
+    }
+
+    pub fn baseline_thresholds(&self) -> &BTreeMap<String, f64> {
+        &self.baseline_thresholds
+    }
+
+    pub fn set_summary_output(&mut self, output: String) {
+        self.summary_output = Some(output);
+    }
+
+    pub fn warm_fraction(&self) -> Option<f64> {
+        self.warm_fraction
+    }
+
+    pub fn stabilize_and_stop(&self) -> bool {
+        self.stabilize_and_stop
+    }
+
+    pub fn stability_windows(&self) -> usize {
+        self.stability_windows.unwrap_or(5)
+    }
+
+    pub fn stability_tolerance(&self) -> f64 {
+        self.stability_tolerance.unwrap_or(0.05)
+    }
+
+    pub fn checkpoint_file(&self) -> Option<&str> {
+        self.checkpoint_file.as_deref()
+    }
+
+    pub fn statsd_address(&self) -> Option<&str> {
+        self.statsd_address.as_deref()
+    }
+
+    pub fn statsd_prefix(&self) -> &str {
+        self.statsd_prefix.as_deref().unwrap_or("rpcperf")
+    }
+
+    pub fn max_bytes_written(&self) -> Option<u64> {
+        self.max_bytes_written
+    }
+
+    pub fn max_bytes_read(&self) -> Option<u64> {
+        self.max_bytes_read
+    }
+
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
+    /// Add a label, replacing any existing label with the same key.
+    pub fn add_label(&mut self, key: String, value: String) {
+        self.labels.insert(key, value);
+    }
+
+    pub fn value_hmac_key(&self) -> Option<&str> {
+        self.value_hmac_key.as_deref()
+    }
+
+    pub fn histogram_grouping_power(&self) -> u8 {
+        self.histogram_grouping_power.unwrap_or(7)
+    }
+
+    pub fn histogram_max_value_power(&self) -> u8 {
+        self.histogram_max_value_power.unwrap_or(64)
+    }
+
+    pub fn size_buckets(&self) -> Option<&[usize]> {
+        self.size_buckets.as_deref()
+    }
+
+    /// Checks that the label names are usable as Prometheus label names. This
+    /// is separate from `validate` so it can be repeated once labels from the
+    /// command line have been added.
+    pub fn validate_labels(&self) {
+        for key in self.labels.keys() {
+            let mut chars = key.chars();
+
+            let valid = chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+            if !valid {
+                eprintln!("label names must match [a-zA-Z_][a-zA-Z0-9_]*: {key}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    pub fn validate(&self) {
+        self.validate_labels();
+
+        if self.histogram_max_value_power() > 64 {
+            eprintln!("histogram_max_value_power must be <= 64");
+            std::process::exit(1);
+        }
+
+        if self.histogram_grouping_power() >= self.histogram_max_value_power() {
+            eprintln!("histogram_grouping_power must be less than histogram_max_value_power");
+            std::process::exit(1);
+        }
+
+        if self
+            .hard_deadline
+            .is_some_and(|deadline| deadline <= self.duration)
+        {
+            eprintln!("hard_deadline must be longer than the test duration");
+            std::process::exit(1);
+        }
+
+        if self
+            .warm_fraction
+            .is_some_and(|fraction| !(0.0..=1.0).contains(&fraction))
+        {
+            eprintln!("warm_fraction must be in the range 0.0 - 1.0");
+            std::process::exit(1);
+        }
+
+        if self.stability_windows() < 2 {
+            eprintln!("stability_windows must be >= 2");
+            std::process::exit(1);
+        }
+
+        if !(0.0..=1.0).contains(&self.stability_tolerance()) {
+            eprintln!("stability_tolerance must be in the range 0.0 - 1.0");
+            std::process::exit(1);
+        }
+
+        if let Some(buckets) = &self.size_buckets {
+            if buckets.is_empty() || buckets.windows(2).any(|pair| pair[0] >= pair[1]) {
+                eprintln!("size_buckets must be a non-empty list of increasing sizes");
+                std::process::exit(1);
+            }
+        }
+
+        for assertion in &self.assertions {
+            if !["get", "set", "delete", "all"].contains(&assertion.operation()) {
+                eprintln!("unknown assertion operation: {}", assertion.operation());
+                std::process::exit(1);
+            }
+
+            if !["ok", "hit", "miss", "ex", "timeout", "ratelimited"].contains(&assertion.outcome())
+            {
+                eprintln!("unknown assertion outcome: {}", assertion.outcome());
+                std::process::exit(1);
+            }
+
+            if assertion.min().is_none() && assertion.max().is_none() {
+                eprintln!("assertions require a `min` or `max`");
+                std::process::exit(1);
+            }
+        }
+
+        for (metric, threshold) in &self.baseline_thresholds {
+            if metric != "throughput"
+                && !crate::metrics::PERCENTILES
+                    .iter()
+                    .any(|(label, _)| label == metric)
+            {
+                eprintln!("unknown baseline threshold metric: {metric}");
+                std::process::exit(1);
+            }
+
+            if *threshold < 0.0 {
+                eprintln!("baseline threshold for {metric} must be >= 0");
+                std::process::exit(1);
+            }
+        }
+
+        if !self.baseline_thresholds.is_empty() && self.baseline.is_none() {
+            eprintln!("baseline_thresholds requires a `baseline`");
+            std::process::exit(1);
+        }
+
+        if let Some(output) = self.latency_histogram_output() {
+            if !output.contains("{op}") {
+                eprintln!("latency_histogram_output must contain `{{op}}`");
+                std::process::exit(1);
+            }
+        }
+    }
+
     pub fn initial_seed(&self) -> Seed512 {
         if let Some(initial_seed) = &self.initial_seed {
             let mut hasher = Sha512::new();
@@ -34,7 +34,8 @@ pub fn interval() -> String {
     "1s".into()
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Metrics {
     /// File for output metrics
     output: String,
@@ -118,6 +119,10 @@ impl Metrics {
         &self.output
     }
 
+    pub fn set_output(&mut self, output: String) {
+        self.output = output;
+    }
+
     pub fn format(&self) -> Format {
         self.format
     }
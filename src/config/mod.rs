@@ -1,6 +1,7 @@
 use core::num::NonZeroU64;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::io::Read;
+use std::path::Path;
 use std::time::Duration;
 
 mod client;
@@ -13,17 +14,17 @@ mod target;
 mod tls;
 mod workload;
 
-pub use client::Client;
+pub use client::{Client, CompatibilityCheck, EndpointRank, ProcessingDelay};
 pub use debug::Debug;
-pub use general::General;
+pub use general::{General, LatencyUnit, ThroughputUnit};
 pub use metrics::{Format as MetricsFormat, Metrics};
 pub use protocol::Protocol;
 pub use pubsub::Pubsub;
 pub use target::Target;
 pub use tls::Tls;
 pub use workload::{
-    Command, Distribution, Keyspace, RampCompletionAction, RampType, Topics, ValueKind, Verb,
-    Workload,
+    BatchOrder, Command, Distribution, ExpectedValue, Keyspace, RampCompletionAction, RampType,
+    Topics, ValueKind, Verb, VlenDistribution, Workload,
 };
 
 pub const PAGESIZE: usize = 4096;
@@ -33,7 +34,8 @@ pub fn default_buffer_size() -> usize {
     DEFAULT_BUFFER_SIZE
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     general: General,
     client: Option<Client>,
@@ -65,11 +67,23 @@ impl Config {
         let mut config: Config = toml::from_str(&content)
             .map_err(|e| {
                 eprintln!("Failed to parse TOML config: {filename}\n{e}");
+                if let Some(suggestion) = suggestion(e.message()) {
+                    eprintln!("did you mean `{suggestion}`?");
+                }
                 std::process::exit(1);
             })
             .unwrap();
 
-        config.workload.ratelimit().validate();
+        config.general.validate();
+        config.workload.load_commands_files();
+        config.workload.ratelimit().validate(&config.general);
+        config
+            .target
+            .validate(config.general.trials(), config.tls.as_ref());
+        config.debug.validate(&config.general);
+        if let Some(client) = config.client.as_mut() {
+            client.validate();
+        }
         if config.metrics().is_none() {
             config.metrics = Metrics::from_general(&config.general);
         }
@@ -83,6 +97,10 @@ impl Config {
         &self.general
     }
 
+    pub fn general_mut(&mut self) -> &mut General {
+        &mut self.general
+    }
+
     pub fn client(&self) -> Option<&Client> {
         self.client.as_ref()
     }
@@ -110,4 +128,93 @@ impl Config {
     pub fn metrics(&self) -> Option<&Metrics> {
         self.metrics.as_ref()
     }
+
+    /// Returns a copy of the config for one trial of a multi-trial run. Each
+    /// trial uses its own cache name, metrics output, latency histogram files,
+    /// and summary output.
+    pub fn for_trial(&self, trial: usize) -> Config {
+        let mut config = self.clone();
+
+        if let Some(cache_name) = self.target.cache_name() {
+            config
+                .target
+                .set_cache_name(format!("{cache_name}-{trial}"));
+        }
+
+        if let Some(metrics) = config.metrics.as_mut() {
+            let output = trial_path(metrics.output(), trial);
+            metrics.set_output(output);
+        }
+
+        if let Some(output) = self.general.latency_histogram_output() {
+            config
+                .general
+                .set_latency_histogram_output(trial_path(output, trial));
+        }
+
+        if let Some(output) = self.general.summary_output() {
+            config.general.set_summary_output(trial_path(output, trial));
+        }
+
+        config
+    }
+}
+
+/// Inserts the trial number into the file name of a path, before any
+/// extension, so that the directory is left unchanged.
+fn trial_path(path: &str, trial: usize) -> String {
+    let path = Path::new(path);
+
+    let Some(stem) = path.file_stem() else {
+        return format!("{}-{trial}", path.display());
+    };
+
+    let name = match path.extension() {
+        Some(extension) => format!(
+            "{}-{trial}.{}",
+            stem.to_string_lossy(),
+            extension.to_string_lossy()
+        ),
+        None => format!("{}-{trial}", stem.to_string_lossy()),
+    };
+
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Returns the closest expected field for an unknown field error, if one is
+/// close enough to be a likely typo. The error message names the unknown field
+/// followed by the expected fields, each quoted in backticks.
+fn suggestion(message: &str) -> Option<&str> {
+    if !message.starts_with("unknown field") {
+        return None;
+    }
+
+    let mut quoted = message.split('`').skip(1).step_by(2);
+
+    let unknown = quoted.next()?;
+
+    quoted
+        .map(|expected| (edit_distance(unknown, expected), expected))
+        .filter(|(distance, _)| *distance <= std::cmp::max(2, unknown.len() / 3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, expected)| expected)
+}
+
+/// Returns the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
 }
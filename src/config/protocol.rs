@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Clone, Copy, Deserialize, Debug)]
+#[derive(Clone, Copy, Deserialize, Serialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum Protocol {
     Blabber,
@@ -8,6 +8,9 @@ pub enum Protocol {
     Http2,
     Memcache,
     Momento,
+    /// Completes each request immediately, measuring only the overhead of the
+    /// client.
+    Null,
     Ping,
     Resp,
     Kafka,
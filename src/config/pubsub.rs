@@ -1,6 +1,7 @@
 use super::*;
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Pubsub {
     // connection timeout in ms
     connect_timeout: u64,
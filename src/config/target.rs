@@ -1,11 +1,54 @@
 use super::*;
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Target {
     /// A list of target endpoints (`IP:PORT`).
     endpoints: Vec<String>,
     /// A cache name
     cache_name: Option<String>,
+    /// An optional secondary cache for shadow reads. When set, each get is
+    /// also sent to this cache and the values are compared. Only the primary
+    /// is included in the latency and response metrics. Currently only used by
+    /// the Momento client.
+    #[serde(default)]
+    shadow_cache_name: Option<String>,
+    /// The environment variable with the API key for the shadow cache, if it
+    /// differs from the primary. Defaults to the first `api_key_env`.
+    #[serde(default)]
+    shadow_api_key_env: Option<String>,
+    /// When set, the endpoints are re-resolved at this interval (in seconds).
+    /// If the resolved addresses change, the client tasks are asked to
+    /// reconnect so that new connections follow the updated DNS records.
+    #[serde(default)]
+    dns_refresh_interval: Option<u64>,
+    /// When set, the cache (and the shadow cache, if any) is created before
+    /// the test begins if it does not already exist. The test waits for each
+    /// cache to serve requests before sending any load. Currently only used by
+    /// the Momento client.
+    #[serde(default)]
+    create_caches: bool,
+    /// The time (in seconds) to wait for a created cache to become ready
+    /// before exiting with an error. Defaults to 60 seconds.
+    #[serde(default)]
+    cache_ready_timeout: Option<u64>,
+    /// The maximum number of caches which are created (and waited on) at the
+    /// same time when `create_caches` is set. Defaults to one, which creates
+    /// the caches one after another.
+    #[serde(default)]
+    max_concurrent_creates: Option<usize>,
+    /// When set, connections are made to the Unix domain socket at this path
+    /// instead of over TCP. The endpoints are still used to determine the
+    /// number of connections and for protocol fields such as the HTTP host.
+    /// Cannot be combined with TLS.
+    #[serde(default)]
+    uds_path: Option<String>,
+    /// An optional limit on the length of the cache names used by the run,
+    /// including the `-<trial>` suffix of a multi-trial run. Names which
+    /// exceed the backend limit are then rejected at startup instead of
+    /// failing once the test is running.
+    #[serde(default)]
+    max_cache_name_len: Option<usize>,
 }
 
 impl Target {
@@ -16,4 +59,67 @@ impl Target {
     pub fn cache_name(&self) -> Option<&str> {
         self.cache_name.as_deref()
     }
+
+    pub fn set_cache_name(&mut self, cache_name: String) {
+        self.cache_name = Some(cache_name);
+    }
+
+    pub fn shadow_cache_name(&self) -> Option<&str> {
+        self.shadow_cache_name.as_deref()
+    }
+
+    pub fn shadow_api_key_env(&self) -> Option<&str> {
+        self.shadow_api_key_env.as_deref()
+    }
+
+    pub fn dns_refresh_interval(&self) -> Option<Duration> {
+        self.dns_refresh_interval
+            .filter(|interval| *interval > 0)
+            .map(Duration::from_secs)
+    }
+
+    pub fn create_caches(&self) -> bool {
+        self.create_caches
+    }
+
+    pub fn cache_ready_timeout(&self) -> Duration {
+        Duration::from_secs(self.cache_ready_timeout.unwrap_or(60))
+    }
+
+    pub fn max_concurrent_creates(&self) -> usize {
+        std::cmp::max(1, self.max_concurrent_creates.unwrap_or(1))
+    }
+
+    /// Checks that the cache names for every trial fit within the configured
+    /// limit, exiting with the longest name if one does not.
+    pub fn validate(&self, trials: usize, tls: Option<&Tls>) {
+        if self.uds_path.is_some() && tls.is_some() {
+            eprintln!("target `uds_path` cannot be combined with TLS");
+            std::process::exit(2);
+        }
+
+        let Some(limit) = self.max_cache_name_len else {
+            return;
+        };
+
+        // the last trial has the longest suffix
+        let cache_name = self.cache_name.as_ref().map(|name| match trials {
+            0 | 1 => name.clone(),
+            trials => format!("{name}-{}", trials - 1),
+        });
+
+        for name in cache_name.iter().chain(self.shadow_cache_name.iter()) {
+            if name.len() > limit {
+                eprintln!(
+                    "cache name `{name}` is {} characters, which exceeds the `max_cache_name_len` of {limit}",
+                    name.len()
+                );
+                std::process::exit(2);
+            }
+        }
+    }
+
+    pub fn uds_path(&self) -> Option<&str> {
+        self.uds_path.as_deref()
+    }
 }
@@ -4,9 +4,11 @@ fn default_true() -> bool {
     true
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Tls {
     private_key: Option<String>,
+    #[serde(skip_serializing)]
     private_key_password: Option<String>,
     certificate: Option<String>,
     certificate_chain: Option<String>,
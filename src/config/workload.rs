@@ -1,10 +1,17 @@
 use super::*;
+use serde::de::value::StrDeserializer;
+use serde::de::IntoDeserializer;
 
 fn one() -> usize {
     1
 }
 
-#[derive(Clone, Deserialize)]
+fn default_vlen_sigma() -> f64 {
+    1.0
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Workload {
     #[serde(default)]
     keyspace: Vec<Keyspace>,
@@ -14,11 +21,24 @@ pub struct Workload {
     ratelimit: Ratelimit,
 }
 
-#[derive(Clone, Deserialize, Copy, Debug, Ord, Eq, PartialOrd, PartialEq, Hash)]
+#[derive(Clone, Deserialize, Serialize, Copy, Debug, Ord, Eq, PartialOrd, PartialEq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum ValueKind {
     I64,
     Bytes,
+    /// A JSON object with string and integer fields, sized to approximately
+    /// the value length.
+    Json,
+    /// A protobuf-shaped message of varint and length-delimited fields, sized
+    /// to approximately the value length.
+    Protobuf,
+}
+
+impl ValueKind {
+    /// Returns `true` if the length of generated values is set by the `vlen`.
+    pub fn sized(&self) -> bool {
+        !matches!(self, Self::I64)
+    }
 }
 
 impl Workload {
@@ -37,9 +57,18 @@ impl Workload {
     pub fn ratelimit(&self) -> &Ratelimit {
         &self.ratelimit
     }
+
+    /// Loads any commands files for the keyspaces, appending the commands to
+    /// those specified directly in the config.
+    pub fn load_commands_files(&mut self) {
+        for keyspace in self.keyspace.iter_mut() {
+            keyspace.load_commands_file();
+        }
+    }
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Topics {
     topics: usize,
     #[serde(default = "one")]
@@ -118,11 +147,21 @@ impl Topics {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Distribution {
     Uniform,
     Zipf,
+    /// Visits the keys in order, wrapping around at the end of the keyspace.
+    Sequential,
+    /// Visits every key exactly once, in a shuffled order, before repeating.
+    /// This guarantees even coverage of the keyspace, such as when every key
+    /// must be written once to populate the keyspace.
+    Permutation,
+    /// Replays the sequence of key indices from the keyspace
+    /// `key_replay_file`, looping once it is exhausted. Only supported for
+    /// keyspaces.
+    Replay,
 }
 
 impl Default for Distribution {
@@ -131,7 +170,22 @@ impl Default for Distribution {
     }
 }
 
-#[derive(Clone, Deserialize)]
+/// The distribution of the lengths of generated values.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VlenDistribution {
+    /// Every value is exactly `vlen` bytes.
+    #[default]
+    Fixed,
+    /// Value lengths are exponentially distributed with a mean of `vlen`.
+    Exponential,
+    /// Value lengths are lognormally distributed with a mean of `vlen` and
+    /// the shape set by `vlen_sigma`.
+    Lognormal,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Keyspace {
     #[serde(default)]
     nkeys: usize,
@@ -139,15 +193,71 @@ pub struct Keyspace {
     klen: usize,
     #[serde(default)]
     key_distribution: Distribution,
+    /// A file with the sequence of key indices (one per line, in the range
+    /// `0..nkeys`) used by the `replay` key distribution.
+    #[serde(default)]
+    key_replay_file: Option<String>,
+    /// A file with keys to write before the test begins, one per line. Each
+    /// line is either `<key>`, in which case a value is generated, or
+    /// `<key> <value>`. The keys are used as-is, without the `key_prefix`.
+    #[serde(default)]
+    preload_keys_file: Option<String>,
+    /// A file to write the keys of the keyspace to, one per line and in the
+    /// order of their indices. When a populate run and a benchmark run are
+    /// separate processes, the populate run saves its keys and the benchmark
+    /// run loads them with `keys_load_file`. The file may also be used as a
+    /// `preload_keys_file`.
+    #[serde(default)]
+    keys_save_file: Option<String>,
+    /// A file with the keys of the keyspace, as written by `keys_save_file`.
+    /// The keys replace the generated keys, and `nkeys` is the number of keys
+    /// in the file. The keys are used as-is, without the `key_prefix`.
+    #[serde(default)]
+    keys_load_file: Option<String>,
     #[serde(default = "one")]
     weight: usize,
+    /// When set, the keyspace is excluded from the weighted mix and instead
+    /// drives a background load at this rate (requests/s), running
+    /// concurrently with the foreground workload. Foreground latency is then
+    /// reported separately for when the background load is on and off.
+    #[serde(default)]
+    background_rate: Option<u64>,
+    /// An optional interval (in seconds) after which the background load is
+    /// toggled on or off, so that foreground latency is measured both with
+    /// and without it. By default the background load runs continuously.
+    #[serde(default)]
+    background_interval: Option<u64>,
     #[serde(default)]
     inner_keys_nkeys: Option<usize>,
     #[serde(default)]
     inner_keys_klen: Option<usize>,
+    #[serde(default)]
     commands: Vec<Command>,
+    /// An optional path to a file with additional command templates. Each
+    /// non-empty line which is not a comment (`#`) has the form:
+    ///
+    /// `<weight> <verb> [key=<prefix>] [vlen=<bytes>] [cardinality=<n>] [isolate]`
+    ///
+    /// The optional key prefix is prepended to the keys for that command, the
+    /// optional vlen overrides the keyspace `vlen`, and `isolate` reports the
+    /// latency of the command separately.
+    #[serde(default)]
+    commands_file: Option<String>,
+    /// The length of generated values in bytes. A `vlen` of zero produces
+    /// empty values, which some backends treat specially.
     #[serde(default)]
     vlen: Option<usize>,
+    /// The distribution of value lengths. For distributions other than
+    /// `fixed` the `vlen` is the mean length.
+    #[serde(default)]
+    vlen_distribution: VlenDistribution,
+    /// The standard deviation of the logarithm of the value length for the
+    /// `lognormal` distribution. Larger values produce a longer tail.
+    #[serde(default = "default_vlen_sigma")]
+    vlen_sigma: f64,
+    /// An optional upper bound on the sampled value lengths.
+    #[serde(default)]
+    vlen_max: Option<usize>,
     #[serde(default)]
     vkind: Option<ValueKind>,
     #[serde(default)]
@@ -155,6 +265,218 @@ pub struct Keyspace {
     #[serde(default)]
     // no ttl is treated as no-expires or max ttl for the protocol
     ttl: Option<String>,
+    /// When set, a get which misses will be followed by a set of a freshly
+    /// generated value for the same key. This emulates a cache-aside
+    /// read-through pattern. The follow-up sets are tracked separately and do
+    /// not count towards the set metrics.
+    #[serde(default)]
+    populate_on_miss: bool,
+    /// Optionally, generate keys so that they are evenly balanced across this
+    /// many shards. The shard for a key is determined by its FNV-1a hash
+    /// modulo the number of shards. Requests per shard are reported at the
+    /// end of the run.
+    #[serde(default)]
+    shards: Option<usize>,
+    /// Optionally, cap generated values at this many bytes. Values which
+    /// would be larger are truncated and counted as clamped. Useful to stay
+    /// within backend limits on item size.
+    #[serde(default)]
+    max_value_bytes: Option<usize>,
+    /// An optional prefix prepended to each generated key. The `klen` does
+    /// not include the prefix.
+    #[serde(default)]
+    key_prefix: Option<String>,
+    /// When set, reads target a separate set of this many keys which are never
+    /// written by this keyspace. This allows testing the miss path directly.
+    #[serde(default)]
+    read_nkeys: Option<usize>,
+    /// The prefix for keys in the separate read key set. Must differ from the
+    /// `key_prefix`. Defaults to `read:`.
+    #[serde(default)]
+    read_key_prefix: Option<String>,
+    /// A file with the keys for reads, one per line, such as a dump of the
+    /// keys already in the store. Reads select from these keys, following the
+    /// key distribution, so that they hit existing data without populating
+    /// it first. Writes still use the keys of the keyspace. The keys are used
+    /// as-is, without the `key_prefix`.
+    #[serde(default)]
+    read_keys_file: Option<String>,
+    /// When set, the hot keys shift at this interval (in seconds). Keys are
+    /// still selected by the key distribution, but the selected index is
+    /// offset by `hotspot_shift_keys` for each interval that has elapsed. With
+    /// a skewed distribution, this continuously moves the hot set to keys which
+    /// were previously cold.
+    #[serde(default)]
+    hotspot_shift_interval: Option<u64>,
+    /// The number of keys the hotspot moves by at each shift. Defaults to one
+    /// tenth of the keyspace.
+    #[serde(default)]
+    hotspot_shift_keys: Option<usize>,
+    /// An optional fixed header written at the start of each generated value.
+    /// Values prefixed with `0x` are decoded as hex, otherwise the bytes of
+    /// the string are used. The header is included in the `vlen`.
+    #[serde(default)]
+    value_header: Option<String>,
+    /// When set, each workload thread guarantees that a get for a key is only
+    /// generated after that thread has generated a write for the key. A get
+    /// for a key which has not been written is replaced by a set of that key.
+    /// This orders generation rather than execution, so the write is only
+    /// guaranteed to complete first with one workload thread and a client
+    /// `poolsize` and `concurrency` of one.
+    #[serde(default)]
+    write_before_read: bool,
+    /// When set, the length of each value returned by a get is checked
+    /// against the fixed value length of the keyspace. A mismatch indicates a
+    /// truncated or padded value. Only applies to `bytes` values which are not
+    /// taken from a value corpus. Currently checked by the Momento and RESP
+    /// clients.
+    #[serde(default)]
+    verify_value_length: bool,
+    /// When set, each set embeds a per-key sequence number of this many bytes
+    /// (1 - 8) in the value, directly after any `value_header`. Gets check the
+    /// sequence number and count a stale read if it is older than one which
+    /// was previously read for the key, which indicates replica lag. Narrow
+    /// sequence numbers wrap sooner, which is also counted as a stale read.
+    /// Sequence numbers are assigned as requests are generated, so this
+    /// requires a single workload thread and a client `poolsize` and
+    /// `concurrency` of 1, and cannot be used with `key_classes`. Currently
+    /// checked by the Momento and RESP clients.
+    #[serde(default)]
+    value_sequence_bytes: Option<usize>,
+    /// When set, the last 32 bytes of each written value hold an HMAC-SHA256
+    /// of the key and the rest of the value, keyed by the run key (see the
+    /// general `value_hmac_key`). Gets verify the HMAC and count a failure if
+    /// it does not match, which detects corrupted values and values written
+    /// by another run. Cannot be used with `vlen_distribution` or `update`
+    /// commands. Currently checked by the Momento and RESP clients.
+    #[serde(default)]
+    value_hmac: bool,
+    /// When set, values are structured records with a field of each of these
+    /// sizes (in bytes). Each field is written as a 4 byte big-endian length
+    /// followed by the field, so the `vlen` must be the sum of the field sizes
+    /// plus 4 bytes for each field. Gets check that every field is present
+    /// with its size and count an incomplete record if not. Currently checked
+    /// by the Momento and RESP clients.
+    #[serde(default)]
+    record_fields: Vec<usize>,
+    /// When set, values longer than this many bytes are split across multiple
+    /// keys, modeling a chunked object store. A set writes each chunk to its
+    /// own key, a get reads all of the chunks and checks that their total
+    /// length matches the value length, and a delete removes all of the
+    /// chunks. Requires a fixed `vlen` and is
+    /// currently used by the Momento and RESP clients.
+    #[serde(default)]
+    chunk_size: Option<usize>,
+    /// The probability (0.0 - 1.0) that a delete targets one of the keys
+    /// recently written by the same workload thread rather than a random key.
+    /// This makes deletes remove keys which are likely to exist, modeling
+    /// cache invalidation churn.
+    #[serde(default)]
+    delete_recent_bias: Option<f64>,
+    /// When set, the keyspace starts with this many active keys and grows
+    /// linearly to `nkeys` over the `growth_duration`. This models a dataset
+    /// which expands during the test.
+    #[serde(default)]
+    growth_start_nkeys: Option<usize>,
+    /// The time (in seconds) over which the keyspace grows to `nkeys`.
+    /// Defaults to the duration of the test.
+    #[serde(default)]
+    growth_duration: Option<u64>,
+    /// When set, the number of active keys is adjusted during a probe phase so
+    /// that gets hit at this ratio (0.0 - 1.0), instead of tuning `nkeys` by
+    /// hand. Each second of the probe the active keys are scaled by the ratio
+    /// of the observed and target hit ratios, between the
+    /// `target_hit_ratio_min_nkeys` and `nkeys`. The active keys are then held
+    /// for the rest of the test. The observed hit ratio is that of all gets,
+    /// so no other keyspace may issue gets.
+    #[serde(default)]
+    target_hit_ratio: Option<f64>,
+    /// The smallest number of active keys when sizing for a target hit ratio.
+    /// Defaults to 1.
+    #[serde(default)]
+    target_hit_ratio_min_nkeys: Option<usize>,
+    /// The length (in seconds) of the probe phase when sizing for a target hit
+    /// ratio. Defaults to 60 seconds.
+    #[serde(default)]
+    target_hit_ratio_duration: Option<u64>,
+    /// An optional directory of files to use as values. Each generated value
+    /// is the contents of a randomly selected file, so the entropy and
+    /// compressibility of the values match the files. Takes precedence over
+    /// the `vlen`.
+    #[serde(default)]
+    value_corpus: Option<String>,
+    /// The maximum number of bytes loaded from the value corpus. Files beyond
+    /// this limit are skipped and a file which would exceed the limit is
+    /// truncated. Defaults to 1GB.
+    #[serde(default)]
+    value_corpus_max_bytes: Option<usize>,
+    /// When set, the keys are the names of the files in the value corpus
+    /// (with the `key_prefix`) instead of being generated.
+    #[serde(default)]
+    value_corpus_keys: bool,
+    /// Optional key classes, each with its own key prefix and value length.
+    /// Each request first selects a class by weight and then uses the
+    /// keyspace keys with the class prefix prepended, so the value size
+    /// depends on the class of the key.
+    #[serde(default)]
+    key_classes: Vec<KeyClass>,
+    /// Optional compressibility classes. Each generated value first selects a
+    /// class by weight and is then generated to match the compression ratio
+    /// of the class, so a single run can mix incompressible and compressible
+    /// values. Takes precedence over the keyspace `compression_ratio`.
+    #[serde(default)]
+    compression_classes: Vec<CompressionClass>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyClass {
+    /// The prefix which identifies keys in this class.
+    prefix: String,
+    #[serde(default = "one")]
+    weight: usize,
+    /// The length of the values for keys in this class. Must be >= 1.
+    vlen: usize,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompressionClass {
+    /// The name used to report the class.
+    name: String,
+    #[serde(default = "one")]
+    weight: usize,
+    /// The approximate (gzip) compression ratio of values in this class. A
+    /// ratio of 1.0 or less produces incompressible values.
+    compression_ratio: f64,
+}
+
+impl CompressionClass {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    pub fn compression_ratio(&self) -> f64 {
+        self.compression_ratio
+    }
+}
+
+impl KeyClass {
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    pub fn vlen(&self) -> usize {
+        self.vlen
+    }
 }
 
 impl Keyspace {
@@ -170,6 +492,30 @@ impl Keyspace {
         self.key_distribution
     }
 
+    pub fn key_replay_file(&self) -> Option<&str> {
+        self.key_replay_file.as_deref()
+    }
+
+    pub fn preload_keys_file(&self) -> Option<&str> {
+        self.preload_keys_file.as_deref()
+    }
+
+    pub fn keys_save_file(&self) -> Option<&str> {
+        self.keys_save_file.as_deref()
+    }
+
+    pub fn keys_load_file(&self) -> Option<&str> {
+        self.keys_load_file.as_deref()
+    }
+
+    pub fn background_rate(&self) -> Option<u64> {
+        self.background_rate
+    }
+
+    pub fn background_interval(&self) -> Option<Duration> {
+        self.background_interval.map(Duration::from_secs)
+    }
+
     pub fn weight(&self) -> usize {
         self.weight
     }
@@ -186,10 +532,52 @@ impl Keyspace {
         &self.commands
     }
 
+    fn load_commands_file(&mut self) {
+        let Some(path) = self.commands_file.as_ref() else {
+            return;
+        };
+
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(error) => {
+                eprintln!("error reading commands file: {path}\n{error}");
+                std::process::exit(1);
+            }
+        };
+
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match Command::from_template(line) {
+                Ok(command) => self.commands.push(command),
+                Err(error) => {
+                    eprintln!("error in commands file: {path}:{}\n{error}", idx + 1);
+                    std::process::exit(2);
+                }
+            }
+        }
+    }
+
     pub fn vlen(&self) -> Option<usize> {
         self.vlen
     }
 
+    pub fn vlen_distribution(&self) -> VlenDistribution {
+        self.vlen_distribution
+    }
+
+    pub fn vlen_sigma(&self) -> f64 {
+        self.vlen_sigma
+    }
+
+    pub fn vlen_max(&self) -> Option<usize> {
+        self.vlen_max
+    }
+
     pub fn vkind(&self) -> ValueKind {
         self.vkind.unwrap_or(ValueKind::Bytes)
     }
@@ -203,9 +591,143 @@ impl Keyspace {
             .as_ref()
             .map(|ttl| ttl.parse::<humantime::Duration>().unwrap().into())
     }
+
+    pub fn populate_on_miss(&self) -> bool {
+        self.populate_on_miss
+    }
+
+    pub fn key_classes(&self) -> &[KeyClass] {
+        &self.key_classes
+    }
+
+    pub fn compression_classes(&self) -> &[CompressionClass] {
+        &self.compression_classes
+    }
+
+    pub fn value_corpus(&self) -> Option<&str> {
+        self.value_corpus.as_deref()
+    }
+
+    pub fn value_corpus_max_bytes(&self) -> usize {
+        self.value_corpus_max_bytes.unwrap_or(1024 * 1024 * 1024)
+    }
+
+    pub fn value_corpus_keys(&self) -> bool {
+        self.value_corpus_keys
+    }
+
+    pub fn growth_start_nkeys(&self) -> Option<usize> {
+        self.growth_start_nkeys
+    }
+
+    pub fn growth_duration(&self) -> Option<Duration> {
+        self.growth_duration.map(Duration::from_secs)
+    }
+
+    pub fn target_hit_ratio(&self) -> Option<f64> {
+        self.target_hit_ratio
+    }
+
+    pub fn target_hit_ratio_min_nkeys(&self) -> usize {
+        self.target_hit_ratio_min_nkeys.unwrap_or(1)
+    }
+
+    pub fn target_hit_ratio_duration(&self) -> Duration {
+        Duration::from_secs(self.target_hit_ratio_duration.unwrap_or(60))
+    }
+
+    pub fn write_before_read(&self) -> bool {
+        self.write_before_read
+    }
+
+    pub fn verify_value_length(&self) -> bool {
+        self.verify_value_length
+    }
+
+    pub fn value_sequence_bytes(&self) -> Option<usize> {
+        self.value_sequence_bytes
+    }
+
+    pub fn value_hmac(&self) -> bool {
+        self.value_hmac
+    }
+
+    pub fn record_fields(&self) -> &[usize] {
+        &self.record_fields
+    }
+
+    pub fn chunk_size(&self) -> Option<usize> {
+        self.chunk_size
+    }
+
+    pub fn delete_recent_bias(&self) -> Option<f64> {
+        self.delete_recent_bias
+    }
+
+    pub fn shards(&self) -> Option<usize> {
+        self.shards
+    }
+
+    pub fn max_value_bytes(&self) -> Option<usize> {
+        self.max_value_bytes
+    }
+
+    pub fn hotspot_shift_interval(&self) -> Option<Duration> {
+        self.hotspot_shift_interval
+            .filter(|interval| *interval > 0)
+            .map(Duration::from_secs)
+    }
+
+    pub fn hotspot_shift_keys(&self) -> usize {
+        self.hotspot_shift_keys.unwrap_or(self.nkeys / 10).max(1)
+    }
+
+    /// Returns the decoded value header, if one is configured.
+    pub fn value_header(&self) -> Option<Vec<u8>> {
+        let header = self.value_header.as_ref()?;
+
+        let Some(hex) = header.strip_prefix("0x") else {
+            return Some(header.as_bytes().to_vec());
+        };
+
+        if hex.len() % 2 != 0 {
+            eprintln!("value_header must have an even number of hex digits");
+            std::process::exit(2);
+        }
+
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..(i + 2)], 16))
+            .collect::<std::result::Result<Vec<u8>, _>>();
+
+        match bytes {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                eprintln!("value_header is not valid hex: {e}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    pub fn key_prefix(&self) -> &str {
+        self.key_prefix.as_deref().unwrap_or("")
+    }
+
+    pub fn read_nkeys(&self) -> Option<usize> {
+        self.read_nkeys
+    }
+
+    pub fn read_keys_file(&self) -> Option<&str> {
+        self.read_keys_file.as_deref()
+    }
+
+    pub fn read_key_prefix(&self) -> &str {
+        self.read_key_prefix.as_deref().unwrap_or("read:")
+    }
 }
 
-#[derive(Clone, Copy, Deserialize)]
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Command {
     verb: Verb,
     #[serde(default = "one")]
@@ -220,6 +742,83 @@ pub struct Command {
     end: Option<i32>,
     #[serde(default)]
     by_score: bool,
+    /// For `ttl_check`, the time in milliseconds to wait after the write
+    /// before reading the key back. Defaults to the keyspace ttl.
+    #[serde(default)]
+    wait: Option<u64>,
+    /// For `ttl_check`, an additional delay in milliseconds added to the wait
+    /// to tolerate clock skew between the client and the backend.
+    #[serde(default)]
+    grace: Option<u64>,
+    /// For `update`, the number of bytes of the value which are overwritten
+    /// before it is written back. Defaults to 8 bytes.
+    #[serde(default)]
+    mutation_bytes: Option<usize>,
+    /// For `delete_if`, how the value which must be read for the key to be
+    /// deleted is derived from the keyspace. Defaults to `length`.
+    #[serde(default)]
+    expected_value: ExpectedValue,
+    /// For `touch`, the new ttl for the key, eg: `5m`. Defaults to the
+    /// keyspace ttl.
+    #[serde(default)]
+    ttl: Option<String>,
+    /// An optional prefix for the keys used by this command. The command will
+    /// use its own copy of the keyspace keys with the prefix prepended.
+    #[serde(default)]
+    key_prefix: Option<String>,
+    /// Optionally override the keyspace `vlen` for this command.
+    #[serde(default)]
+    vlen: Option<usize>,
+    /// Optionally override the keyspace `vlen_distribution` for this command.
+    #[serde(default)]
+    vlen_distribution: Option<VlenDistribution>,
+    /// Optionally override the keyspace `vlen_sigma` for this command.
+    #[serde(default)]
+    vlen_sigma: Option<f64>,
+    /// Optionally override the keyspace `vlen_max` for this command.
+    #[serde(default)]
+    vlen_max: Option<usize>,
+    /// For commands with a cardinality, the order in which the fields or
+    /// members are sent. Defaults to the order in which they are sampled.
+    #[serde(default)]
+    batch_order: BatchOrder,
+    /// When set, the latency of the requests for this command is reported
+    /// separately from the requests for all other commands. Combined with a
+    /// low weight and a large `cardinality` or `vlen`, this models rare but
+    /// expensive requests alongside frequent cheap ones without their latency
+    /// being hidden in the overall percentiles.
+    #[serde(default)]
+    isolate: bool,
+}
+
+/// The order of the fields or members within a multi-field command. Sampled
+/// keys follow the key distribution, which for a sequential distribution is
+/// ascending and for set based commands is not reproducible between runs. A
+/// sorted order is reproducible and a shuffled order avoids any bias from the
+/// key distribution.
+#[derive(Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchOrder {
+    #[default]
+    Sampled,
+    Sorted,
+    Shuffled,
+}
+
+/// The condition on the current value for a `delete_if` to delete the key.
+/// Generated values are not reproducible, so the condition is derived from the
+/// properties which every value of the keyspace shares.
+#[derive(Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectedValue {
+    /// Any value matches, so that every key which is found is deleted.
+    Any,
+    /// The value must have the length of the values of the keyspace, which
+    /// requires a fixed `vlen` and `bytes` values.
+    #[default]
+    Length,
+    /// The value must begin with the `value_header` of the keyspace.
+    Header,
 }
 
 impl Command {
@@ -250,12 +849,126 @@ impl Command {
     pub fn by_score(&self) -> bool {
         self.by_score
     }
+
+    pub fn wait(&self) -> Option<Duration> {
+        self.wait.map(Duration::from_millis)
+    }
+
+    pub fn grace(&self) -> Duration {
+        Duration::from_millis(self.grace.unwrap_or(0))
+    }
+
+    pub fn mutation_bytes(&self) -> Option<usize> {
+        self.mutation_bytes
+    }
+
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+            .as_ref()
+            .map(|ttl| ttl.parse::<humantime::Duration>().unwrap().into())
+    }
+
+    pub fn batch_order(&self) -> BatchOrder {
+        self.batch_order
+    }
+
+    pub fn isolate(&self) -> bool {
+        self.isolate
+    }
+
+    pub fn expected_value(&self) -> ExpectedValue {
+        self.expected_value
+    }
+
+    pub fn key_prefix(&self) -> Option<&str> {
+        self.key_prefix.as_deref()
+    }
+
+    pub fn vlen(&self) -> Option<usize> {
+        self.vlen
+    }
+
+    pub fn vlen_distribution(&self) -> Option<VlenDistribution> {
+        self.vlen_distribution
+    }
+
+    pub fn vlen_sigma(&self) -> Option<f64> {
+        self.vlen_sigma
+    }
+
+    pub fn vlen_max(&self) -> Option<usize> {
+        self.vlen_max
+    }
+
+    /// Returns `true` if the command overrides any of the keyspace value
+    /// length settings.
+    pub fn overrides_vlen(&self) -> bool {
+        self.vlen.is_some()
+            || self.vlen_distribution.is_some()
+            || self.vlen_sigma.is_some()
+            || self.vlen_max.is_some()
+    }
+
+    /// Parses a command template of the form:
+    /// `<weight> <verb> [key=<prefix>] [vlen=<bytes>] [cardinality=<n>] [isolate]`
+    ///
+    /// Any option which is not given takes the same default as when the
+    /// command is written in the config.
+    fn from_template(line: &str) -> std::result::Result<Self, String> {
+        let mut tokens = line.split_whitespace();
+
+        let weight = tokens
+            .next()
+            .ok_or("missing weight")?
+            .parse::<usize>()
+            .map_err(|e| format!("invalid weight: {e}"))?;
+
+        let verb = tokens.next().ok_or("missing verb")?;
+        let verb: StrDeserializer<serde::de::value::Error> = verb.into_deserializer();
+        let verb = Verb::deserialize(verb).map_err(|e| format!("invalid verb: {e}"))?;
+
+        let mut command = Command {
+            verb,
+            weight,
+            cardinality: 1,
+            ..Default::default()
+        };
+
+        for token in tokens {
+            match token.split_once('=') {
+                Some(("key", prefix)) => {
+                    command.key_prefix = Some(prefix.to_string());
+                }
+                Some(("vlen", vlen)) => {
+                    command.vlen = Some(
+                        vlen.parse::<usize>()
+                            .map_err(|e| format!("invalid vlen: {e}"))?,
+                    );
+                }
+                Some(("cardinality", cardinality)) => {
+                    command.cardinality = cardinality
+                        .parse::<usize>()
+                        .map_err(|e| format!("invalid cardinality: {e}"))?;
+                }
+                None if token == "isolate" => {
+                    command.isolate = true;
+                }
+                _ => {
+                    return Err(format!("unknown option: {token}"));
+                }
+            }
+        }
+
+        Ok(command)
+    }
 }
 
 // #[derive(Deserialize, Clone, Copy, Eq, PartialEq)]
 // #[serde(rename_all = "snake_case")]
 // #[serde(deny_unknown_fields)]
-#[derive(Clone, Deserialize, Copy, Debug, Ord, Eq, PartialOrd, PartialEq, Hash)]
+#[derive(
+    Clone, Default, Deserialize, Serialize, Copy, Debug, Ord, Eq, PartialOrd, PartialEq, Hash,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum Verb {
     /// Sends a `PING` to the server and expects a `PONG`
@@ -275,6 +988,7 @@ pub enum Verb {
     /// * Memcache: `get`
     /// * Momento: `get` (NOTE: cardinality > 1 is not supported)
     /// * RESP: `GET` or `MGET`
+    #[default]
     Get,
     /// Set the value for a key.
     /// * Memcache: `set`
@@ -287,6 +1001,33 @@ pub enum Verb {
     /// * RESP: `DEL`
     #[serde(alias = "del")]
     Delete,
+    /// Set the value for a key with the keyspace ttl, then read it back after
+    /// the ttl has elapsed and expect a miss. The keyspace should not be
+    /// shared with other writes.
+    /// * Momento: `set` followed by a delayed `get`
+    TtlCheck,
+    /// Read the value for a key, overwrite a small region of it in place, and
+    /// then write it back. Misses are not written.
+    /// * Momento: `get` followed by a `set`
+    /// * RESP: `GET` followed by a `SET`
+    Update,
+    /// Set the value for a key and then immediately delete it, as a single
+    /// operation. This stresses the insert and delete paths back-to-back to
+    /// create tombstone and compaction pressure.
+    /// * Momento: `set` followed by a `delete`
+    /// * RESP: `SET` followed by a `DEL`
+    ChurnWrite,
+    /// Delete a key only if its current value matches the expected value. This
+    /// is emulated by reading the value and then deleting the key if it
+    /// matches, as neither client has a native conditional delete.
+    /// * Momento: `get` followed by a `delete`
+    /// * RESP: `GET` followed by a `DEL`
+    DeleteIf,
+    /// Extend the ttl of a key without reading or rewriting its value.
+    /// * Memcache: unsupported
+    /// * Momento: `update_ttl`
+    /// * RESP: `PEXPIRE`
+    Touch,
     /// Set the value for a key only if it already exists.
     /// * Memcache: `replace`
     /// * Momento: unsupported
@@ -466,6 +1207,61 @@ impl Verb {
         matches!(self, Self::SortedSetRange)
     }
 
+    /// Returns `true` if the verb only reads data.
+    pub fn is_read(&self) -> bool {
+        matches!(
+            self,
+            Self::Get
+                | Self::HashExists
+                | Self::HashGet
+                | Self::HashGetAll
+                | Self::ListFetch
+                | Self::ListLength
+                | Self::SetMembers
+                | Self::SortedSetRange
+                | Self::SortedSetRank
+                | Self::SortedSetScore
+        )
+    }
+
+    pub fn supports_wait(&self) -> bool {
+        matches!(self, Self::TtlCheck)
+    }
+
+    /// Returns `true` if the verb sends an ordered list of fields or members,
+    /// so that their order can be set for the command.
+    pub fn supports_batch_order(&self) -> bool {
+        self.supports_cardinality() && *self != Self::HashSet
+    }
+
+    pub fn supports_expected_value(&self) -> bool {
+        matches!(self, Self::DeleteIf)
+    }
+
+    pub fn supports_ttl(&self) -> bool {
+        matches!(self, Self::Touch)
+    }
+
+    pub fn supports_mutation_bytes(&self) -> bool {
+        matches!(self, Self::Update)
+    }
+
+    /// Returns `true` if the verb generates values, so that the value length
+    /// can be set for the command. Gets generate values when populating keys
+    /// after a miss.
+    pub fn supports_vlen(&self) -> bool {
+        matches!(
+            self,
+            Self::Add
+                | Self::ChurnWrite
+                | Self::Get
+                | Self::HashSet
+                | Self::Replace
+                | Self::Set
+                | Self::TtlCheck
+        )
+    }
+
     pub fn needs_inner_key(&self) -> bool {
         matches!(
             self,
@@ -495,7 +1291,7 @@ impl Verb {
 // and end value by the step function in a sequence. A shuffled ramp means
 // that the same stepwise ratelimits are explored in random order; however,
 // only ratelimits at the specified steps are applied.
-#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum RampType {
     #[default]
@@ -507,7 +1303,7 @@ pub enum RampType {
 // state, it can loop around and repeat the entire workload in the same
 // sequence, or can repeat the same workload in reverse order (for example,
 // to perform a corresponding ramp-down to the initial ramp-up).
-#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum RampCompletionAction {
     #[default]
@@ -516,7 +1312,27 @@ pub enum RampCompletionAction {
     Mirror,
 }
 
-#[derive(Clone, Deserialize)]
+// A step of a stepped rate schedule, holding the rate (requests/s) for the
+// duration (seconds) of the step.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateStep {
+    rate: u64,
+    duration: u64,
+}
+
+impl RateStep {
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.duration)
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Ratelimit {
     #[serde(default)]
     start: u64,
@@ -535,11 +1351,49 @@ pub struct Ratelimit {
 
     #[serde(default)]
     on_ramp_completion: RampCompletionAction,
+
+    /// When set, the rate is divided evenly across the workload threads and
+    /// each thread paces itself with its own ratelimiter. This produces a
+    /// smoother aggregate rate than a single shared ratelimiter.
+    #[serde(default)]
+    per_thread: bool,
+
+    /// A stepped rate schedule, as an alternative to a ramp. Each step holds
+    /// its rate for its duration and the metrics for each step are reported
+    /// at the step boundary, producing a throughput-latency curve.
+    #[serde(default)]
+    steps: Vec<RateStep>,
+
+    /// An optional on/off duty cycle. Requests are issued at the ratelimit
+    /// for `burst_on` seconds and then none are issued for `burst_off`
+    /// seconds, repeating. Both must be set together.
+    #[serde(default)]
+    burst_on: Option<u64>,
+
+    #[serde(default)]
+    burst_off: Option<u64>,
+
+    /// An optional ramp-down, in seconds, at the end of the run. Over this
+    /// window the ratelimit decreases linearly from its current value to zero
+    /// so that the load declines gradually instead of stopping abruptly.
+    #[serde(default)]
+    ramp_down: Option<u64>,
+
+    /// When set, the responses received during the ramp-down are excluded
+    /// from the cumulative summary and from the trial results used for
+    /// aggregation, baselines and assertions, which then cover only the
+    /// steady state.
+    #[serde(default)]
+    exclude_ramp_down: bool,
 }
 
 impl Ratelimit {
     pub fn start(&self) -> Option<NonZeroU64> {
-        NonZeroU64::new(self.start)
+        // a stepped schedule starts at the rate of its first step
+        match self.steps.first() {
+            Some(step) => NonZeroU64::new(step.rate),
+            None => NonZeroU64::new(self.start),
+        }
     }
 
     pub fn end(&self) -> Option<u64> {
@@ -562,15 +1416,97 @@ impl Ratelimit {
         self.on_ramp_completion
     }
 
+    pub fn per_thread(&self) -> bool {
+        self.per_thread
+    }
+
+    pub fn steps(&self) -> &[RateStep] {
+        &self.steps
+    }
+
+    /// Returns the on and off durations of the burst duty cycle, if one is
+    /// configured.
+    pub fn burst(&self) -> Option<(Duration, Duration)> {
+        match (self.burst_on, self.burst_off) {
+            (Some(on), Some(off)) => Some((Duration::from_secs(on), Duration::from_secs(off))),
+            _ => None,
+        }
+    }
+
+    pub fn ramp_down(&self) -> Option<Duration> {
+        self.ramp_down.map(Duration::from_secs)
+    }
+
+    pub fn exclude_ramp_down(&self) -> bool {
+        self.exclude_ramp_down
+    }
+
     pub fn is_dynamic(&self) -> bool {
-        self.end.is_some() || self.step.is_some() || self.interval.is_some()
+        self.end.is_some()
+            || self.step.is_some()
+            || self.interval.is_some()
+            || !self.steps.is_empty()
     }
 
-    pub fn validate(&self) {
+    pub fn validate(&self, general: &General) {
+        if let Some(ramp_down) = self.ramp_down {
+            if self.start().is_none() {
+                eprintln!("ratelimit `ramp_down` requires a ratelimit");
+                std::process::exit(2);
+            }
+
+            if ramp_down == 0 || self.ramp_down() >= Some(general.duration()) {
+                eprintln!(
+                    "ratelimit `ramp_down` must be greater than zero and shorter than the `duration`"
+                );
+                std::process::exit(2);
+            }
+        } else if self.exclude_ramp_down {
+            eprintln!("ratelimit `exclude_ramp_down` requires a `ramp_down`");
+            std::process::exit(2);
+        }
+
+        match (self.burst_on, self.burst_off) {
+            (None, None) => {}
+            (Some(on), Some(off)) if on > 0 && off > 0 => {}
+            (Some(_), Some(_)) => {
+                eprintln!("ratelimit `burst_on` and `burst_off` must be greater than zero");
+                std::process::exit(2);
+            }
+            _ => {
+                eprintln!("ratelimit `burst_on` and `burst_off` must be specified together");
+                std::process::exit(2);
+            }
+        }
+
         if !self.is_dynamic() {
             return;
         }
 
+        if !self.steps.is_empty() {
+            if self.start != 0
+                || self.end.is_some()
+                || self.step.is_some()
+                || self.interval.is_some()
+            {
+                eprintln!(
+                    "a stepped ratelimit cannot be combined with start, end, step, or interval"
+                );
+                std::process::exit(2);
+            }
+
+            if self
+                .steps
+                .iter()
+                .any(|step| step.rate == 0 || step.duration == 0)
+            {
+                eprintln!("each ratelimit step must have a non-zero rate and duration");
+                std::process::exit(2);
+            }
+
+            return;
+        }
+
         if !(self.end.is_some() && self.step.is_some() && self.interval.is_some()) {
             eprintln!("end, step, and interval need to be specified for dynamic ratelimit");
             std::process::exit(2);
@@ -1,16 +1,16 @@
-use crate::clients::launch_clients;
+use crate::clients::{check_compatibility, launch_clients};
 use crate::pubsub::launch_pubsub;
 use crate::workload::{launch_workload, Generator, Ratelimit};
 use async_channel::{bounded, Sender};
 use backtrace::Backtrace;
 use clap::{Arg, Command};
-use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use once_cell::sync::Lazy;
 use ringlog::*;
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::runtime::Builder;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::runtime::{Builder, Runtime};
 use tokio::sync::RwLock;
 use tokio::time::sleep;
 
@@ -20,6 +20,8 @@ mod config;
 mod metrics;
 mod net;
 mod output;
+#[cfg(feature = "profiling")]
+mod profiler;
 mod pubsub;
 mod workload;
 
@@ -27,11 +29,26 @@ use config::*;
 use metrics::*;
 
 static RUNNING: AtomicBool = AtomicBool::new(true);
+// set once the ramp-down at the end of a run has begun, after which the
+// ratelimit is only lowered by the ramp-down
+static RAMP_DOWN: AtomicBool = AtomicBool::new(false);
+// the ratelimit when the ramp-down began, which remains the target rate of the
+// run once the ramp-down has lowered the current ratelimit
+static RAMP_DOWN_FROM: AtomicU64 = AtomicU64::new(0);
+// set once all trials have completed, stops the control plane tasks which
+// persist across trials
+static COMPLETE: AtomicBool = AtomicBool::new(false);
 static WAIT: AtomicUsize = AtomicUsize::new(0);
 
 static METRICS_SNAPSHOT: Lazy<Arc<RwLock<MetricsSnapshot>>> =
     Lazy::new(|| Arc::new(RwLock::new(Default::default())));
 
+/// Runs as each runtime thread starts.
+fn thread_start() {
+    #[cfg(feature = "profiling")]
+    profiler::register_thread();
+}
+
 fn main() {
     // custom panic hook to terminate whole process after unwinding
     std::panic::set_hook(Box::new(|s| {
@@ -53,16 +70,71 @@ fn main() {
                 .action(clap::ArgAction::Set)
                 .index(1),
         )
+        .arg(
+            Arg::new("LABEL")
+                .long("label")
+                .help("Attach a key=value label to the metrics output, may be repeated")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("EMIT_WORKLOAD")
+                .long("emit-workload")
+                .value_name("FILE")
+                .help("Write the generated workload to a file and exit without sending requests")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("EMIT_COUNT")
+                .long("emit-count")
+                .value_name("N")
+                .help("The number of requests written by --emit-workload")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1000")
+                .action(clap::ArgAction::Set),
+        )
         .get_matches();
 
     // load config from file
-    let config = if let Some(file) = matches.get_one::<String>("CONFIG") {
+    let mut config = if let Some(file) = matches.get_one::<String>("CONFIG") {
         Config::new(file)
     } else {
         eprintln!("configuration file not provided");
         std::process::exit(1);
     };
 
+    // labels from the command line take precedence over the config file
+    if let Some(labels) = matches.get_many::<String>("LABEL") {
+        for label in labels {
+            if let Some((key, value)) = label.split_once('=') {
+                config
+                    .general_mut()
+                    .add_label(key.to_string(), value.to_string());
+            } else {
+                eprintln!("label must be in the form key=value: {label}");
+                std::process::exit(1);
+            }
+        }
+
+        config.general().validate_labels();
+    }
+
+    // configure the histograms before any are used
+    set_histogram_config(
+        config.general().histogram_grouping_power(),
+        config.general().histogram_max_value_power(),
+    );
+
+    if let Some(buckets) = config.general().size_buckets() {
+        SizeBuckets::init(buckets);
+    }
+
+    // write out the generated workload for inspection instead of running it
+    if let Some(path) = matches.get_one::<String>("EMIT_WORKLOAD") {
+        let count = *matches.get_one::<usize>("EMIT_COUNT").unwrap();
+        Generator::new(&config).emit(&config, path, count);
+        return;
+    }
+
     // configure debug log
     let debug_output: Box<dyn Output> = if let Some(file) = config.debug().log_file() {
         let backup = config
@@ -103,12 +175,13 @@ fn main() {
     let control_runtime = Builder::new_multi_thread()
         .enable_all()
         .worker_threads(4)
+        .on_thread_start(thread_start)
         .build()
         .expect("failed to initialize tokio runtime");
 
     // spawn logging thread
     control_runtime.spawn(async move {
-        while RUNNING.load(Ordering::Relaxed) {
+        while !COMPLETE.load(Ordering::Relaxed) {
             sleep(Duration::from_millis(1)).await;
             let _ = log.flush();
         }
@@ -119,7 +192,7 @@ fn main() {
     {
         let interval = config.general().interval();
         control_runtime.spawn(async move {
-            while RUNNING.load(Ordering::Relaxed) {
+            while !COMPLETE.load(Ordering::Relaxed) {
                 // acquire a lock and update the snapshots
                 {
                     let mut snapshots = METRICS_SNAPSHOT.write().await;
@@ -132,50 +205,275 @@ fn main() {
         });
     }
 
+    // watch for jumps of the wall clock
+    if let Some(threshold) = config.general().clock_skew_threshold() {
+        control_runtime.spawn(output::clock_skew(threshold));
+    }
+
+    // spawn thread to checkpoint the counters
+    if let Some(path) = config.general().checkpoint_file() {
+        control_runtime.spawn(output::checkpoint(
+            path.to_string(),
+            config.general().interval(),
+        ));
+    }
+
+    output!("Protocol: {:?}", config.general().protocol());
+
+    if config.debug().inject_errors() {
+        output!(
+            "WARNING: error injection is enabled, {:.2} % of requests will time out and {:.2} % will fail with an exception. Results are NOT valid benchmarks.",
+            100.0 * config.debug().inject_timeout_rate(),
+            100.0 * config.debug().inject_exception_rate(),
+        );
+        warn!("error injection is enabled, results are not valid benchmarks");
+    }
+
+    if let Some(client_config) = config.client() {
+        output!(
+            "Client Timeouts: Connect: {} ms Request: {} ms",
+            client_config.connect_timeout().as_millis(),
+            client_config.request_timeout().as_millis(),
+        );
+
+        if client_config.request_timeout_clamped() {
+            output!("Client Timeouts: request_timeout was clamped by max_request_timeout");
+        }
+
+        for warning in client_config.timeout_warnings() {
+            output!("WARNING: {warning}");
+            warn!("{warning}");
+        }
+    }
+
+    check_compatibility(&config, &control_runtime);
+
+    #[cfg(feature = "profiling")]
+    let profiler = config
+        .debug()
+        .profile_file()
+        .map(|path| profiler::start(path, config.debug().profile_frequency()));
+
+    #[cfg(not(feature = "profiling"))]
+    if config.debug().profile_file().is_some() {
+        eprintln!("profiling requires building with the `profiling` feature");
+        std::process::exit(1);
+    }
+
+    // the watchdog uses its own thread so that it still fires if the runtimes
+    // are stuck
+    if let Some(deadline) = config.general().hard_deadline() {
+        let config = config.clone();
+        let start = SystemTime::now();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(deadline);
+
+            output!("-----");
+            output!(
+                "Stopping: exceeded the hard deadline of {} s",
+                deadline.as_secs()
+            );
+            output::deadline_summary(&config, start);
+
+            std::process::exit(1);
+        });
+    }
+
+    let trials = config.general().trials();
+    let mut summaries = Vec::with_capacity(trials);
+
+    for trial in 0..trials {
+        let config = if trials > 1 {
+            output!("-----");
+            output!("Trial: {} of {}", trial + 1, trials);
+            config.for_trial(trial)
+        } else {
+            config.clone()
+        };
+
+        let retries = config.general().run_retries();
+        let mut attempt = 0;
+
+        let summary = loop {
+            let summary = run(&config, &control_runtime, trial, attempt);
+
+            // a run without any successful responses is assumed to be an
+            // infrastructure failure
+            let succeeded = summary.counter_delta(RESPONSE_OK_COUNTER) > 0;
+
+            if retries > 0 {
+                output!("-----");
+                output!(
+                    "Attempt: {} of {}: {}",
+                    attempt + 1,
+                    retries + 1,
+                    if succeeded {
+                        "succeeded"
+                    } else {
+                        "failed, no successful responses"
+                    }
+                );
+            }
+
+            if succeeded || attempt == retries {
+                break summary;
+            }
+
+            attempt += 1;
+        };
+
+        summaries.push(summary);
+    }
+
+    #[cfg(feature = "profiling")]
+    if let Some(profiler) = profiler {
+        profiler.stop();
+    }
+
+    if trials > 1 {
+        output::trials(&summaries, config.general().latency_unit());
+    }
+
+    let passed = output::assertions(&config, &summaries);
+    let passed = output::baseline(&config, &summaries) && passed;
+
+    COMPLETE.store(true, Ordering::Relaxed);
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
+
+/// Runs a single attempt at a trial of the test and returns a snapshot
+/// covering the attempt.
+fn run(
+    config: &Config,
+    control_runtime: &Runtime,
+    trial: usize,
+    attempt: usize,
+) -> MetricsSnapshot {
+    RUNNING.store(true, Ordering::Relaxed);
+    RAMP_DOWN.store(false, Ordering::Relaxed);
+    RAMP_DOWN_FROM.store(0, Ordering::Relaxed);
+
     // TODO: figure out what a reasonable size is here
     let (client_sender, client_receiver) = bounded(128);
     let (pubsub_sender, pubsub_receiver) = bounded(128);
 
-    output!("Protocol: {:?}", config.general().protocol());
-
     debug!("Initializing workload generator");
-    let workload_generator = Generator::new(&config);
+    let workload_generator = Generator::new(config);
 
     let workload_ratelimit = workload_generator.ratelimiter();
 
     let workload_components = workload_generator.components().to_owned();
 
-    // spawn the admin thread
-    control_runtime.spawn(admin::http(config.clone(), workload_ratelimit.clone()));
+    // spawn the admin thread, which persists across trials. Only the
+    // ratelimit of the first trial may be adjusted through the admin endpoint.
+    if trial == 0 && attempt == 0 {
+        control_runtime.spawn(admin::http(config.clone(), workload_ratelimit.clone()));
+    }
+
+    // start client(s)
+    let client_runtime = launch_clients(config, client_receiver);
+
+    // write any preloaded keys before measurement begins
+    workload_generator.preload(config, &client_sender);
+
+    // the log stops the summary at the start of an excluded ramp-down
+    let summary = Arc::new(Mutex::new(output::TrialSummary::new()));
 
     // launch metrics file output
     control_runtime.spawn(output::metrics(config.clone()));
 
     // begin cli output
-    control_runtime.spawn(output::log(config.clone()));
+    control_runtime.spawn(output::log(
+        config.clone(),
+        workload_ratelimit.clone(),
+        summary.clone(),
+    ));
+
+    // the startup error grace period only applies at the start of the run
+    if trial == 0 && attempt == 0 {
+        control_runtime.spawn(output::startup_error_grace(config.clone()));
+    }
+
+    // send metrics to statsd, if configured
+    control_runtime.spawn(output::statsd(config.clone()));
 
     // start the workload generator(s)
     let workload_runtime =
-        launch_workload(workload_generator, &config, client_sender, pubsub_sender);
-
-    // start client(s)
-    let client_runtime = launch_clients(&config, client_receiver);
+        launch_workload(workload_generator, config, client_sender, pubsub_sender);
 
     // start publisher(s) and subscriber(s)
-    let mut pubsub_runtimes = launch_pubsub(&config, pubsub_receiver, &workload_components);
+    let mut pubsub_runtimes = launch_pubsub(config, pubsub_receiver, &workload_components);
 
     // start ratelimit controller thread if a dynamic ratelimit is configured
     {
-        if let Some(mut ratelimit_controller) = Ratelimit::new(&config) {
+        if let Some(mut ratelimit_controller) = Ratelimit::new(config) {
+            let unit = config.general().latency_unit();
+
             control_runtime.spawn(async move {
+                let mut step = 0;
+
                 while RUNNING.load(Ordering::Relaxed) {
-                    let _ = admin::handlers::update_ratelimit(
-                        ratelimit_controller.next_ratelimit(),
-                        workload_ratelimit.clone(),
-                    )
-                    .await;
+                    let (rate, interval) = ratelimit_controller.next_ratelimit();
+
+                    if RAMP_DOWN.load(Ordering::Relaxed) {
+                        break;
+                    }
+
+                    let _ =
+                        admin::handlers::update_ratelimit(rate, workload_ratelimit.clone()).await;
+
+                    // the metrics for a step cover the responses received
+                    // between its boundaries
+                    let mut snapshot = MetricsSnapshot::default();
+                    snapshot.update();
+
                     // delay until next step function
-                    sleep(ratelimit_controller.interval()).await;
+                    sleep(interval).await;
+
+                    step += 1;
+
+                    // a step which was cut short by the end of the run is
+                    // not reported
+                    if ratelimit_controller.stepped() && RUNNING.load(Ordering::Relaxed) {
+                        snapshot.refresh();
+                        output::rate_step(step, rate, &snapshot, unit);
+                    }
+                }
+            });
+        }
+    }
+
+    // stop the test once any configured byte budget is exhausted
+    {
+        let max_bytes_written = config.general().max_bytes_written();
+        let max_bytes_read = config.general().max_bytes_read();
+
+        // the counters are cumulative across trials
+        let bytes_written = BYTES_WRITTEN.value();
+        let bytes_read = BYTES_READ.value();
+
+        if max_bytes_written.is_some() || max_bytes_read.is_some() {
+            control_runtime.spawn(async move {
+                while RUNNING.load(Ordering::Relaxed) {
+                    if max_bytes_written
+                        .is_some_and(|max| BYTES_WRITTEN.value() - bytes_written >= max)
+                    {
+                        output!("Stopping: reached the limit for bytes written");
+                        RUNNING.store(false, Ordering::Relaxed);
+                    } else if max_bytes_read
+                        .is_some_and(|max| BYTES_READ.value() - bytes_read >= max)
+                    {
+                        output!("Stopping: reached the limit for bytes read");
+                        RUNNING.store(false, Ordering::Relaxed);
+                    }
+
+                    sleep(Duration::from_millis(10)).await;
                 }
             });
         }
@@ -195,11 +493,14 @@ fn main() {
 
     workload_runtime.shutdown_timeout(std::time::Duration::from_millis(100));
 
+    output::shards(&workload_components);
+
     // delay before exiting
 
     while WAIT.load(Ordering::Relaxed) > 0 {
         std::thread::sleep(std::time::Duration::from_millis(100));
     }
 
-    std::thread::sleep(std::time::Duration::from_millis(100));
+    let mut summary = summary.lock().unwrap();
+    summary.finish()
 }
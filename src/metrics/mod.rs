@@ -8,6 +8,8 @@ use ahash::HashMapExt;
 use metriken::Lazy;
 use paste::paste;
 use std::concat;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::SystemTime;
 
 pub static PERCENTILES: &[(&str, f64)] = &[
@@ -54,10 +56,35 @@ impl MetricsSnapshot {
         self.histograms.update();
     }
 
+    /// Updates the current values without moving the window start, so that
+    /// rates and percentiles cover everything since the last rebase.
+    pub fn refresh(&mut self) {
+        self.current = SystemTime::now();
+
+        self.counters.refresh();
+        self.histograms.refresh();
+    }
+
+    /// Moves the window start to the most recent refresh.
+    pub fn rebase(&mut self) {
+        self.previous = self.current;
+
+        self.counters.rebase();
+        self.histograms.rebase();
+    }
+
     pub fn percentiles(&self, name: &str) -> Vec<(String, f64, u64)> {
         self.histograms.percentiles(name)
     }
 
+    pub fn mean(&self, name: &str) -> Option<f64> {
+        self.histograms.mean(name)
+    }
+
+    pub fn stddev(&self, name: &str) -> Option<f64> {
+        self.histograms.stddev(name)
+    }
+
     pub fn counter_rate(&self, name: &str) -> f64 {
         self.counter_delta(name) as f64
             / (self.current.duration_since(self.previous).unwrap()).as_secs_f64()
@@ -74,9 +101,32 @@ impl MetricsSnapshot {
 
         current.unwrap() - previous
     }
+
+    /// Returns the delta for a counter which is referenced directly, such as
+    /// the counters defined in the protocol crates, which do not export their
+    /// names.
+    pub fn delta(&self, counter: &metriken::Counter) -> u64 {
+        for metric in metriken::metrics().iter() {
+            let any = if let Some(any) = metric.as_any() {
+                any
+            } else {
+                continue;
+            };
+
+            if any
+                .downcast_ref::<metriken::Counter>()
+                .is_some_and(|c| std::ptr::eq(c, counter))
+            {
+                return self.counter_delta(metric.name());
+            }
+        }
+
+        0
+    }
 }
 
 pub struct HistogramsSnapshot {
+    pub current: HashMap<String, histogram::Histogram>,
     pub previous: HashMap<String, histogram::Histogram>,
     pub deltas: HashMap<String, histogram::Histogram>,
 }
@@ -114,6 +164,7 @@ impl HistogramsSnapshot {
         let deltas = current.clone();
 
         Self {
+            current: current.clone(),
             previous: current,
             deltas,
         }
@@ -140,6 +191,7 @@ impl HistogramsSnapshot {
                                 .insert(name.clone(), histogram.wrapping_sub(previous).unwrap());
                         }
 
+                        self.current.insert(name.clone(), histogram.clone());
                         self.previous.insert(name, histogram);
                     }
                 }
@@ -148,6 +200,39 @@ impl HistogramsSnapshot {
         }
     }
 
+    pub fn refresh(&mut self) {
+        for metric in &metriken::metrics() {
+            match metric.value() {
+                Some(Value::Other(other)) => {
+                    let histogram = if let Some(histogram) = other.downcast_ref::<AtomicHistogram>()
+                    {
+                        histogram.load()
+                    } else if let Some(histogram) = other.downcast_ref::<RwLockHistogram>() {
+                        histogram.load()
+                    } else {
+                        None
+                    };
+
+                    if let Some(histogram) = histogram {
+                        let name = metric.name().to_string();
+
+                        if let Some(previous) = self.previous.get(&name) {
+                            self.deltas
+                                .insert(name.clone(), histogram.wrapping_sub(previous).unwrap());
+                        }
+
+                        self.current.insert(name, histogram);
+                    }
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    pub fn rebase(&mut self) {
+        self.previous = self.current.clone();
+    }
+
     pub fn percentiles(&self, metric: &str) -> Vec<(String, f64, u64)> {
         let mut result = Vec::new();
 
@@ -167,6 +252,55 @@ impl HistogramsSnapshot {
 
         result
     }
+
+    /// Returns the approximate mean of the histogram using the midpoint of
+    /// each bucket, or `None` if there are no samples.
+    pub fn mean(&self, metric: &str) -> Option<f64> {
+        let snapshot = self.deltas.get(metric)?;
+
+        let mut count = 0_u64;
+        let mut total = 0.0;
+
+        for bucket in snapshot {
+            if bucket.count() == 0 {
+                continue;
+            }
+
+            let midpoint = bucket.start() as f64 + (bucket.end() - bucket.start()) as f64 / 2.0;
+
+            count += bucket.count();
+            total += midpoint * bucket.count() as f64;
+        }
+
+        if count == 0 {
+            None
+        } else {
+            Some(total / count as f64)
+        }
+    }
+
+    /// Returns the approximate standard deviation of the histogram using the
+    /// midpoint of each bucket, or `None` if there are no samples.
+    pub fn stddev(&self, metric: &str) -> Option<f64> {
+        let mean = self.mean(metric)?;
+        let snapshot = self.deltas.get(metric)?;
+
+        let mut count = 0_u64;
+        let mut total = 0.0;
+
+        for bucket in snapshot {
+            if bucket.count() == 0 {
+                continue;
+            }
+
+            let midpoint = bucket.start() as f64 + (bucket.end() - bucket.start()) as f64 / 2.0;
+
+            count += bucket.count();
+            total += (midpoint - mean).powi(2) * bucket.count() as f64;
+        }
+
+        Some((total / count as f64).sqrt())
+    }
 }
 
 #[derive(Clone)]
@@ -184,7 +318,6 @@ impl Default for CountersSnapshot {
 impl CountersSnapshot {
     pub fn new() -> Self {
         let mut current = HashMap::new();
-        let previous = HashMap::new();
 
         for metric in metriken::metrics().iter() {
             let any = if let Some(any) = metric.as_any() {
@@ -195,10 +328,19 @@ impl CountersSnapshot {
 
             let metric = metric.name().to_string();
 
-            if let Some(_counter) = any.downcast_ref::<metriken::Counter>() {
-                current.insert(metric.clone(), 0);
+            if let Some(counter) = any.downcast_ref::<metriken::Counter>() {
+                current.insert(metric.clone(), counter.value());
             }
         }
+
+        for account in ACCOUNTS.lock().unwrap().iter() {
+            current.extend(account.counters());
+        }
+
+        // start from the current values so that a new snapshot only covers
+        // what happens after it is created
+        let previous = current.clone();
+
         Self { current, previous }
     }
 
@@ -219,6 +361,218 @@ impl CountersSnapshot {
                 }
             }
         }
+
+        for account in ACCOUNTS.lock().unwrap().iter() {
+            for (name, value) in account.counters() {
+                if let Some(old_value) = self.current.insert(name.clone(), value) {
+                    self.previous.insert(name, old_value);
+                }
+            }
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        for metric in metriken::metrics().iter() {
+            let any = if let Some(any) = metric.as_any() {
+                any
+            } else {
+                continue;
+            };
+
+            if let Some(counter) = any.downcast_ref::<metriken::Counter>() {
+                self.current
+                    .insert(metric.name().to_string(), counter.value());
+            }
+        }
+
+        for account in ACCOUNTS.lock().unwrap().iter() {
+            self.current.extend(account.counters());
+        }
+    }
+
+    pub fn rebase(&mut self) {
+        self.previous = self.current.clone();
+    }
+}
+
+// The histogram configuration, this must be set before any histograms are
+// first used. Defaults to a grouping power of 7 and a max value power of 64.
+static HISTOGRAM_GROUPING_POWER: AtomicU8 = AtomicU8::new(7);
+static HISTOGRAM_MAX_VALUE_POWER: AtomicU8 = AtomicU8::new(64);
+
+/// Sets the configuration for all histograms defined by this crate.
+pub fn set_histogram_config(grouping_power: u8, max_value_power: u8) {
+    HISTOGRAM_GROUPING_POWER.store(grouping_power, Ordering::Relaxed);
+    HISTOGRAM_MAX_VALUE_POWER.store(max_value_power, Ordering::Relaxed);
+}
+
+pub fn histogram_grouping_power() -> u8 {
+    HISTOGRAM_GROUPING_POWER.load(Ordering::Relaxed)
+}
+
+pub fn histogram_max_value_power() -> u8 {
+    HISTOGRAM_MAX_VALUE_POWER.load(Ordering::Relaxed)
+}
+
+/// Registry of per-account response counters. The set of accounts is only
+/// known once the config is loaded, so these live outside of the static
+/// metrics registry.
+pub static ACCOUNTS: Lazy<Mutex<Vec<Arc<AccountMetrics>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub struct AccountMetrics {
+    name: String,
+    ok: AtomicU64,
+    ex: AtomicU64,
+    timeout: AtomicU64,
+    ratelimited: AtomicU64,
+}
+
+impl AccountMetrics {
+    /// Returns the metrics for the named account, registering it if this is
+    /// the first time it has been seen.
+    pub fn register(name: &str) -> Arc<Self> {
+        let mut accounts = ACCOUNTS.lock().unwrap();
+
+        if let Some(account) = accounts.iter().find(|a| a.name == name) {
+            return account.clone();
+        }
+
+        let account = Arc::new(Self {
+            name: name.to_string(),
+            ok: AtomicU64::new(0),
+            ex: AtomicU64::new(0),
+            timeout: AtomicU64::new(0),
+            ratelimited: AtomicU64::new(0),
+        });
+
+        accounts.push(account.clone());
+
+        account
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ok(&self) {
+        self.ok.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn ex(&self) {
+        self.ex.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn timeout(&self) {
+        self.timeout.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn ratelimited(&self) {
+        self.ratelimited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the name used for one of the account's counters in a
+    /// `CountersSnapshot`, where `kind` is one of `ok`, `ex`, `timeout`, or
+    /// `ratelimited`.
+    pub fn counter_name(&self, kind: &str) -> String {
+        format!("account/{}/{kind}", self.name)
+    }
+
+    fn counters(&self) -> [(String, u64); 4] {
+        [
+            (self.counter_name("ok"), self.ok.load(Ordering::Relaxed)),
+            (self.counter_name("ex"), self.ex.load(Ordering::Relaxed)),
+            (
+                self.counter_name("timeout"),
+                self.timeout.load(Ordering::Relaxed),
+            ),
+            (
+                self.counter_name("ratelimited"),
+                self.ratelimited.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+/// Latency histograms for requests bucketed by their value size. The bucket
+/// boundaries are only known once the config is loaded, so these live outside
+/// of the static metrics registry.
+pub static SIZE_BUCKETS: OnceLock<SizeBuckets> = OnceLock::new();
+
+pub struct SizeBuckets {
+    bounds: Vec<usize>,
+    histograms: Vec<AtomicHistogram>,
+    previous: Mutex<Vec<Option<histogram::Histogram>>>,
+}
+
+impl SizeBuckets {
+    /// Enables recording latency by value size. Each bound is the exclusive
+    /// upper limit (in bytes) of a bucket, and a final bucket holds any larger
+    /// values.
+    pub fn init(bounds: &[usize]) {
+        let histograms = (0..=bounds.len())
+            .map(|_| AtomicHistogram::new(histogram_grouping_power(), histogram_max_value_power()))
+            .collect();
+
+        let _ = SIZE_BUCKETS.set(Self {
+            bounds: bounds.to_vec(),
+            histograms,
+            previous: Mutex::new(vec![None; bounds.len() + 1]),
+        });
+    }
+
+    /// Records the latency of a request in the bucket for its value size. This
+    /// has no effect unless the size buckets are enabled.
+    pub fn record(size: usize, latency_ns: u64) {
+        if let Some(buckets) = SIZE_BUCKETS.get() {
+            let index = buckets.bounds.partition_point(|bound| *bound <= size);
+            let _ = buckets.histograms[index].increment(latency_ns);
+        }
+    }
+
+    /// Returns the label and the change in the latency histogram for each
+    /// bucket since the previous call.
+    pub fn deltas(&self) -> Vec<(String, Option<histogram::Histogram>)> {
+        let mut previous = self.previous.lock().unwrap();
+        let mut result = Vec::new();
+
+        for (index, histogram) in self.histograms.iter().enumerate() {
+            let current = histogram.load();
+
+            let delta = match (&current, &previous[index]) {
+                (Some(current), Some(previous)) => current.wrapping_sub(previous).ok(),
+                (current, None) => current.clone(),
+                (None, Some(_)) => None,
+            };
+
+            result.push((self.label(index), delta));
+            previous[index] = current;
+        }
+
+        result
+    }
+
+    fn label(&self, index: usize) -> String {
+        if index == 0 {
+            format!("<{}", size_label(self.bounds[0]))
+        } else if index == self.bounds.len() {
+            format!(">={}", size_label(self.bounds[index - 1]))
+        } else {
+            format!(
+                "{}-{}",
+                size_label(self.bounds[index - 1]),
+                size_label(self.bounds[index])
+            )
+        }
+    }
+}
+
+fn size_label(bytes: usize) -> String {
+    if bytes >= 1 << 20 && bytes % (1 << 20) == 0 {
+        format!("{}MB", bytes >> 20)
+    } else if bytes >= 1 << 10 && bytes % (1 << 10) == 0 {
+        format!("{}KB", bytes >> 10)
+    } else {
+        format!("{bytes}B")
     }
 }
 
@@ -287,10 +641,12 @@ macro_rules! histogram {
             name = $name,
             crate = metriken
         )]
-        pub static $ident: metriken::AtomicHistogram = metriken::AtomicHistogram::new(
-            7,
-            64,
-        );
+        pub static $ident: Lazy<metriken::AtomicHistogram> = metriken::Lazy::new(|| {
+            metriken::AtomicHistogram::new(
+                $crate::metrics::histogram_grouping_power(),
+                $crate::metrics::histogram_max_value_power(),
+            )
+        });
         paste! {
             pub static [<$ident _HISTOGRAM>]: &'static str = $name;
         }
@@ -301,10 +657,12 @@ macro_rules! histogram {
             description = $description,
             crate = metriken
         )]
-        pub static $ident: metriken::AtomicHistogram = metriken::AtomicHistogram::new(
-            7,
-            64,
-        );
+        pub static $ident: Lazy<metriken::AtomicHistogram> = metriken::Lazy::new(|| {
+            metriken::AtomicHistogram::new(
+                $crate::metrics::histogram_grouping_power(),
+                $crate::metrics::histogram_max_value_power(),
+            )
+        });
         paste! {
             #[allow(dead_code)]
             pub static [<$ident _HISTOGRAM>]: &'static str = $name;
@@ -382,29 +740,213 @@ histogram!(
     "distribution of response latencies in nanoseconds."
 );
 
+counter!(
+    LATENCY_OVERFLOW,
+    "response_latency/overflow",
+    "response latencies which exceeded the range of the histogram"
+);
+
 histogram!(
     SESSION_LIFECYCLE_REQUESTS,
     "session_lifecycle_requests",
     "distribution of requests per session lifecycle. incremented at time of session close."
 );
 
+histogram!(
+    GET_HIT_LATENCY,
+    "get_hit_latency",
+    "distribution of get latencies for hits in nanoseconds."
+);
+
+histogram!(
+    GET_MISS_LATENCY,
+    "get_miss_latency",
+    "distribution of get latencies for misses in nanoseconds."
+);
+
+histogram!(
+    RESPONSE_EX_LATENCY,
+    "response_ex_latency",
+    "distribution of the time until a request failed with an exception in nanoseconds."
+);
+
+histogram!(
+    RESPONSE_TIMEOUT_LATENCY,
+    "response_timeout_latency",
+    "distribution of the time until a request timed out in nanoseconds."
+);
+
+histogram!(
+    GET_LATENCY,
+    "get_latency",
+    "distribution of successful get latencies in nanoseconds."
+);
+
+histogram!(
+    SET_LATENCY,
+    "set_latency",
+    "distribution of successful set, add, and replace latencies in nanoseconds."
+);
+
+histogram!(
+    DELETE_LATENCY,
+    "delete_latency",
+    "distribution of successful delete latencies in nanoseconds."
+);
+
+histogram!(
+    SERVER_LATENCY,
+    "server_latency",
+    "distribution of the backend-reported processing times in nanoseconds."
+);
+
+histogram!(
+    CHURN_LATENCY,
+    "churn_latency",
+    "distribution of successful churn write (set then delete) latencies in nanoseconds."
+);
+
+histogram!(
+    DELETE_IF_LATENCY,
+    "delete_if_latency",
+    "distribution of successful conditional delete (get then delete) latencies in nanoseconds."
+);
+
+histogram!(
+    TOUCH_LATENCY,
+    "touch_latency",
+    "distribution of successful touch latencies in nanoseconds."
+);
+
+histogram!(
+    BACKGROUND_LATENCY,
+    "background_latency",
+    "distribution of successful background request latencies in nanoseconds."
+);
+
+histogram!(
+    FOREGROUND_LATENCY_BACKGROUND_ON,
+    "foreground_latency/background_on",
+    "distribution of successful foreground latencies while the background load is on."
+);
+
+histogram!(
+    FOREGROUND_LATENCY_BACKGROUND_OFF,
+    "foreground_latency/background_off",
+    "distribution of successful foreground latencies while the background load is off."
+);
+
+counter!(
+    BURST,
+    "burst",
+    "the number of bursts of the workload duty cycle which have started after an idle period."
+);
+
+histogram!(
+    ISOLATED_LATENCY,
+    "isolated_latency",
+    "distribution of successful latencies for commands with isolated latency in nanoseconds."
+);
+
+histogram!(
+    NON_ISOLATED_LATENCY,
+    "non_isolated_latency",
+    "distribution of successful latencies for all other commands when any command has isolated latency."
+);
+
+histogram!(
+    BURST_START_LATENCY,
+    "burst_start_latency",
+    "distribution of successful latencies for the first request of each burst in nanoseconds."
+);
+
+histogram!(
+    COLD_PROBE_LATENCY,
+    "cold_probe_latency",
+    "distribution of latencies for a get on a fresh connection, including connection setup, in nanoseconds."
+);
+
+histogram!(
+    REQUEST_BUILD_LATENCY,
+    "request_build_latency",
+    "distribution of time spent preparing requests before they are sent in nanoseconds."
+);
+
+histogram!(
+    RESPONSE_RECORD_LATENCY,
+    "response_record_latency",
+    "distribution of time the null client spends recording each response in nanoseconds."
+);
+
+histogram!(
+    INTERARRIVAL,
+    "interarrival",
+    "distribution of the time between consecutive requests being issued in nanoseconds."
+);
+
+histogram!(
+    KEY_BYTES,
+    "key_bytes",
+    "distribution of the lengths of the generated keys in bytes."
+);
+
+histogram!(
+    VALUE_BYTES,
+    "value_bytes",
+    "distribution of the lengths of the generated values in bytes."
+);
+
 histogram!(PUBSUB_LATENCY, "pubsub_latency");
 
 histogram!(PUBSUB_PUBLISH_LATENCY, "pubsub_publish_latency");
 
 gauge!(RATELIMIT_CURR, "ratelimit/current");
+gauge!(HOTSPOT_SHIFT, "workload/hotspot/shift");
+gauge!(KEYSPACE_ACTIVE_KEYS, "workload/keyspace/active_keys");
 counter!(RATELIMIT_DROPPED, "ratelimit/dropped");
 
+counter!(
+    CLOCK_JUMP,
+    "clock/jump",
+    "times the wall clock diverged from the monotonic clock"
+);
+
 gauge!(CONNECT_CURR, "client/connections/current");
+gauge!(
+    TASK_MEM_BYTES,
+    "client/buffered_bytes",
+    "the bytes of request payloads queued for or in flight in the client tasks"
+);
 counter!(CONNECT_OK, "client/connect/ok");
 counter!(CONNECT_TIMEOUT, "client/connect/timeout");
 
 counter!(REQUEST, "client/request/total", "total requests dequeued");
+counter!(
+    REQUEST_GET,
+    "client/request/get",
+    "get requests dequeued, including chunked gets"
+);
+counter!(
+    REQUEST_SET,
+    "client/request/set",
+    "set requests dequeued, including adds, replaces, and chunked sets"
+);
+counter!(
+    REQUEST_DELETE,
+    "client/request/delete",
+    "delete requests dequeued"
+);
 counter!(
     REQUEST_DROPPED,
     "client/request/dropped",
     "number of requests dropped due to a full work queue"
 );
+
+counter!(
+    PRELOAD,
+    "client/request/preload",
+    "number of writes sent during the preload phase"
+);
 counter!(
     REQUEST_OK,
     "client/request/ok",
@@ -415,6 +957,51 @@ counter!(
     "client/connect/reconnect",
     "requests to reconnect"
 );
+counter!(
+    DNS_CHANGE,
+    "client/dns/change",
+    "re-resolutions of the endpoints which found changed addresses"
+);
+counter!(
+    CONNECT_RECYCLE,
+    "client/connect/recycle",
+    "connections closed because they reached their maximum age or request count"
+);
+counter!(
+    RECONNECT_THROTTLED,
+    "client/connect/throttled",
+    "reconnects which were delayed by the maximum reconnect rate"
+);
+counter!(
+    IDLE_TIME,
+    "client/idle_time",
+    "total time in nanoseconds that client tasks spent waiting for work"
+);
+counter!(
+    RESPONSE_NEAR_TIMEOUT,
+    "client/response/near_timeout",
+    "successful responses with a latency just below the request timeout"
+);
+counter!(
+    CHANNEL_CLOSED,
+    "client/channel_closed",
+    "client tasks which exited because the work queue closed during the run"
+);
+counter!(
+    CIRCUIT_OPEN,
+    "client/circuit_breaker/open",
+    "number of times a client task opened its circuit breaker"
+);
+counter!(
+    CIRCUIT_OPEN_TIME,
+    "client/circuit_breaker/open_time",
+    "total time in nanoseconds that client tasks spent with an open circuit breaker"
+);
+counter!(
+    INJECTED_ERROR,
+    "client/request/injected_error",
+    "requests failed with a synthetic error instead of being sent"
+);
 counter!(
     REQUEST_UNSUPPORTED,
     "client/request/unsupported",
@@ -481,6 +1068,101 @@ counter!(
     "responses that were invalid for the protocol"
 );
 
+counter!(SET_EMPTY, "set/empty", "sets of a zero-length value");
+counter!(
+    SET_CREATED,
+    "set/created",
+    "successful sets which created a new key"
+);
+counter!(
+    SET_OVERWRITTEN,
+    "set/overwritten",
+    "successful sets which overwrote the value of an existing key"
+);
+
+counter!(
+    COALESCED,
+    "client/request/coalesced",
+    "gets which shared the result of an in-flight get for the same key"
+);
+counter!(
+    CLIENT_CACHE_HIT,
+    "client/cache/hit",
+    "gets which were served by the client-side cache without a request"
+);
+counter!(
+    CLIENT_CACHE_MISS,
+    "client/cache/miss",
+    "gets which were not in the client-side cache and sent a request"
+);
+counter!(
+    KEY_LOCK_CONTENDED,
+    "client/request/key_lock_contended",
+    "sets which waited for a concurrent set to the same key to complete"
+);
+
+counter!(
+    SIZE_MISMATCH,
+    "client/response/size_mismatch",
+    "get hits where the value length did not match the expected length"
+);
+
+counter!(
+    STALE_READ,
+    "client/response/stale_read",
+    "gets which read an older sequence number than a previous get for the key"
+);
+
+counter!(
+    RECORD_INCOMPLETE,
+    "client/response/record_incomplete",
+    "gets which read a record with a missing or incorrectly sized field"
+);
+
+counter!(
+    HMAC_FAIL,
+    "client/response/hmac_fail",
+    "gets which read a value with an HMAC which does not match the key and value"
+);
+
+counter!(
+    BYTES_WRITTEN,
+    "client/bytes/written",
+    "value bytes successfully written to the backend"
+);
+counter!(
+    BYTES_READ,
+    "client/bytes/read",
+    "value bytes read from the backend"
+);
+counter!(
+    BYTES_STORED,
+    "client/bytes/stored",
+    "key and value bytes of successful sets"
+);
+counter!(
+    BYTES_LOGICAL,
+    "client/bytes/logical",
+    "payload bytes of successful sets, excluding value headers"
+);
+
+counter!(
+    RECENT_DELETE,
+    "workload/recent_delete",
+    "deletes which were retargeted to a recently written key"
+);
+
+counter!(
+    ORDERED_WRITE,
+    "workload/ordered_write",
+    "gets replaced by a set because the key had not yet been written"
+);
+counter!(
+    VALUE_CLAMPED,
+    "client/request/value_clamped",
+    "generated values truncated to the maximum value size"
+);
+
 counter!(RESPONSE_HIT, "client/response/hit");
 counter!(RESPONSE_MISS, "client/response/miss");
 
@@ -525,6 +1207,111 @@ counter!(
     "delete requests that resulted in timeout"
 );
 
+// sets issued to repopulate a key after a get miss, these are tracked apart
+// from the set metrics so they do not skew the configured command mix
+request!(POPULATE, "populate");
+
+request!(COLD_PROBE, "cold_probe");
+
+counter!(
+    SERVER_LATENCY_MISSING,
+    "server_latency/missing",
+    "responses without a valid server latency header"
+);
+
+// clients rebuilt with fresh credentials after the backend rejected them
+request!(CREDENTIAL_REFRESH, "credential_refresh");
+
+// gets which are mirrored to the shadow cache
+request!(SHADOW, "shadow");
+counter!(
+    SHADOW_MISMATCH,
+    "shadow/mismatch",
+    "shadow reads which returned a different result than the primary"
+);
+
+request!(TTL_CHECK, "ttl_check");
+counter!(
+    TTL_EXPIRED_OK,
+    "ttl_check/expired_ok",
+    "keys which were not found after their ttl elapsed"
+);
+counter!(
+    TTL_EXPIRED_FAIL,
+    "ttl_check/expired_fail",
+    "keys which were still found after their ttl elapsed"
+);
+counter!(
+    TTL_EXPIRED_ERROR,
+    "ttl_check/expired_error",
+    "reads of expired keys which failed or timed out"
+);
+counter!(
+    TTL_EXPIRED_SKIPPED,
+    "ttl_check/expired_skipped",
+    "expiry reads which were skipped because too many were already pending"
+);
+
+request!(CHUNKED_GET, "chunked_get");
+counter!(
+    CHUNKED_GET_MISS,
+    "chunked_get/miss",
+    "chunked gets where at least one chunk was not found"
+);
+request!(CHUNKED_SET, "chunked_set");
+request!(CHUNKED_DELETE, "chunked_delete");
+counter!(CHUNK_READ, "chunk/read", "chunks read by chunked gets");
+counter!(CHUNK_WRITE, "chunk/write", "chunks written by chunked sets");
+
+counter!(
+    BATCH_KEY_OK,
+    "batch/key/ok",
+    "keys within multi-key operations which succeeded"
+);
+counter!(
+    BATCH_KEY_FAIL,
+    "batch/key/fail",
+    "keys within multi-key operations which failed or were not found"
+);
+counter!(
+    BATCH_PARTIAL,
+    "batch/partial",
+    "multi-key operations where some, but not all, of the keys succeeded"
+);
+
+request!(UPDATE, "update");
+counter!(
+    UPDATE_MISS,
+    "update/miss",
+    "updates which were not written back because the key was not found"
+);
+
+request!(CHURN, "churn");
+
+request!(DELETE_IF, "delete_if");
+counter!(
+    DELETE_IF_MATCH,
+    "delete_if/match",
+    "conditional deletes which deleted the key because its value matched"
+);
+counter!(
+    DELETE_IF_MISMATCH,
+    "delete_if/mismatch",
+    "conditional deletes which did not delete the key because its value did not match"
+);
+counter!(
+    DELETE_IF_MISS,
+    "delete_if/miss",
+    "conditional deletes which did not delete the key because it was not found"
+);
+
+request!(TOUCH, "touch");
+counter!(
+    TOUCH_MISS,
+    "touch/miss",
+    "touches which did not update a ttl because the key was not found"
+);
+
 request!(HASH_GET, "hash_get");
 counter!(HASH_GET_FIELD_HIT, "hash_get/field_hit");
 counter!(HASH_GET_FIELD_MISS, "hash_get/field_miss");
@@ -39,6 +39,13 @@ pub struct Connector {
 
 impl Connector {
     pub fn new(config: &Config) -> Result<Self> {
+        // the config rejects a `uds_path` with TLS
+        if let Some(path) = config.target().uds_path() {
+            return Ok(Connector {
+                inner: ConnectorImpl::Unix(path.into()),
+            });
+        }
+
         if config.tls().is_none() {
             Self::plaintext()
         } else {
@@ -195,6 +202,12 @@ impl Connector {
                     inner: StreamImpl::Tcp(s),
                 })
             }
+            ConnectorImpl::Unix(path) => {
+                let s = tokio::net::UnixStream::connect(path).await?;
+                Ok(Stream {
+                    inner: StreamImpl::Unix(s),
+                })
+            }
             #[cfg(feature = "boringssl")]
             ConnectorImpl::BoringsslTlsTcp(connector) => {
                 let stream = tokio::net::TcpStream::connect(addr).await?;
@@ -251,6 +264,7 @@ impl Connector {
 
 enum ConnectorImpl {
     Tcp,
+    Unix(std::path::PathBuf),
     #[cfg(feature = "boringssl")]
     BoringsslTlsTcp(BoringsslTlsTcp),
     #[cfg(feature = "openssl")]
@@ -277,6 +291,7 @@ pub struct Stream {
 
 enum StreamImpl {
     Tcp(tokio::net::TcpStream),
+    Unix(tokio::net::UnixStream),
     #[cfg(feature = "boringssl")]
     BoringsslTlsTcp(tokio_boring::SslStream<tokio::net::TcpStream>),
     #[cfg(feature = "openssl")]
@@ -291,6 +306,7 @@ impl AsyncRead for Stream {
     ) -> std::task::Poll<std::result::Result<(), std::io::Error>> {
         match &mut self.inner {
             StreamImpl::Tcp(s) => std::pin::Pin::new(s).poll_read(cx, buf),
+            StreamImpl::Unix(s) => std::pin::Pin::new(s).poll_read(cx, buf),
             #[cfg(feature = "boringssl")]
             StreamImpl::BoringsslTlsTcp(s) => std::pin::Pin::new(s).poll_read(cx, buf),
             #[cfg(feature = "openssl")]
@@ -307,6 +323,7 @@ impl AsyncWrite for Stream {
     ) -> std::task::Poll<std::result::Result<usize, std::io::Error>> {
         match &mut self.inner {
             StreamImpl::Tcp(s) => std::pin::Pin::new(s).poll_write(cx, buf),
+            StreamImpl::Unix(s) => std::pin::Pin::new(s).poll_write(cx, buf),
             #[cfg(feature = "boringssl")]
             StreamImpl::BoringsslTlsTcp(s) => std::pin::Pin::new(s).poll_write(cx, buf),
             #[cfg(feature = "openssl")]
@@ -319,6 +336,7 @@ impl AsyncWrite for Stream {
     ) -> std::task::Poll<std::result::Result<(), std::io::Error>> {
         match &mut self.inner {
             StreamImpl::Tcp(s) => std::pin::Pin::new(s).poll_flush(cx),
+            StreamImpl::Unix(s) => std::pin::Pin::new(s).poll_flush(cx),
             #[cfg(feature = "boringssl")]
             StreamImpl::BoringsslTlsTcp(s) => std::pin::Pin::new(s).poll_flush(cx),
             #[cfg(feature = "openssl")]
@@ -331,6 +349,7 @@ impl AsyncWrite for Stream {
     ) -> std::task::Poll<std::result::Result<(), std::io::Error>> {
         match &mut self.inner {
             StreamImpl::Tcp(s) => std::pin::Pin::new(s).poll_shutdown(cx),
+            StreamImpl::Unix(s) => std::pin::Pin::new(s).poll_shutdown(cx),
             #[cfg(feature = "boringssl")]
             StreamImpl::BoringsslTlsTcp(s) => std::pin::Pin::new(s).poll_shutdown(cx),
             #[cfg(feature = "openssl")]
@@ -350,6 +369,10 @@ impl hyper::rt::Read for Stream {
                 let mut buf = ReadBuf::uninit(unsafe { rbc.as_mut() });
                 std::pin::Pin::new(s).poll_read(cx, &mut buf)
             }
+            StreamImpl::Unix(s) => {
+                let mut buf = ReadBuf::uninit(unsafe { rbc.as_mut() });
+                std::pin::Pin::new(s).poll_read(cx, &mut buf)
+            }
             #[cfg(feature = "boringssl")]
             StreamImpl::BoringsslTlsTcp(s) => {
                 let mut buf = ReadBuf::uninit(unsafe { rbc.as_mut() });
@@ -372,6 +395,7 @@ impl hyper::rt::Write for Stream {
     ) -> std::task::Poll<std::result::Result<usize, std::io::Error>> {
         match &mut self.inner {
             StreamImpl::Tcp(s) => std::pin::Pin::new(s).poll_write(cx, buf),
+            StreamImpl::Unix(s) => std::pin::Pin::new(s).poll_write(cx, buf),
             #[cfg(feature = "boringssl")]
             StreamImpl::BoringsslTlsTcp(s) => std::pin::Pin::new(s).poll_write(cx, buf),
             #[cfg(feature = "openssl")]
@@ -385,6 +409,7 @@ impl hyper::rt::Write for Stream {
     ) -> std::task::Poll<std::result::Result<(), std::io::Error>> {
         match &mut self.inner {
             StreamImpl::Tcp(s) => std::pin::Pin::new(s).poll_flush(cx),
+            StreamImpl::Unix(s) => std::pin::Pin::new(s).poll_flush(cx),
             #[cfg(feature = "boringssl")]
             StreamImpl::BoringsslTlsTcp(s) => std::pin::Pin::new(s).poll_flush(cx),
             #[cfg(feature = "openssl")]
@@ -398,6 +423,7 @@ impl hyper::rt::Write for Stream {
     ) -> std::task::Poll<std::result::Result<(), std::io::Error>> {
         match &mut self.inner {
             StreamImpl::Tcp(s) => std::pin::Pin::new(s).poll_shutdown(cx),
+            StreamImpl::Unix(s) => std::pin::Pin::new(s).poll_shutdown(cx),
             #[cfg(feature = "boringssl")]
             StreamImpl::BoringsslTlsTcp(s) => std::pin::Pin::new(s).poll_shutdown(cx),
             #[cfg(feature = "openssl")]
@@ -1,8 +1,13 @@
+use crate::workload::Component;
 use crate::*;
 use chrono::{Timelike, Utc};
-use config::MetricsFormat;
+use config::{EndpointRank, LatencyUnit, MetricsFormat, ThroughputUnit, Verb};
 use metriken_exposition::{MsgpackToParquet, ParquetOptions, Snapshot, SnapshotterBuilder};
+use ratelimit::Ratelimiter;
+use std::collections::VecDeque;
+use std::io::IsTerminal;
 use std::os::fd::{AsRawFd, FromRawFd};
+use std::sync::Mutex;
 use tokio::fs::File;
 use tokio::io::{AsyncWriteExt, BufWriter};
 use tokio::time::{timeout, Instant};
@@ -19,66 +24,422 @@ macro_rules! output {
     }};
 }
 
-pub async fn log(config: Config) {
+/// The snapshot returned for a trial. It is shared with the log so that an
+/// excluded ramp-down is cut from it at the same point as from the cumulative
+/// stats.
+#[derive(Default)]
+pub struct TrialSummary {
+    snapshot: MetricsSnapshot,
+    frozen: bool,
+}
+
+impl TrialSummary {
+    pub fn new() -> Self {
+        let mut snapshot = MetricsSnapshot::default();
+        snapshot.update();
+
+        Self {
+            snapshot,
+            frozen: false,
+        }
+    }
+
+    /// Stops the summary at the current metrics.
+    fn freeze(&mut self) {
+        self.snapshot.refresh();
+        self.frozen = true;
+    }
+
+    /// Returns the snapshot, which covers the trial up to now unless it was
+    /// stopped at the start of the ramp-down.
+    pub fn finish(&mut self) -> MetricsSnapshot {
+        if !self.frozen {
+            self.snapshot.refresh();
+        }
+
+        std::mem::take(&mut self.snapshot)
+    }
+}
+
+pub async fn log(
+    config: Config,
+    ratelimiter: Option<Arc<Ratelimiter>>,
+    summary: Arc<Mutex<TrialSummary>>,
+) {
     WAIT.fetch_add(1, Ordering::Relaxed);
 
+    let test_start = Instant::now();
+
     let mut window_id = 0;
 
     let mut snapshot = MetricsSnapshot::default();
     tokio::time::sleep(Duration::from_secs(1)).await;
     snapshot.update();
 
+    // a separate snapshot which is never rebased, used for the final summary
+    let mut cumulative = MetricsSnapshot::default();
+    cumulative.update();
+
+    // the number of reporting windows between each reset of the stats
+    let reset_windows = std::cmp::max(
+        1,
+        config.general().reset_interval().as_secs() / config.general().interval().as_secs(),
+    );
+
+    let unit = config.general().latency_unit();
+    let throughput = config.general().throughput_unit();
+
     let client = !config.workload().keyspaces().is_empty();
     let pubsub = !config.workload().topics().is_empty();
 
     // get an aligned start time
-    let start = tokio::time::Instant::now() - Duration::from_nanos(Utc::now().nanosecond() as u64)
-        + config.general().interval();
+    let start = if config.general().align_reports() {
+        report_boundary(config.general().interval())
+    } else {
+        tokio::time::Instant::now() - Duration::from_nanos(Utc::now().nanosecond() as u64)
+            + config.general().interval()
+    };
 
     // get the stop time
     let stop = start + config.general().duration();
 
     let mut interval = tokio::time::interval_at(start, config.general().interval());
 
+    // the ramp-down ends with the run
+    let ramp_down = config
+        .workload()
+        .ratelimit()
+        .ramp_down()
+        .map(|window| stop - window);
+
+    if let (Some(ramp_start), Some(ratelimiter)) = (ramp_down, ratelimiter) {
+        tokio::spawn(workload::ramp_down(ratelimiter, ramp_start, stop));
+    }
+
+    // set once the cumulative stats have been captured at the start of an
+    // excluded ramp-down
+    let mut excluded = false;
+
+    let mut hotspot_shift = HOTSPOT_SHIFT.value();
+
+    // the recent p50 and p99 latencies for the sparkline, only shown when
+    // writing to a terminal
+    let sparkline = config
+        .general()
+        .sparkline()
+        .filter(|_| std::io::stdout().is_terminal());
+    let mut recent: VecDeque<(u64, u64)> = VecDeque::new();
+
+    // the hits and misses in each reporting window, along with the time since
+    // the start of the test at the end of the window
+    let mut hit_windows: Vec<(Duration, u64, u64)> = Vec::new();
+    let mut hits = RESPONSE_HIT.value();
+    let mut misses = RESPONSE_MISS.value();
+
+    // the p99 of the most recent reporting windows, used to stop the test once
+    // it has stabilized
+    let mut recent_p99: VecDeque<u64> = VecDeque::new();
+
+    // the start of a ramp-down which is excluded from the cumulative stats
+    let exclude_from = ramp_down.filter(|_| config.workload().ratelimit().exclude_ramp_down());
+
     while RUNNING.load(Ordering::Relaxed) && Instant::now() + config.general().interval() <= stop {
-        // use a timeout here so we always check RUNNING at least once a second
-        if timeout(Duration::from_secs(1), interval.tick())
-            .await
-            .is_err()
-        {
+        // use a timeout here so we always check RUNNING at least once a
+        // second, and wake at the start of an excluded ramp-down
+        let mut wait = Duration::from_secs(1);
+
+        if let Some(ramp_start) = exclude_from.filter(|_| !excluded) {
+            wait = wait.min(ramp_start.saturating_duration_since(Instant::now()));
+        }
+
+        let tick = timeout(wait, interval.tick()).await;
+
+        if !excluded && exclude_from.is_some_and(|ramp_start| Instant::now() >= ramp_start) {
+            exclude(&mut cumulative, &summary);
+            excluded = true;
+        }
+
+        if tick.is_err() {
             continue;
         }
 
-        snapshot.update();
+        snapshot.refresh();
 
         output!("-----");
         output!("Window: {}", window_id);
 
+        hit_windows.push((
+            test_start.elapsed(),
+            RESPONSE_HIT.value() - hits,
+            RESPONSE_MISS.value() - misses,
+        ));
+        hits = RESPONSE_HIT.value();
+        misses = RESPONSE_MISS.value();
+
+        // a shifted hotspot means the cache must re-warm, which should be
+        // visible in the hit rate of the following windows
+        if HOTSPOT_SHIFT.value() != hotspot_shift {
+            hotspot_shift = HOTSPOT_SHIFT.value();
+            output!("Hotspot: shifted (shift: {hotspot_shift})");
+        }
+
         // output the client stats
         if client {
-            client_stats(&mut snapshot);
+            client_stats(&mut snapshot, unit, throughput);
+
+            if let Some(windows) = sparkline {
+                sparklines(&snapshot, &mut recent, windows);
+            }
+
+            if config.general().stabilize_and_stop()
+                && p99_stable(&snapshot, &mut recent_p99, &config)
+            {
+                output!(
+                    "Stopping: p99 latency stabilized over the last {} windows",
+                    recent_p99.len()
+                );
+                RUNNING.store(false, Ordering::Relaxed);
+            }
         }
 
         // output the pubsub stats
         if pubsub {
-            pubsub_stats(&mut snapshot);
+            pubsub_stats(&mut snapshot, unit);
         }
 
         window_id += 1;
+
+        if window_id % reset_windows == 0 {
+            snapshot.rebase();
+        }
+    }
+
+    // the reporting windows may end before an excluded ramp-down begins, so
+    // wait for it unless the run stops first
+    if let Some(ramp_start) = exclude_from.filter(|_| !excluded) {
+        while RUNNING.load(Ordering::Relaxed) && Instant::now() < ramp_start {
+            let _ = timeout(Duration::from_secs(1), tokio::time::sleep_until(ramp_start)).await;
+        }
+
+        if Instant::now() >= ramp_start {
+            exclude(&mut cumulative, &summary);
+            excluded = true;
+        }
+    }
+
+    // output the cumulative stats for the entire run, or up to the start of
+    // an excluded ramp-down
+    if !excluded {
+        cumulative.refresh();
+    }
+
+    output!("-----");
+    output!("Summary (cumulative):");
+
+    if excluded {
+        output!("Responses received during the ramp-down are excluded");
+    }
+
+    let clock_jumps = CLOCK_JUMP.value();
+
+    if clock_jumps > 0 {
+        output!("WARNING: the wall clock jumped {clock_jumps} times during the run");
+    }
+
+    let overflows = LATENCY_OVERFLOW.value();
+
+    if overflows > 0 {
+        output!(
+            "WARNING: {overflows} response latencies exceeded the histogram range and are excluded from the percentiles, consider raising `histogram_max_value_power`"
+        );
+    }
+
+    if client {
+        client_stats(&mut cumulative, unit, throughput);
+        latency_jitter(&cumulative, unit);
+        coordinated_omission(&cumulative, &config);
+        operation_mix(&cumulative, &config);
+        lengths(&cumulative);
+        background_latencies(&cumulative, unit);
+        isolated_latencies(&cumulative, unit);
+        burst_latencies(&cumulative, unit);
+        size_latencies(unit);
+        server_latencies(&cumulative, unit);
+        outcome_table(&cumulative);
+
+        if config
+            .client()
+            .is_some_and(|client| client.connection_stats())
+        {
+            connection_table();
+        }
+
+        if let Some(client) = config
+            .client()
+            .filter(|client| client.endpoint_comparison())
+        {
+            endpoint_comparison(unit, client.endpoint_rank());
+        }
+
+        if config
+            .client()
+            .is_some_and(|client| client.client_cache_size().is_some())
+        {
+            client_cache_stats(&cumulative);
+        }
+
+        if config
+            .client()
+            .is_some_and(|client| client.classify_writes())
+        {
+            write_classification(&cumulative);
+        }
+
+        if let Some(fraction) = config.general().warm_fraction() {
+            time_to_warm(&hit_windows, fraction);
+        }
+
+        if let Some(output) = config.general().latency_histogram_output() {
+            latency_histograms(&cumulative, output);
+        }
+    }
+
+    if pubsub {
+        pubsub_stats(&mut cumulative, unit);
+    }
+
+    if let Some(output) = config.general().summary_output() {
+        summary_json(output, client, &cumulative, &config);
     }
 
     RUNNING.store(false, Ordering::Relaxed);
     WAIT.fetch_sub(1, Ordering::Relaxed);
 }
 
+/// Returns the time of the next wall clock boundary which is a multiple of the
+/// reporting interval since the unix epoch.
+fn report_boundary(interval: Duration) -> Instant {
+    let interval = interval.as_nanos();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+
+    Instant::now() + Duration::from_nanos((interval - now % interval) as u64)
+}
+
+/// Periodically writes the current value of every counter to the file at
+/// `path`. Each checkpoint is written to a temporary file which then replaces
+/// the previous checkpoint, so the file is never left partially written.
+pub async fn checkpoint(path: String, interval: Duration) {
+    let temporary = format!("{path}.tmp");
+
+    while !COMPLETE.load(Ordering::Relaxed) {
+        let mut contents = format!(
+            "# {}\n",
+            Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
+        );
+
+        for metric in metriken::metrics().iter() {
+            let Some(any) = metric.as_any() else {
+                continue;
+            };
+
+            if let Some(counter) = any.downcast_ref::<metriken::Counter>() {
+                contents.push_str(&format!("{} {}\n", metric.name(), counter.value()));
+            }
+        }
+
+        let result = match tokio::fs::write(&temporary, contents).await {
+            Ok(()) => tokio::fs::rename(&temporary, &path).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            error!("failed to write counter checkpoint: {path}: {e}");
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Compares the progress of the wall clock and the monotonic clock once a
+/// second, warning when they differ by more than the threshold. A jump of the
+/// wall clock does not affect the measured latencies, but does affect wall
+/// clock timestamps and any timing derived from them.
+pub async fn clock_skew(threshold: Duration) {
+    let mut instant = Instant::now();
+    let mut system = SystemTime::now();
+
+    while !COMPLETE.load(Ordering::Relaxed) {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let now_instant = Instant::now();
+        let now_system = SystemTime::now();
+
+        let monotonic = now_instant.duration_since(instant).as_secs_f64();
+
+        // the wall clock may have moved backwards
+        let wall = match now_system.duration_since(system) {
+            Ok(elapsed) => elapsed.as_secs_f64(),
+            Err(e) => -e.duration().as_secs_f64(),
+        };
+
+        let skew = wall - monotonic;
+
+        if skew.abs() > threshold.as_secs_f64() {
+            CLOCK_JUMP.increment();
+            output!(
+                "WARNING: the wall clock jumped by {:.3} s relative to the monotonic clock, wall clock based timing may be corrupted",
+                skew
+            );
+        }
+
+        instant = now_instant;
+        system = now_system;
+    }
+}
+
+/// Stops the cumulative stats and the trial summary at the start of an
+/// excluded ramp-down.
+fn exclude(cumulative: &mut MetricsSnapshot, summary: &Mutex<TrialSummary>) {
+    cumulative.refresh();
+    summary.lock().unwrap().freeze();
+}
+
+/// Outputs the cumulative stats since `start`. This is used when the process
+/// is terminated without producing the regular summary.
+pub fn deadline_summary(config: &Config, start: SystemTime) {
+    let unit = config.general().latency_unit();
+    let throughput = config.general().throughput_unit();
+
+    // the histograms of a new snapshot cover everything recorded so far, so
+    // the counters are made to match and only the start of the window needs
+    // to be set
+    let mut snapshot = MetricsSnapshot::new();
+    snapshot.previous = start;
+    snapshot.counters.previous.clear();
+
+    output!("Summary (at deadline):");
+
+    if !config.workload().keyspaces().is_empty() {
+        client_stats(&mut snapshot, unit, throughput);
+    }
+
+    if !config.workload().topics().is_empty() {
+        pubsub_stats(&mut snapshot, unit);
+    }
+}
+
 /// Outputs client stats
-fn client_stats(snapshot: &mut MetricsSnapshot) {
+fn client_stats(snapshot: &mut MetricsSnapshot, unit: LatencyUnit, throughput: ThroughputUnit) {
     let connect_ok = snapshot.counter_rate(CONNECT_OK_COUNTER);
     let connect_ex = snapshot.counter_rate(CONNECT_EX_COUNTER);
     let connect_timeout = snapshot.counter_rate(CONNECT_TIMEOUT_COUNTER);
     let connect_total = snapshot.counter_rate(CONNECT_COUNTER);
 
     let request_reconnect = snapshot.counter_rate(REQUEST_RECONNECT_COUNTER);
+    let connect_recycle = snapshot.counter_rate(CONNECT_RECYCLE_COUNTER);
     let request_ok = snapshot.counter_rate(REQUEST_OK_COUNTER);
     let request_unsupported = snapshot.counter_rate(REQUEST_UNSUPPORTED_COUNTER);
     let request_total = snapshot.counter_rate(REQUEST_COUNTER);
@@ -99,12 +460,25 @@ fn client_stats(snapshot: &mut MetricsSnapshot) {
         connect_sr
     );
     output!(
-        "Client Connection Rates (/s): Attempt: {:.2} Opened: {:.2} Errors: {:.2} Timeout: {:.2} Closed: {:.2}",
+        "Client Connection Rates (/s): Attempt: {:.2} Opened: {:.2} Errors: {:.2} Timeout: {:.2} Closed: {:.2} Recycled: {:.2}",
         connect_total,
         connect_ok,
         connect_ex,
         connect_timeout,
         request_reconnect,
+        connect_recycle,
+    );
+
+    // idle time is in nanoseconds, so this is the average number of idle tasks
+    let idle_tasks = snapshot.counter_rate(IDLE_TIME_COUNTER) / 1_000_000_000.0;
+
+    // similarly, the average number of tasks with an open circuit breaker
+    let open_tasks = snapshot.counter_rate(CIRCUIT_OPEN_TIME_COUNTER) / 1_000_000_000.0;
+
+    output!(
+        "Client Tasks: Idle (avg): {:.2} Circuit Open (avg): {:.2}",
+        idle_tasks,
+        open_tasks
     );
 
     let request_sr = 100.0 * request_ok / request_total;
@@ -127,10 +501,14 @@ fn client_stats(snapshot: &mut MetricsSnapshot) {
     let response_to = 100.0 * response_timeout / response_total;
     let response_hr = 100.0 * response_hit / (response_hit + response_miss);
 
+    let response_near_timeout = snapshot.counter_rate(RESPONSE_NEAR_TIMEOUT_COUNTER);
+    let response_nt = 100.0 * response_near_timeout / response_total;
+
     output!(
-        "Client Response: Success: {:.2} % Timeout: {:.2} % Hit: {:.2} %",
+        "Client Response: Success: {:.2} % Timeout: {:.2} % Near Timeout: {:.2} % Hit: {:.2} %",
         response_sr,
         response_to,
+        response_nt,
         response_hr,
     );
     output!(
@@ -140,119 +518,1532 @@ fn client_stats(snapshot: &mut MetricsSnapshot) {
         response_timeout,
     );
 
-    let mut latencies = "Client Response Latency (us):".to_owned();
+    // the rate of each operation shows when the mix shifts or when a single
+    // operation degrades
+    let get_rate = snapshot.counter_rate(REQUEST_GET_COUNTER);
+    let set_rate = snapshot.counter_rate(REQUEST_SET_COUNTER);
+    let delete_rate = snapshot.counter_rate(REQUEST_DELETE_COUNTER);
+
+    if get_rate + set_rate + delete_rate > 0.0 {
+        output!(
+            "Client Operation Rate (/s): Get: {:.2} Set: {:.2} Delete: {:.2}",
+            get_rate,
+            set_rate,
+            delete_rate,
+        );
+    }
+
+    let mut latencies = format!("Client Response Latency ({}):", unit.label());
 
-    for (label, _percentile, nanoseconds) in response_latency {
-        let microseconds = nanoseconds / 1000;
-        latencies.push_str(&format!(" {label}: {microseconds}"))
+    let mut p50 = None;
+    let mut p99 = None;
+
+    for (label, percentile, nanoseconds) in response_latency {
+        latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)));
+
+        if percentile == 50.0 {
+            p50 = Some(nanoseconds);
+        } else if percentile == 99.0 {
+            p99 = Some(nanoseconds);
+        }
     }
 
     output!("{latencies}");
-}
 
-/// Output pubsub metrics and return the number of successful publish operations
-fn pubsub_stats(snapshot: &mut MetricsSnapshot) {
-    // publisher stats
-    let pubsub_tx_ex = snapshot.counter_rate(PUBSUB_PUBLISH_EX_COUNTER);
-    let pubsub_tx_ok = snapshot.counter_rate(PUBSUB_PUBLISH_OK_COUNTER);
-    let pubsub_tx_timeout = snapshot.counter_rate(PUBSUB_PUBLISH_TIMEOUT_COUNTER);
-    let pubsub_tx_total = snapshot.counter_rate(PUBSUB_PUBLISH_COUNTER);
+    // the time taken by failed requests distinguishes errors which fail fast
+    // from those which fail slowly
+    for (kind, histogram) in [
+        ("Error", RESPONSE_EX_LATENCY_HISTOGRAM),
+        ("Timeout", RESPONSE_TIMEOUT_LATENCY_HISTOGRAM),
+    ] {
+        let percentiles = snapshot.percentiles(histogram);
 
-    let pubsub_publish_latency = snapshot.percentiles(PUBSUB_PUBLISH_LATENCY_HISTOGRAM);
+        if percentiles.is_empty() {
+            continue;
+        }
 
-    // subscriber stats
-    let pubsub_rx_ok = snapshot.counter_rate(PUBSUB_RECEIVE_OK_COUNTER);
-    let pubsub_rx_ex = snapshot.counter_rate(PUBSUB_RECEIVE_EX_COUNTER);
-    let pubsub_rx_corrupt = snapshot.counter_rate(PUBSUB_RECEIVE_CORRUPT_COUNTER);
-    let pubsub_rx_invalid = snapshot.counter_rate(PUBSUB_RECEIVE_INVALID_COUNTER);
-    let pubsub_rx_total = snapshot.counter_rate(PUBSUB_RECEIVE_COUNTER);
+        let mut latencies = format!("Client {kind} Latency ({}):", unit.label());
 
-    // end-to-end stats
-    let pubsub_latency = snapshot.percentiles(PUBSUB_LATENCY_HISTOGRAM);
-    output!("Ratelimit: Current: {}", RATELIMIT_CURR.value());
-    output!("Publishers: Current: {}", PUBSUB_PUBLISHER_CURR.value());
+        for (label, _percentile, nanoseconds) in percentiles {
+            latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)));
+        }
 
-    let pubsub_tx_sr = 100.0 * pubsub_tx_ok / pubsub_tx_total;
-    let pubsub_tx_to = 100.0 * pubsub_tx_timeout / pubsub_tx_total;
-    output!(
-        "Publisher Publish: Success: {:.2} % Timeout: {:.2} %",
-        pubsub_tx_sr,
-        pubsub_tx_to
-    );
+        output!("{latencies}");
+    }
 
-    output!(
-        "Publisher Publish Rate (/s): Ok: {:.2} Error: {:.2} Timeout: {:.2}",
-        pubsub_tx_ok,
-        pubsub_tx_ex,
-        pubsub_tx_timeout,
-    );
+    // goodput only counts the value bytes of successful reads and writes, so
+    // it excludes protocol overhead and failed requests
+    let read = throughput.format(snapshot.counter_rate(BYTES_READ_COUNTER));
+    let written = throughput.format(snapshot.counter_rate(BYTES_WRITTEN_COUNTER));
 
-    output!("Subscribers: Current: {}", PUBSUB_SUBSCRIBER_CURR.value(),);
+    match throughput.label() {
+        Some(label) => output!("Client Goodput ({label}): Read: {read} Written: {written}"),
+        None => output!("Client Goodput: Read: {read} Written: {written}"),
+    }
 
-    let pubsub_rx_sr = 100.0 * pubsub_rx_ok / pubsub_rx_total;
-    let pubsub_rx_cr = 100.0 * pubsub_rx_corrupt / pubsub_rx_total;
-    output!(
-        "Subscriber Receive: Success: {:.2} % Corrupted: {:.2} %",
-        pubsub_rx_sr,
-        pubsub_rx_cr
-    );
+    // the stored bytes include the keys and value headers, so this ratio is
+    // the overhead of a successful set relative to its payload
+    let logical = snapshot.counter_rate(BYTES_LOGICAL_COUNTER);
+    if logical > 0.0 {
+        output!(
+            "Client Write Overhead: Stored/Logical: {:.3}",
+            snapshot.counter_rate(BYTES_STORED_COUNTER) / logical
+        );
+    }
 
-    output!(
-        "Subscriber Receive Rate (/s): Ok: {:.2} Error: {:.2} Corrupt: {:.2} Invalid: {:.2}",
-        pubsub_rx_ok,
-        pubsub_rx_ex,
-        pubsub_rx_corrupt,
-        pubsub_rx_invalid,
-    );
+    // the p99/p50 ratio indicates how much the tail is amplified relative to
+    // the median
+    if let (Some(p50), Some(p99)) = (p50, p99) {
+        if p50 > 0 {
+            output!(
+                "Client Response Latency Tail Ratio (p99/p50): {:.2}",
+                p99 as f64 / p50 as f64
+            );
+        }
+    }
 
-    let mut latencies = "Pubsub Publish Latency (us):".to_owned();
+    // the active keys are only tracked when the keyspace grows
+    let active_keys = KEYSPACE_ACTIVE_KEYS.value();
 
-    for (label, _percentile, nanoseconds) in pubsub_publish_latency {
-        let microseconds = nanoseconds / 1000;
-        latencies.push_str(&format!(" {label}: {microseconds}"))
+    if active_keys > 0 {
+        output!("Client Keyspace: Active Keys: {active_keys}");
     }
 
-    output!("{latencies}");
+    let coalesced = snapshot.counter_rate(COALESCED_COUNTER);
+
+    if coalesced > 0.0 {
+        output!("Client Request Rate (/s): Coalesced Gets: {:.2}", coalesced);
+    }
 
-    let mut latencies = "Pubsub End-to-End Latency (us):".to_owned();
+    let size_mismatch = snapshot.counter_rate(SIZE_MISMATCH_COUNTER);
 
-    for (label, _percentile, nanoseconds) in pubsub_latency {
-        let microseconds = nanoseconds / 1000;
-        latencies.push_str(&format!(" {label}: {microseconds}"))
+    if size_mismatch > 0.0 {
+        output!(
+            "Client Response: Size Mismatch: {:.2} % of hits",
+            100.0 * size_mismatch / response_hit
+        );
     }
 
-    output!("{latencies}");
+    let stale_read = snapshot.counter_rate(STALE_READ_COUNTER);
+
+    if stale_read > 0.0 {
+        output!(
+            "Client Response: Stale Read: {:.2} % of hits",
+            100.0 * stale_read / response_hit
+        );
+    }
+
+    let record_incomplete = snapshot.counter_rate(RECORD_INCOMPLETE_COUNTER);
+
+    if record_incomplete > 0.0 {
+        output!(
+            "Client Response: Incomplete Record: {:.2} % of hits",
+            100.0 * record_incomplete / response_hit
+        );
+    }
+
+    let hmac_fail = snapshot.counter_rate(HMAC_FAIL_COUNTER);
+
+    if hmac_fail > 0.0 {
+        output!(
+            "Client Response: HMAC Failure: {:.2} % of hits",
+            100.0 * hmac_fail / response_hit
+        );
+    }
+
+    let batch_ok = snapshot.counter_rate(BATCH_KEY_OK_COUNTER);
+    let batch_fail = snapshot.counter_rate(BATCH_KEY_FAIL_COUNTER);
+
+    if batch_fail > 0.0 {
+        output!(
+            "Client Batch: Partial (/s): {:.2} Key Success: {:.2} %",
+            snapshot.counter_rate(BATCH_PARTIAL_COUNTER),
+            100.0 * batch_ok / (batch_ok + batch_fail),
+        );
+    }
+
+    let shadow_total = snapshot.counter_rate(SHADOW_COUNTER);
+
+    if shadow_total > 0.0 {
+        let shadow_ok = snapshot.counter_rate(SHADOW_OK_COUNTER);
+        let shadow_mismatch = snapshot.counter_rate(SHADOW_MISMATCH_COUNTER);
+
+        output!(
+            "Client Shadow: Success: {:.2} % Mismatch: {:.2} %",
+            100.0 * shadow_ok / shadow_total,
+            100.0 * shadow_mismatch / shadow_total,
+        );
+    }
+
+    account_stats(snapshot);
 }
 
-pub async fn metrics(config: Config) {
-    if config.metrics().is_none() {
-        return;
+/// Writes the latency histogram for each operation family, and the combined
+/// response latency, to their own files.
+fn latency_histograms(snapshot: &MetricsSnapshot, output: &str) {
+    let histograms = [
+        ("all", RESPONSE_LATENCY_HISTOGRAM),
+        ("get", GET_LATENCY_HISTOGRAM),
+        ("set", SET_LATENCY_HISTOGRAM),
+        ("delete", DELETE_LATENCY_HISTOGRAM),
+    ];
+
+    for (op, name) in histograms {
+        let Some(histogram) = snapshot.histograms.deltas.get(name) else {
+            continue;
+        };
+
+        let mut contents = String::new();
+
+        for bucket in histogram {
+            if bucket.count() > 0 {
+                contents.push_str(&format!(
+                    "{} {} {}\n",
+                    bucket.start(),
+                    bucket.end(),
+                    bucket.count()
+                ));
+            }
+        }
+
+        let path = output.replace("{op}", op);
+
+        if let Err(e) = std::fs::write(&path, contents) {
+            eprintln!("failed to write latency histogram: {path}\n{e}");
+        }
     }
-    let metrics_config = config.metrics().unwrap();
-    let output = metrics_config.output();
+}
 
-    let file = tempfile::NamedTempFile::new_in("./");
-    if file.is_err() {
-        return;
+/// Outputs sparklines of the p50 and p99 response latency over the most recent
+/// windows.
+fn sparklines(snapshot: &MetricsSnapshot, recent: &mut VecDeque<(u64, u64)>, windows: usize) {
+    let percentiles = snapshot.percentiles(RESPONSE_LATENCY_HISTOGRAM);
+
+    let p50 = percentiles.iter().find(|(_, p, _)| *p == 50.0);
+    let p99 = percentiles.iter().find(|(_, p, _)| *p == 99.0);
+
+    if let (Some(p50), Some(p99)) = (p50, p99) {
+        recent.push_back((p50.2, p99.2));
     }
-    let file = file.unwrap();
-    let mut writer = BufWriter::new(unsafe { File::from_raw_fd(file.as_raw_fd()) });
 
-    WAIT.fetch_add(1, Ordering::Relaxed);
+    while recent.len() > windows {
+        recent.pop_front();
+    }
 
-    // get an aligned start time
-    let start = tokio::time::Instant::now() - Duration::from_nanos(Utc::now().nanosecond() as u64)
-        + Duration::from_secs(1);
+    // both lines share a scale so they are comparable
+    let max = recent.iter().map(|(_, p99)| *p99).max().unwrap_or(0);
 
-    // get the stop time
-    let stop = start + config.general().duration();
+    let p50: Vec<u64> = recent.iter().map(|(p50, _)| *p50).collect();
+    let p99: Vec<u64> = recent.iter().map(|(_, p99)| *p99).collect();
 
-    let mut interval = tokio::time::interval_at(start, metrics_config.interval());
+    output!("Client Response Latency p50: {}", sparkline(&p50, max));
+    output!("Client Response Latency p99: {}", sparkline(&p99, max));
+}
 
-    let snapshotter = SnapshotterBuilder::new()
-        .metadata("source".to_string(), env!("CARGO_BIN_NAME").to_string())
-        .metadata("version".to_string(), env!("CARGO_PKG_VERSION").to_string())
-        .build();
+/// Records the p99 response latency of the current window and returns `true`
+/// if its variation over the most recent windows is within the configured
+/// tolerance.
+fn p99_stable(snapshot: &MetricsSnapshot, recent: &mut VecDeque<u64>, config: &Config) -> bool {
+    let windows = config.general().stability_windows();
+
+    let percentiles = snapshot.percentiles(RESPONSE_LATENCY_HISTOGRAM);
+
+    // a window without any responses resets the history
+    let Some(p99) = percentiles.iter().find(|(_, p, _)| *p == 99.0) else {
+        recent.clear();
+        return false;
+    };
+
+    recent.push_back(p99.2);
+
+    while recent.len() > windows {
+        recent.pop_front();
+    }
+
+    if recent.len() < windows {
+        return false;
+    }
+
+    let max = *recent.iter().max().unwrap() as f64;
+    let min = *recent.iter().min().unwrap() as f64;
+    let mean = recent.iter().sum::<u64>() as f64 / recent.len() as f64;
+
+    mean > 0.0 && (max - min) / mean <= config.general().stability_tolerance()
+}
+
+fn sparkline(values: &[u64], max: u64) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    values
+        .iter()
+        .map(|value| {
+            if max == 0 {
+                BARS[0]
+            } else {
+                BARS[((*value as f64 / max as f64) * (BARS.len() - 1) as f64).round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// Outputs the mean, standard deviation, min, and max of the key metrics
+/// across the trials of a multi-trial run.
+pub fn trials(summaries: &[MetricsSnapshot], unit: LatencyUnit) {
+    let mut throughput = Vec::with_capacity(summaries.len());
+    let mut success = Vec::with_capacity(summaries.len());
+    let mut latencies: Vec<(String, Vec<f64>)> = Vec::new();
+
+    for summary in summaries {
+        let ok = summary.counter_rate(RESPONSE_OK_COUNTER);
+        let total = ok
+            + summary.counter_rate(RESPONSE_EX_COUNTER)
+            + summary.counter_rate(RESPONSE_TIMEOUT_COUNTER);
+
+        throughput.push(total);
+        success.push(if total > 0.0 { 100.0 * ok / total } else { 0.0 });
+
+        for (label, _percentile, nanoseconds) in summary.percentiles(RESPONSE_LATENCY_HISTOGRAM) {
+            let value = unit.convert(nanoseconds);
+
+            match latencies.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, values)) => values.push(value),
+                None => latencies.push((label, vec![value])),
+            }
+        }
+    }
+
+    output!("-----");
+    output!("Summary ({} trials):", summaries.len());
+
+    trial_stats("Response Rate (/s)", &throughput);
+    trial_stats("Response Success (%)", &success);
+
+    for (label, values) in latencies {
+        trial_stats(
+            &format!("Response Latency {label} ({})", unit.label()),
+            &values,
+        );
+    }
+}
+
+fn trial_stats(name: &str, values: &[f64]) {
+    if values.is_empty() {
+        return;
+    }
+
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    output!(
+        "Trials: {name}: Mean: {:.2} Stddev: {:.2} Min: {:.2} Max: {:.2}",
+        mean,
+        variance.sqrt(),
+        min,
+        max
+    );
+}
+
+// the columns of the outcome table
+const OUTCOMES: [&str; 6] = ["ok", "hit", "miss", "ex", "timeout", "ratelimited"];
+
+/// Returns the count of each outcome for each operation family within the
+/// snapshot, using the existing per-operation counters. Outcomes which are not
+/// tracked for an operation are `None`.
+fn outcomes(snapshot: &MetricsSnapshot) -> Vec<(&'static str, [Option<u64>; 6])> {
+    let get_hit = snapshot.delta(&GET_KEY_HIT);
+    let get_miss = snapshot.delta(&GET_KEY_MISS);
+    let delete_deleted = snapshot.delta(&DELETE_DELETED);
+    let delete_not_found = snapshot.delta(&DELETE_NOT_FOUND);
+
+    vec![
+        (
+            "get",
+            [
+                Some(get_hit + get_miss),
+                Some(get_hit),
+                Some(get_miss),
+                Some(snapshot.delta(&GET_EX)),
+                Some(snapshot.delta(&GET_TIMEOUT)),
+                None,
+            ],
+        ),
+        (
+            "set",
+            [
+                Some(snapshot.delta(&SET_STORED) + snapshot.delta(&SET_NOT_STORED)),
+                None,
+                None,
+                Some(snapshot.delta(&SET_EX)),
+                Some(snapshot.delta(&SET_TIMEOUT)),
+                None,
+            ],
+        ),
+        (
+            "delete",
+            [
+                Some(snapshot.delta(&DELETE_OK) + delete_deleted + delete_not_found),
+                Some(delete_deleted),
+                Some(delete_not_found),
+                Some(snapshot.delta(&DELETE_EX)),
+                Some(snapshot.delta(&DELETE_TIMEOUT)),
+                None,
+            ],
+        ),
+        (
+            "all",
+            [
+                Some(snapshot.counter_delta(RESPONSE_OK_COUNTER)),
+                Some(snapshot.counter_delta(RESPONSE_HIT_COUNTER)),
+                Some(snapshot.counter_delta(RESPONSE_MISS_COUNTER)),
+                Some(snapshot.counter_delta(RESPONSE_EX_COUNTER)),
+                Some(snapshot.counter_delta(RESPONSE_TIMEOUT_COUNTER)),
+                Some(snapshot.counter_delta(RESPONSE_RATELIMITED_COUNTER)),
+            ],
+        ),
+    ]
+}
+
+/// Outputs a table of the outcomes for each operation family.
+fn outcome_table(snapshot: &MetricsSnapshot) {
+    let mut header = format!("{:<8}", "Outcomes");
+    for outcome in OUTCOMES {
+        header.push_str(&format!(" {outcome:>12}"));
+    }
+    output!("{header}");
+
+    for (op, counts) in outcomes(snapshot) {
+        let mut row = format!("{op:<8}");
+        for count in counts {
+            match count {
+                Some(count) => row.push_str(&format!(" {count:>12}")),
+                None => row.push_str(&format!(" {:>12}", "-")),
+            }
+        }
+        output!("{row}");
+    }
+}
+
+/// The outcomes at the end of the startup error grace period, if one is
+/// configured and has elapsed.
+static GRACE_OUTCOMES: Mutex<Option<Vec<(&'static str, [Option<u64>; 6])>>> = Mutex::new(None);
+
+/// Records the outcomes at the end of the startup error grace period so that
+/// the errors during it can be excluded from the error accounting.
+pub async fn startup_error_grace(config: Config) {
+    let Some(grace) = config.general().startup_error_grace() else {
+        return;
+    };
+
+    let mut snapshot = MetricsSnapshot::new();
+
+    sleep(grace).await;
+
+    snapshot.refresh();
+
+    let outcomes = outcomes(&snapshot);
+
+    // the ex, timeout, and ratelimited outcomes are errors
+    let errors: u64 = outcomes
+        .iter()
+        .filter(|(op, _)| *op == "all")
+        .flat_map(|(_, counts)| counts[3..].iter().flatten())
+        .sum();
+
+    output!("Startup Grace: ended, excluding {errors} errors from the error accounting");
+
+    *GRACE_OUTCOMES.lock().unwrap() = Some(outcomes);
+}
+
+/// Returns the outcomes used for the error accounting, summed across the
+/// summaries of each trial, with any errors during the startup error grace
+/// period removed.
+fn accounted_outcomes(summaries: &[MetricsSnapshot]) -> Vec<(&'static str, [Option<u64>; 6])> {
+    let mut totals = outcomes(&summaries[0]);
+
+    for summary in &summaries[1..] {
+        for ((_, counts), (_, summary_counts)) in totals.iter_mut().zip(outcomes(summary)) {
+            for (count, summary_count) in counts.iter_mut().zip(summary_counts) {
+                if let (Some(count), Some(summary_count)) = (count, summary_count) {
+                    *count += summary_count;
+                }
+            }
+        }
+    }
+
+    if let Some(grace) = GRACE_OUTCOMES.lock().unwrap().as_ref() {
+        for ((_, counts), (_, grace_counts)) in totals.iter_mut().zip(grace) {
+            // only the error outcomes are excluded
+            for (count, grace_count) in counts.iter_mut().zip(grace_counts).skip(3) {
+                if let (Some(count), Some(grace_count)) = (count, grace_count) {
+                    // the grace period may have been part of an attempt
+                    // which was retried and is not in the summaries
+                    *count = count.saturating_sub(*grace_count);
+                }
+            }
+        }
+    }
+
+    totals
+}
+
+/// Checks each of the configured outcome rate assertions against the outcomes
+/// of the trials and outputs the results. Returns `false` if any of the
+/// assertions were violated.
+pub fn assertions(config: &Config, summaries: &[MetricsSnapshot]) -> bool {
+    let assertions = config.general().assertions();
+
+    if assertions.is_empty() || summaries.is_empty() {
+        return true;
+    }
+
+    let outcomes = accounted_outcomes(summaries);
+
+    output!("-----");
+
+    let mut passed = true;
+
+    for assertion in assertions {
+        let (_, counts) = outcomes
+            .iter()
+            .find(|(op, _)| *op == assertion.operation())
+            .unwrap();
+
+        // the ok, ex, timeout, and ratelimited outcomes cover every response
+        let total: u64 = [0, 3, 4, 5].iter().filter_map(|i| counts[*i]).sum();
+
+        let index = OUTCOMES
+            .iter()
+            .position(|outcome| *outcome == assertion.outcome())
+            .unwrap();
+
+        let mut bounds = String::new();
+        if let Some(min) = assertion.min() {
+            bounds.push_str(&format!(" >= {min} %"));
+        }
+        if let Some(max) = assertion.max() {
+            bounds.push_str(&format!(" <= {max} %"));
+        }
+
+        let name = format!("{} {}{bounds}", assertion.operation(), assertion.outcome());
+
+        let Some(count) = counts[index] else {
+            output!("Assertion: {name}: FAIL (not tracked for this operation)");
+            passed = false;
+            continue;
+        };
+
+        if total == 0 {
+            output!("Assertion: {name}: FAIL (no responses)");
+            passed = false;
+            continue;
+        }
+
+        let rate = 100.0 * count as f64 / total as f64;
+
+        let pass = assertion.min().is_none_or(|min| rate >= min)
+            && assertion.max().is_none_or(|max| rate <= max);
+
+        output!(
+            "Assertion: {name}: {} (actual: {rate:.4} %)",
+            if pass { "PASS" } else { "FAIL" }
+        );
+
+        passed &= pass;
+    }
+
+    passed
+}
+
+/// Writes the machine readable summary to the file at `path`.
+fn summary_json(path: &str, client: bool, snapshot: &MetricsSnapshot, config: &Config) {
+    let mut summary = serde_json::Map::new();
+
+    if client {
+        // a rate over an empty window is not a number, which is left out
+        // rather than written as a null
+        let metrics: serde_json::Map<String, serde_json::Value> = summary_metrics(snapshot)
+            .into_iter()
+            .filter(|(_, value)| value.is_finite())
+            .map(|(metric, value)| (metric, value.into()))
+            .collect();
+
+        summary.insert("metrics".to_string(), metrics.into());
+
+        let latency = snapshot
+            .mean(RESPONSE_LATENCY_HISTOGRAM)
+            .zip(snapshot.stddev(RESPONSE_LATENCY_HISTOGRAM))
+            .filter(|(mean, stddev)| mean.is_finite() && stddev.is_finite());
+
+        if let Some((mean, stddev)) = latency {
+            let cov = if mean > 0.0 { stddev / mean } else { 0.0 };
+
+            summary.insert(
+                "latency".to_string(),
+                serde_json::json!({ "mean": mean, "stddev": stddev, "cov": cov }),
+            );
+        }
+
+        let operations: serde_json::Map<String, serde_json::Value> = outcomes(snapshot)
+            .into_iter()
+            .map(|(op, counts)| {
+                let counts: serde_json::Map<String, serde_json::Value> = OUTCOMES
+                    .iter()
+                    .zip(counts)
+                    .filter_map(|(outcome, count)| {
+                        count.map(|count| (outcome.to_string(), count.into()))
+                    })
+                    .collect();
+
+                (op.to_string(), counts.into())
+            })
+            .collect();
+
+        summary.insert("outcomes".to_string(), operations.into());
+    }
+
+    if !config.general().labels().is_empty() {
+        match serde_json::to_value(config.general().labels()) {
+            Ok(labels) => {
+                summary.insert("labels".to_string(), labels);
+            }
+            Err(e) => eprintln!("failed to serialize the labels for the summary: {e}"),
+        }
+    }
+
+    if config.general().summary_config() {
+        match serde_json::to_value(config) {
+            Ok(config) => {
+                summary.insert("config".to_string(), config);
+            }
+            Err(e) => eprintln!("failed to serialize the config for the summary: {e}"),
+        }
+    }
+
+    let contents = format!("{}\n", serde_json::Value::Object(summary));
+
+    if let Err(e) = std::fs::write(path, contents) {
+        eprintln!("failed to write summary: {path}\n{e}");
+    }
+}
+
+/// Returns the throughput (responses/s) and the response latency percentiles
+/// (in nanoseconds) which are written to the summary and compared against a
+/// baseline.
+fn summary_metrics(snapshot: &MetricsSnapshot) -> Vec<(String, f64)> {
+    let throughput = snapshot.counter_rate(RESPONSE_OK_COUNTER)
+        + snapshot.counter_rate(RESPONSE_EX_COUNTER)
+        + snapshot.counter_rate(RESPONSE_TIMEOUT_COUNTER);
+
+    let mut metrics = vec![("throughput".to_string(), throughput)];
+
+    for (label, _percentile, nanoseconds) in snapshot.percentiles(RESPONSE_LATENCY_HISTOGRAM) {
+        metrics.push((label, nanoseconds as f64));
+    }
+
+    metrics
+}
+
+/// Reads the metrics from a JSON summary written by a previous run.
+fn read_baseline(path: &str) -> Vec<(String, f64)> {
+    let contents = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("failed to read baseline: {path}\n{e}");
+        std::process::exit(1);
+    });
+
+    let summary: serde_json::Value = serde_json::from_str(&contents).unwrap_or_else(|e| {
+        eprintln!("failed to parse baseline: {path}\n{e}");
+        std::process::exit(1);
+    });
+
+    // the summary is written by `summary_json`, so the metrics are a flat
+    // object of numbers
+    let Some(metrics) = summary.get("metrics").and_then(|m| m.as_object()) else {
+        eprintln!("baseline does not contain any metrics: {path}");
+        std::process::exit(1);
+    };
+
+    metrics
+        .iter()
+        .filter_map(|(metric, value)| Some((metric.clone(), value.as_f64()?)))
+        .collect()
+}
+
+/// Compares the throughput and latency of the run, averaged across the
+/// trials, against the baseline and outputs the differences. Returns `false`
+/// if any metric regressed by more than its configured threshold.
+pub fn baseline(config: &Config, summaries: &[MetricsSnapshot]) -> bool {
+    let Some(path) = config.general().baseline() else {
+        return true;
+    };
+
+    if summaries.is_empty() {
+        return true;
+    }
+
+    let baseline = read_baseline(path);
+
+    let mut current: Vec<(String, f64)> = Vec::new();
+
+    for summary in summaries {
+        for (metric, value) in summary_metrics(summary) {
+            match current.iter_mut().find(|(m, _)| *m == metric) {
+                Some((_, total)) => *total += value,
+                None => current.push((metric, value)),
+            }
+        }
+    }
+
+    output!("-----");
+    output!("Baseline: {path}");
+
+    let mut passed = true;
+
+    for (metric, total) in current {
+        let value = total / summaries.len() as f64;
+
+        let threshold = config.general().baseline_thresholds().get(&metric);
+
+        // a metric without a usable baseline can't be compared, which fails
+        // the comparison if it has a threshold
+        let previous = match baseline.iter().find(|(m, _)| *m == metric) {
+            Some((_, previous)) if *previous != 0.0 => previous,
+            Some(_) | None => {
+                if threshold.is_some() {
+                    output!("Baseline: {metric}: FAIL (missing or zero in the baseline)");
+                    passed = false;
+                } else {
+                    output!("Baseline: {metric}: missing or zero in the baseline");
+                }
+                continue;
+            }
+        };
+
+        let change = 100.0 * (value - previous) / previous;
+
+        // lower throughput and higher latency are regressions
+        let regression = if metric == "throughput" {
+            -change
+        } else {
+            change
+        };
+
+        let (previous, value) = if metric == "throughput" {
+            (format!("{previous:.2}"), format!("{value:.2}"))
+        } else {
+            let unit = config.general().latency_unit();
+            (
+                format!("{} {}", unit.format(*previous as u64), unit.label()),
+                format!("{} {}", unit.format(value as u64), unit.label()),
+            )
+        };
+
+        match threshold {
+            Some(threshold) => {
+                let pass = regression <= *threshold;
+                passed &= pass;
+
+                output!(
+                    "Baseline: {metric}: {previous} -> {value} ({change:+.2} %): {}",
+                    if pass { "PASS" } else { "FAIL" }
+                );
+            }
+            None => output!("Baseline: {metric}: {previous} -> {value} ({change:+.2} %)"),
+        }
+    }
+
+    passed
+}
+
+/// Outputs the distributions of the generated key and value lengths, which
+/// makes it easy to spot a misconfigured length or to confirm the shape of a
+/// value length distribution.
+fn lengths(snapshot: &MetricsSnapshot) {
+    let histograms = [
+        ("Key", KEY_BYTES_HISTOGRAM),
+        ("Value", VALUE_BYTES_HISTOGRAM),
+    ];
+
+    for (name, histogram) in histograms {
+        let percentiles = snapshot.percentiles(histogram);
+
+        if percentiles.is_empty() {
+            continue;
+        }
+
+        let mut lengths = format!("Workload {name} Length (bytes):");
+
+        for (label, _percentile, bytes) in percentiles {
+            lengths.push_str(&format!(" {label}: {bytes}"));
+        }
+
+        output!("{lengths}");
+    }
+}
+
+/// Outputs the latency of the background load and of the foreground requests
+/// issued while it was on and off.
+fn background_latencies(snapshot: &MetricsSnapshot, unit: LatencyUnit) {
+    let histograms = [
+        ("Background", BACKGROUND_LATENCY_HISTOGRAM),
+        (
+            "Foreground (background on)",
+            FOREGROUND_LATENCY_BACKGROUND_ON_HISTOGRAM,
+        ),
+        (
+            "Foreground (background off)",
+            FOREGROUND_LATENCY_BACKGROUND_OFF_HISTOGRAM,
+        ),
+    ];
+
+    for (name, histogram) in histograms {
+        let percentiles = snapshot.percentiles(histogram);
+
+        if percentiles.is_empty() {
+            continue;
+        }
+
+        let mut latencies = format!("{name} Latency ({}):", unit.label());
+
+        for (label, _percentile, nanoseconds) in percentiles {
+            latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)));
+        }
+
+        output!("{latencies}");
+    }
+}
+
+/// Outputs the latency of the commands which are isolated alongside the latency
+/// of all other requests, so that rare and expensive requests are not hidden in
+/// the overall percentiles.
+fn isolated_latencies(snapshot: &MetricsSnapshot, unit: LatencyUnit) {
+    let histograms = [
+        ("Isolated", ISOLATED_LATENCY_HISTOGRAM),
+        ("Non-isolated", NON_ISOLATED_LATENCY_HISTOGRAM),
+    ];
+
+    for (name, histogram) in histograms {
+        let percentiles = snapshot.percentiles(histogram);
+
+        if percentiles.is_empty() {
+            continue;
+        }
+
+        let mut latencies = format!("{name} Latency ({}):", unit.label());
+
+        for (label, _percentile, nanoseconds) in percentiles {
+            latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)));
+        }
+
+        output!("{latencies}");
+    }
+}
+
+/// Outputs the latency of the first request of each burst of the duty cycle.
+fn burst_latencies(snapshot: &MetricsSnapshot, unit: LatencyUnit) {
+    let percentiles = snapshot.percentiles(BURST_START_LATENCY_HISTOGRAM);
+
+    if percentiles.is_empty() {
+        return;
+    }
+
+    let mut latencies = format!("Burst Start Latency ({}):", unit.label());
+
+    for (label, _percentile, nanoseconds) in percentiles {
+        latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)));
+    }
+
+    output!(
+        "{latencies} Bursts: {}",
+        snapshot.counter_delta(BURST_COUNTER)
+    );
+}
+
+/// Outputs the backend-reported processing times and how the mean response
+/// latency splits between the backend and the rest of the request path.
+fn server_latencies(snapshot: &MetricsSnapshot, unit: LatencyUnit) {
+    let percentiles = snapshot.percentiles(SERVER_LATENCY_HISTOGRAM);
+
+    if percentiles.is_empty() {
+        return;
+    }
+
+    let mut latencies = format!("Server Latency ({}):", unit.label());
+
+    for (label, _percentile, nanoseconds) in percentiles {
+        latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)));
+    }
+
+    output!("{latencies}");
+
+    if let (Some(client), Some(server)) = (
+        snapshot.mean(RESPONSE_LATENCY_HISTOGRAM),
+        snapshot.mean(SERVER_LATENCY_HISTOGRAM),
+    ) {
+        output!(
+            "Latency Split ({}): Client Mean: {:.2} Server Mean: {:.2} Other: {:.2}",
+            unit.label(),
+            unit.convert(client as u64),
+            unit.convert(server as u64),
+            unit.convert((client - server).max(0.0) as u64),
+        );
+    }
+
+    let missing = snapshot.counter_delta(SERVER_LATENCY_MISSING_COUNTER);
+
+    if missing > 0 {
+        output!("Server Latency: {missing} responses without a valid header");
+    }
+}
+
+/// Outputs the latency of the requests in each value size bucket since the
+/// previous summary.
+fn size_latencies(unit: LatencyUnit) {
+    let Some(buckets) = SIZE_BUCKETS.get() else {
+        return;
+    };
+
+    let percentiles: Vec<f64> = PERCENTILES
+        .iter()
+        .map(|(_, percentile)| *percentile)
+        .collect();
+
+    for (label, histogram) in buckets.deltas() {
+        let Some(Ok(Some(values))) = histogram.map(|h| h.percentiles(&percentiles)) else {
+            continue;
+        };
+
+        let mut latencies = format!("Value Size {label} Latency ({}):", unit.label());
+
+        for ((name, _), (_, bucket)) in PERCENTILES.iter().zip(values.iter()) {
+            latencies.push_str(&format!(" {name}: {}", unit.format(bucket.end())));
+        }
+
+        output!("{latencies}");
+    }
+}
+
+/// Outputs the hits and misses of the client-side cache, where each hit is a
+/// get which did not need to be sent to the backend.
+fn client_cache_stats(snapshot: &MetricsSnapshot) {
+    let hit = snapshot.counter_delta(CLIENT_CACHE_HIT_COUNTER);
+    let miss = snapshot.counter_delta(CLIENT_CACHE_MISS_COUNTER);
+    let total = hit + miss;
+
+    let ratio = if total == 0 {
+        0.0
+    } else {
+        100.0 * hit as f64 / total as f64
+    };
+
+    output!("Client Cache: Hit: {hit} Miss: {miss} Hit Rate (%): {ratio:.2}");
+}
+
+fn write_classification(snapshot: &MetricsSnapshot) {
+    let created = snapshot.counter_delta(SET_CREATED_COUNTER);
+    let overwritten = snapshot.counter_delta(SET_OVERWRITTEN_COUNTER);
+    let total = created + overwritten;
+
+    let ratio = if total == 0 {
+        0.0
+    } else {
+        100.0 * overwritten as f64 / total as f64
+    };
+
+    output!("Sets: Created: {created} Overwritten: {overwritten} Overwrite Rate (%): {ratio:.2}");
+}
+
+// the number of connections listed in the connection table
+const CONNECTION_ROWS: usize = 10;
+
+/// Outputs the connections with the highest error rates along with the
+/// overall error rate, so that outlier connections stand out.
+fn connection_table() {
+    let mut connections = crate::clients::connection_stats();
+
+    if connections.is_empty() {
+        return;
+    }
+
+    let rate = |requests: u64, errors: u64| {
+        if requests == 0 {
+            0.0
+        } else {
+            100.0 * errors as f64 / requests as f64
+        }
+    };
+
+    let requests: u64 = connections.iter().map(|c| c.3).sum();
+    let errors: u64 = connections.iter().map(|c| c.4).sum();
+
+    output!(
+        "Connections: {} Requests: {requests} Errors: {errors} Error Rate (%): {:.2}",
+        connections.len(),
+        rate(requests, errors)
+    );
+
+    connections.sort_by(|a, b| rate(b.3, b.4).total_cmp(&rate(a.3, a.4)));
+
+    output!(
+        "{:<32} {:>6} {:>10} {:>12} {:>12} {:>10}",
+        "Endpoint",
+        "Id",
+        "Connects",
+        "Requests",
+        "Errors",
+        "Error %"
+    );
+
+    for (endpoint, id, connects, requests, errors) in connections.into_iter().take(CONNECTION_ROWS)
+    {
+        output!(
+            "{endpoint:<32} {id:>6} {connects:>10} {requests:>12} {errors:>12} {:>10.2}",
+            rate(requests, errors)
+        );
+    }
+}
+
+/// Outputs the response latency and error rate of each endpoint side by side,
+/// ranked from best to worst by the configured metric. Endpoints without any
+/// successful responses are ranked last.
+fn endpoint_comparison(unit: LatencyUnit, rank: EndpointRank) {
+    let endpoints = crate::clients::endpoint_stats();
+
+    if endpoints.is_empty() {
+        return;
+    }
+
+    let mut rows: Vec<(String, Option<u64>, Option<u64>, f64)> = endpoints
+        .into_iter()
+        .map(|(endpoint, requests, errors, histogram)| {
+            let percentiles =
+                histogram.and_then(|histogram| histogram.percentiles(&[50.0, 99.0]).ok().flatten());
+
+            let (p50, p99) = match percentiles.as_deref() {
+                Some([(_, p50), (_, p99)]) => (Some(p50.end()), Some(p99.end())),
+                _ => (None, None),
+            };
+
+            let error_rate = if requests == 0 {
+                0.0
+            } else {
+                100.0 * errors as f64 / requests as f64
+            };
+
+            (endpoint, p50, p99, error_rate)
+        })
+        .collect();
+
+    match rank {
+        EndpointRank::P50 => rows.sort_by_key(|row| row.1.unwrap_or(u64::MAX)),
+        EndpointRank::P99 => rows.sort_by_key(|row| row.2.unwrap_or(u64::MAX)),
+        EndpointRank::ErrorRate => rows.sort_by(|a, b| a.3.total_cmp(&b.3)),
+    }
+
+    let metric = match rank {
+        EndpointRank::P50 => "p50",
+        EndpointRank::P99 => "p99",
+        EndpointRank::ErrorRate => "error rate",
+    };
+
+    output!("Endpoint Comparison (ranked by {metric}):");
+
+    output!(
+        "{:>4} {:<32} {:>12} {:>12} {:>10}",
+        "Rank",
+        "Endpoint",
+        format!("p50 ({})", unit.label()),
+        format!("p99 ({})", unit.label()),
+        "Error %"
+    );
+
+    let format = |value: Option<u64>| value.map(|v| unit.format(v)).unwrap_or("-".to_string());
+
+    for (index, (endpoint, p50, p99, error_rate)) in rows.into_iter().enumerate() {
+        output!(
+            "{:>4} {endpoint:<32} {:>12} {:>12} {error_rate:>10.2}",
+            index + 1,
+            format(p50),
+            format(p99),
+        );
+    }
+}
+
+/// Outputs the achieved rate and response latency for a step of a stepped rate
+/// schedule. Taken together, the steps form a throughput-latency curve.
+pub fn rate_step(step: usize, target: u64, snapshot: &MetricsSnapshot, unit: LatencyUnit) {
+    let ok = snapshot.counter_rate(RESPONSE_OK_COUNTER);
+    let total = ok
+        + snapshot.counter_rate(RESPONSE_EX_COUNTER)
+        + snapshot.counter_rate(RESPONSE_TIMEOUT_COUNTER);
+    let success = if total > 0.0 { 100.0 * ok / total } else { 0.0 };
+
+    let mut latencies = String::new();
+
+    for (label, _percentile, nanoseconds) in snapshot.percentiles(RESPONSE_LATENCY_HISTOGRAM) {
+        if matches!(label.as_str(), "p50" | "p99" | "p999") {
+            latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)));
+        }
+    }
+
+    output!(
+        "Step {step}: Target (/s): {target} Achieved (/s): {total:.2} Success: {success:.2} % Latency ({}):{latencies}",
+        unit.label(),
+    );
+}
+
+/// Outputs the mean and standard deviation of the response latency. The
+/// coefficient of variation (stddev / mean) indicates how consistent the
+/// latency was, which the percentiles alone may not reveal.
+fn latency_jitter(snapshot: &MetricsSnapshot, unit: LatencyUnit) {
+    let (Some(mean), Some(stddev)) = (
+        snapshot.mean(RESPONSE_LATENCY_HISTOGRAM),
+        snapshot.stddev(RESPONSE_LATENCY_HISTOGRAM),
+    ) else {
+        return;
+    };
+
+    output!(
+        "Client Response Latency ({}): Mean: {:.2} Stddev: {:.2} CoV: {:.3}",
+        unit.label(),
+        unit.convert(mean as u64),
+        unit.convert(stddev as u64),
+        if mean > 0.0 { stddev / mean } else { 0.0 },
+    );
+}
+
+// the divergence (in percentage points) between the achieved and configured
+// share of an operation which is warned about
+const MIX_DIVERGENCE: f64 = 5.0;
+
+/// Outputs the achieved share of successful gets, sets, and deletes next to
+/// the share configured by the keyspace and command weights. A large
+/// divergence means that one operation was failing or throttled more than the
+/// others.
+fn operation_mix(snapshot: &MetricsSnapshot, config: &Config) {
+    let outcomes = outcomes(snapshot);
+
+    // the ok outcome of each operation family
+    let achieved = ["get", "set", "delete"].map(|family| {
+        outcomes
+            .iter()
+            .find(|(op, _)| *op == family)
+            .and_then(|(_, counts)| counts[0])
+            .unwrap_or(0) as f64
+    });
+
+    let mut target = [0.0; 3];
+
+    // background keyspaces are not part of the weighted mix
+    for keyspace in config
+        .workload()
+        .keyspaces()
+        .iter()
+        .filter(|keyspace| keyspace.background_rate().is_none())
+    {
+        let total: usize = keyspace.commands().iter().map(|c| c.weight()).sum();
+
+        if total == 0 {
+            continue;
+        }
+
+        for command in keyspace.commands() {
+            let family = match command.verb() {
+                Verb::Get => 0,
+                Verb::Set | Verb::Add | Verb::Replace => 1,
+                Verb::Delete => 2,
+                _ => continue,
+            };
+
+            target[family] += (keyspace.weight() * command.weight()) as f64 / total as f64;
+        }
+    }
+
+    let achieved_total: f64 = achieved.iter().sum();
+    let target_total: f64 = target.iter().sum();
+
+    if achieved_total == 0.0 || target_total == 0.0 {
+        return;
+    }
+
+    let mut mix = "Client Operation Mix (%):".to_string();
+    let mut divergence: f64 = 0.0;
+
+    for (name, (achieved, target)) in ["Get", "Set", "Delete"]
+        .iter()
+        .zip(achieved.iter().zip(target.iter()))
+    {
+        let achieved = 100.0 * achieved / achieved_total;
+        let target = 100.0 * target / target_total;
+
+        mix.push_str(&format!(" {name}: {achieved:.2} (target {target:.2})"));
+        divergence = divergence.max((achieved - target).abs());
+    }
+
+    output!("{mix}");
+
+    if divergence > MIX_DIVERGENCE {
+        output!(
+            "WARNING: the operation mix diverged from the configured mix by {divergence:.2} percentage points"
+        );
+    }
+}
+
+/// Warns if the achieved rate falls short of the target rate and the number of
+/// client tasks at the measured mean latency could not have achieved the
+/// target. In that case requests were delayed before being sent and the
+/// latency percentiles omit that delay.
+fn coordinated_omission(snapshot: &MetricsSnapshot, config: &Config) {
+    // a ramp-down lowers the current ratelimit, so the target is the rate
+    // before it began
+    let target = if RAMP_DOWN.load(Ordering::Relaxed) {
+        RAMP_DOWN_FROM.load(Ordering::Relaxed) as i64
+    } else {
+        RATELIMIT_CURR.value()
+    };
+
+    if target <= 0 {
+        return;
+    }
+
+    let Some(mean_ns) = snapshot.mean(RESPONSE_LATENCY_HISTOGRAM) else {
+        return;
+    };
+
+    let tasks = crate::clients::client_tasks(config);
+
+    if tasks == 0 || mean_ns <= 0.0 {
+        return;
+    }
+
+    let target = target as f64;
+    let achieved = snapshot.counter_rate(RESPONSE_OK_COUNTER)
+        + snapshot.counter_rate(RESPONSE_EX_COUNTER)
+        + snapshot.counter_rate(RESPONSE_TIMEOUT_COUNTER);
+
+    // by little's law, each task can complete at most one request per mean
+    // latency
+    let capacity = tasks as f64 * 1_000_000_000.0 / mean_ns;
+
+    if achieved < 0.9 * target && capacity < target {
+        output!(
+            "WARNING: likely coordinated omission: target rate: {:.2}/s achieved rate: {:.2}/s estimated capacity: {:.2}/s ({} tasks at a mean latency of {:.2} us). Latencies do not include time spent waiting to send.",
+            target,
+            achieved,
+            capacity,
+            tasks,
+            mean_ns / 1000.0,
+        );
+    }
+}
+
+/// Outputs the time at which the hit rate of a window first reached the given
+/// fraction of the steady-state hit rate, taken over the last quarter of the
+/// windows.
+fn time_to_warm(windows: &[(Duration, u64, u64)], fraction: f64) {
+    let hit_rate = |hits: u64, misses: u64| {
+        if hits + misses == 0 {
+            None
+        } else {
+            Some(hits as f64 / (hits + misses) as f64)
+        }
+    };
+
+    let steady_windows = std::cmp::max(1, windows.len() / 4);
+
+    let (hits, misses) = windows
+        .iter()
+        .rev()
+        .take(steady_windows)
+        .fold((0, 0), |(hits, misses), window| {
+            (hits + window.1, misses + window.2)
+        });
+
+    let Some(steady) = hit_rate(hits, misses) else {
+        return;
+    };
+
+    let warm = windows.iter().find(|(_, hits, misses)| {
+        hit_rate(*hits, *misses).is_some_and(|rate| rate >= fraction * steady)
+    });
+
+    match warm {
+        Some((elapsed, _, _)) => output!(
+            "Time to Warm: {:.2} s (hit rate reached {:.2} % of the steady-state {:.2} %)",
+            elapsed.as_secs_f64(),
+            100.0 * fraction,
+            100.0 * steady,
+        ),
+        None => output!(
+            "Time to Warm: not reached (steady-state hit rate: {:.2} %)",
+            100.0 * steady
+        ),
+    }
+}
+
+/// Outputs per-account response rates when multiple accounts are in use
+fn account_stats(snapshot: &MetricsSnapshot) {
+    let accounts = ACCOUNTS.lock().unwrap();
+
+    if accounts.len() < 2 {
+        return;
+    }
+
+    for account in accounts.iter() {
+        let rate = |kind| snapshot.counter_rate(&account.counter_name(kind));
+
+        output!(
+            "Account {} Response Rate (/s): Ok: {:.2} Error: {:.2} Timeout: {:.2} Ratelimited: {:.2}",
+            account.name(),
+            rate("ok"),
+            rate("ex"),
+            rate("timeout"),
+            rate("ratelimited"),
+        );
+    }
+}
+
+/// Output pubsub metrics and return the number of successful publish operations
+fn pubsub_stats(snapshot: &mut MetricsSnapshot, unit: LatencyUnit) {
+    // publisher stats
+    let pubsub_tx_ex = snapshot.counter_rate(PUBSUB_PUBLISH_EX_COUNTER);
+    let pubsub_tx_ok = snapshot.counter_rate(PUBSUB_PUBLISH_OK_COUNTER);
+    let pubsub_tx_timeout = snapshot.counter_rate(PUBSUB_PUBLISH_TIMEOUT_COUNTER);
+    let pubsub_tx_total = snapshot.counter_rate(PUBSUB_PUBLISH_COUNTER);
+
+    let pubsub_publish_latency = snapshot.percentiles(PUBSUB_PUBLISH_LATENCY_HISTOGRAM);
+
+    // subscriber stats
+    let pubsub_rx_ok = snapshot.counter_rate(PUBSUB_RECEIVE_OK_COUNTER);
+    let pubsub_rx_ex = snapshot.counter_rate(PUBSUB_RECEIVE_EX_COUNTER);
+    let pubsub_rx_corrupt = snapshot.counter_rate(PUBSUB_RECEIVE_CORRUPT_COUNTER);
+    let pubsub_rx_invalid = snapshot.counter_rate(PUBSUB_RECEIVE_INVALID_COUNTER);
+    let pubsub_rx_total = snapshot.counter_rate(PUBSUB_RECEIVE_COUNTER);
+
+    // end-to-end stats
+    let pubsub_latency = snapshot.percentiles(PUBSUB_LATENCY_HISTOGRAM);
+    output!("Ratelimit: Current: {}", RATELIMIT_CURR.value());
+    output!("Publishers: Current: {}", PUBSUB_PUBLISHER_CURR.value());
+
+    let pubsub_tx_sr = 100.0 * pubsub_tx_ok / pubsub_tx_total;
+    let pubsub_tx_to = 100.0 * pubsub_tx_timeout / pubsub_tx_total;
+    output!(
+        "Publisher Publish: Success: {:.2} % Timeout: {:.2} %",
+        pubsub_tx_sr,
+        pubsub_tx_to
+    );
+
+    output!(
+        "Publisher Publish Rate (/s): Ok: {:.2} Error: {:.2} Timeout: {:.2}",
+        pubsub_tx_ok,
+        pubsub_tx_ex,
+        pubsub_tx_timeout,
+    );
+
+    output!("Subscribers: Current: {}", PUBSUB_SUBSCRIBER_CURR.value(),);
+
+    let pubsub_rx_sr = 100.0 * pubsub_rx_ok / pubsub_rx_total;
+    let pubsub_rx_cr = 100.0 * pubsub_rx_corrupt / pubsub_rx_total;
+    output!(
+        "Subscriber Receive: Success: {:.2} % Corrupted: {:.2} %",
+        pubsub_rx_sr,
+        pubsub_rx_cr
+    );
+
+    output!(
+        "Subscriber Receive Rate (/s): Ok: {:.2} Error: {:.2} Corrupt: {:.2} Invalid: {:.2}",
+        pubsub_rx_ok,
+        pubsub_rx_ex,
+        pubsub_rx_corrupt,
+        pubsub_rx_invalid,
+    );
+
+    let mut latencies = format!("Pubsub Publish Latency ({}):", unit.label());
+
+    for (label, _percentile, nanoseconds) in pubsub_publish_latency {
+        latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)))
+    }
+
+    output!("{latencies}");
+
+    let mut latencies = format!("Pubsub End-to-End Latency ({}):", unit.label());
+
+    for (label, _percentile, nanoseconds) in pubsub_latency {
+        latencies.push_str(&format!(" {label}: {}", unit.format(nanoseconds)))
+    }
+
+    output!("{latencies}");
+}
+
+/// Outputs the number of requests sent to each shard for any sharded keyspaces
+pub fn shards(components: &[Component]) {
+    for (id, component) in components.iter().enumerate() {
+        if let Component::Keyspace(keyspace) = component {
+            if let Some(requests) = keyspace.shard_requests() {
+                let mut line = format!("Keyspace {id} Shard Requests:");
+                for (shard, count) in requests.iter().enumerate() {
+                    line.push_str(&format!(" {shard}: {count}"));
+                }
+                output!("{line}");
+            }
+
+            if let Some(requests) = keyspace.key_class_requests() {
+                let mut line = format!("Keyspace {id} Key Class Requests:");
+                for (prefix, count) in requests {
+                    line.push_str(&format!(" {prefix}: {count}"));
+                }
+                output!("{line}");
+            }
+
+            if let Some(values) = keyspace.compression_class_values() {
+                let mut line = format!("Keyspace {id} Compression Class Values:");
+                for (name, count) in values {
+                    line.push_str(&format!(" {name}: {count}"));
+                }
+                output!("{line}");
+            }
+        }
+    }
+}
+
+// keeps each statsd packet within a typical ethernet mtu
+const STATSD_PACKET_SIZE: usize = 1432;
+
+/// Sends the counter deltas, gauges, and latency percentiles to the StatsD
+/// address at each reporting interval. Send failures are ignored.
+pub async fn statsd(config: Config) {
+    let Some(address) = config.general().statsd_address() else {
+        return;
+    };
+
+    let socket = match tokio::net::UdpSocket::bind("0.0.0.0:0").await {
+        Ok(socket) => socket,
+        Err(e) => {
+            error!("failed to bind statsd socket: {e}");
+            return;
+        }
+    };
+
+    if let Err(e) = socket.connect(address).await {
+        error!("failed to resolve statsd address: {address}: {e}");
+        return;
+    }
+
+    let prefix = config.general().statsd_prefix();
+
+    // datadog extension for tagging each metric
+    let tags = if config.general().labels().is_empty() {
+        String::new()
+    } else {
+        let tags: Vec<String> = config
+            .general()
+            .labels()
+            .iter()
+            .map(|(key, value)| format!("{key}:{value}"))
+            .collect();
+
+        format!("|#{}", tags.join(","))
+    };
+
+    let mut snapshot = MetricsSnapshot::default();
+    snapshot.update();
+
+    let mut interval = tokio::time::interval(config.general().interval());
+    interval.tick().await;
+
+    while RUNNING.load(Ordering::Relaxed) {
+        // use a timeout here so we always check RUNNING at least once a second
+        if timeout(Duration::from_secs(1), interval.tick())
+            .await
+            .is_err()
+        {
+            continue;
+        }
+
+        snapshot.update();
+
+        let mut lines = Vec::new();
+
+        for metric in metriken::metrics().iter() {
+            let Some(any) = metric.as_any() else {
+                continue;
+            };
+
+            let name = metric.name().replace('/', ".");
+
+            if any.downcast_ref::<metriken::Counter>().is_some() {
+                let delta = snapshot.counter_delta(metric.name());
+                lines.push(format!("{prefix}.{name}:{delta}|c{tags}"));
+            } else if let Some(gauge) = any.downcast_ref::<metriken::Gauge>() {
+                lines.push(format!("{prefix}.{name}:{}|g{tags}", gauge.value()));
+            }
+        }
+
+        for name in snapshot.histograms.deltas.keys() {
+            for (label, _percentile, value) in snapshot.percentiles(name) {
+                let name = name.replace('/', ".");
+                lines.push(format!("{prefix}.{name}.{label}:{value}|g{tags}"));
+            }
+        }
+
+        // batch the lines into as few packets as possible
+        let mut packet = String::with_capacity(STATSD_PACKET_SIZE);
+
+        for line in lines {
+            if !packet.is_empty() && packet.len() + 1 + line.len() > STATSD_PACKET_SIZE {
+                let _ = socket.send(packet.as_bytes()).await;
+                packet.clear();
+            }
+
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+
+            packet.push_str(&line);
+        }
+
+        if !packet.is_empty() {
+            let _ = socket.send(packet.as_bytes()).await;
+        }
+    }
+}
+
+pub async fn metrics(config: Config) {
+    if config.metrics().is_none() {
+        return;
+    }
+    let metrics_config = config.metrics().unwrap();
+    let output = metrics_config.output();
+
+    let file = tempfile::NamedTempFile::new_in("./");
+    if file.is_err() {
+        return;
+    }
+    let file = file.unwrap();
+    let mut writer = BufWriter::new(unsafe { File::from_raw_fd(file.as_raw_fd()) });
+
+    WAIT.fetch_add(1, Ordering::Relaxed);
+
+    // get an aligned start time
+    let start = tokio::time::Instant::now() - Duration::from_nanos(Utc::now().nanosecond() as u64)
+        + Duration::from_secs(1);
+
+    // get the stop time
+    let stop = start + config.general().duration();
+
+    let mut interval = tokio::time::interval_at(start, metrics_config.interval());
+
+    let mut snapshotter = SnapshotterBuilder::new()
+        .metadata("source".to_string(), env!("CARGO_BIN_NAME").to_string())
+        .metadata("version".to_string(), env!("CARGO_PKG_VERSION").to_string());
+
+    for (key, value) in config.general().labels() {
+        snapshotter = snapshotter.metadata(key.clone(), value.clone());
+    }
+
+    let snapshotter = snapshotter.build();
 
     while RUNNING.load(Ordering::Relaxed) && Instant::now() + config.general().interval() <= stop {
         // use a timeout here so we always check RUNNING at least once a second
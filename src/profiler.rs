@@ -0,0 +1,309 @@
+//! A minimal sampling profiler for the client itself. A `SIGPROF` timer
+//! interrupts the process at the configured frequency (in CPU time) and the
+//! signal handler records the raw instruction pointers of the interrupted
+//! stack into a pre-allocated buffer. Symbols are only resolved after the
+//! profiler is stopped, when the samples are written out as folded stacks.
+//!
+//! The stack is walked by following frame pointers from the interrupted
+//! context, since unwinding with debug info is not async-signal-safe. The
+//! profiler refuses to start unless the build was made with
+//! `RUSTFLAGS="-C force-frame-pointers=yes"`. The walk is bounded by the stack
+//! of the interrupted thread, which is only known for threads which called
+//! `register_thread` as they started, so only the interrupted instruction is
+//! recorded for any other thread. Only Linux on x86_64 and aarch64 is
+//! supported, elsewhere every sample is empty.
+
+use crate::*;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::io::{BufWriter, Write};
+use std::sync::OnceLock;
+
+// the maximum number of frames recorded for each sample
+const MAX_DEPTH: usize = 64;
+
+// the maximum number of samples which will be recorded, any further samples
+// are dropped
+const MAX_SAMPLES: usize = 1 << 15;
+
+thread_local! {
+    // the lowest and highest addresses of this thread's stack, or zero if the
+    // thread was not registered. This is read in the signal handler, so it
+    // must not need lazy initialization.
+    static STACK: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+struct Samples {
+    frames: Box<[AtomicUsize]>,
+    depths: Box<[AtomicUsize]>,
+    next: AtomicUsize,
+}
+
+static SAMPLES: OnceLock<Samples> = OnceLock::new();
+
+pub struct Profiler {
+    path: String,
+}
+
+/// Starts the profiler. Samples will be written to the file at `path` when the
+/// returned profiler is stopped.
+pub fn start(path: String, frequency: u32) -> Profiler {
+    if !cfg!(frame_pointers) {
+        eprintln!("profiling requires building with `RUSTFLAGS=\"-C force-frame-pointers=yes\"`");
+        std::process::exit(1);
+    }
+
+    register_thread();
+
+    SAMPLES.get_or_init(|| Samples {
+        frames: (0..(MAX_SAMPLES * MAX_DEPTH))
+            .map(|_| AtomicUsize::new(0))
+            .collect(),
+        depths: (0..MAX_SAMPLES).map(|_| AtomicUsize::new(0)).collect(),
+        next: AtomicUsize::new(0),
+    });
+
+    let interval_us = 1_000_000 / frequency as i64;
+
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+        libc::sigemptyset(&mut action.sa_mask);
+
+        if libc::sigaction(libc::SIGPROF, &action, std::ptr::null_mut()) != 0 {
+            eprintln!("failed to install the profiler signal handler");
+            std::process::exit(1);
+        }
+
+        set_timer(interval_us);
+    }
+
+    Profiler { path }
+}
+
+impl Profiler {
+    /// Stops sampling and writes the folded stacks to the output file.
+    pub fn stop(self) {
+        unsafe {
+            set_timer(0);
+            libc::signal(libc::SIGPROF, libc::SIG_IGN);
+        }
+
+        let samples = SAMPLES.get().unwrap();
+        let count = std::cmp::min(samples.next.load(Ordering::Relaxed), MAX_SAMPLES);
+
+        // resolve each unique instruction pointer only once
+        let mut symbols: HashMap<usize, Vec<String>> = HashMap::new();
+        let mut stacks: HashMap<String, u64> = HashMap::new();
+
+        for sample in 0..count {
+            let depth = samples.depths[sample].load(Ordering::Relaxed);
+
+            let mut frames: Vec<String> = Vec::with_capacity(depth);
+
+            // frames are recorded innermost first, but folded stacks are
+            // listed from the outermost frame
+            for frame in (0..depth).rev() {
+                let ip = samples.frames[sample * MAX_DEPTH + frame].load(Ordering::Relaxed);
+
+                let names = symbols.entry(ip).or_insert_with(|| resolve(ip));
+
+                // inlined functions resolve to multiple symbols, innermost
+                // first
+                frames.extend(names.iter().rev().cloned());
+            }
+
+            if frames.is_empty() {
+                continue;
+            }
+
+            *stacks.entry(frames.join(";")).or_insert(0) += 1;
+        }
+
+        let file = match std::fs::File::create(&self.path) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("failed to create profile file: {}\n{e}", self.path);
+                return;
+            }
+        };
+
+        let mut writer = BufWriter::new(file);
+
+        for (stack, count) in stacks {
+            if let Err(e) = writeln!(writer, "{stack} {count}") {
+                eprintln!("failed to write profile file: {}\n{e}", self.path);
+                return;
+            }
+        }
+
+        let dropped = samples
+            .next
+            .load(Ordering::Relaxed)
+            .saturating_sub(MAX_SAMPLES);
+
+        output!("Profile: wrote {count} samples to {}", self.path);
+
+        if dropped > 0 {
+            output!("Profile: dropped {dropped} samples after the buffer filled");
+        }
+    }
+}
+
+/// Records the stack range of the calling thread so that samples taken on it
+/// can walk its frames. This must be called as each thread starts.
+pub fn register_thread() {
+    if let Some(range) = unsafe { stack_range() } {
+        STACK.with(|stack| stack.set(range));
+    }
+}
+
+#[cfg(target_os = "linux")]
+unsafe fn stack_range() -> Option<(usize, usize)> {
+    let mut attr: libc::pthread_attr_t = std::mem::zeroed();
+
+    if libc::pthread_getattr_np(libc::pthread_self(), &mut attr) != 0 {
+        return None;
+    }
+
+    let mut addr: *mut c_void = std::ptr::null_mut();
+    let mut size: libc::size_t = 0;
+
+    let result = libc::pthread_attr_getstack(&attr, &mut addr, &mut size);
+
+    libc::pthread_attr_destroy(&mut attr);
+
+    if result != 0 {
+        return None;
+    }
+
+    Some((addr as usize, addr as usize + size))
+}
+
+#[cfg(not(target_os = "linux"))]
+unsafe fn stack_range() -> Option<(usize, usize)> {
+    None
+}
+
+unsafe fn set_timer(interval_us: i64) {
+    let interval = libc::timeval {
+        tv_sec: (interval_us / 1_000_000) as _,
+        tv_usec: (interval_us % 1_000_000) as _,
+    };
+
+    let timer = libc::itimerval {
+        it_interval: interval,
+        it_value: interval,
+    };
+
+    libc::setitimer(libc::ITIMER_PROF, &timer, std::ptr::null_mut());
+}
+
+/// Records the interrupted stack. This runs in the signal handler, so it must
+/// not allocate, take any locks, or call into the unwinder.
+extern "C" fn handler(_signal: libc::c_int, _info: *mut libc::siginfo_t, context: *mut c_void) {
+    let Some(samples) = SAMPLES.get() else {
+        return;
+    };
+
+    let sample = samples.next.fetch_add(1, Ordering::Relaxed);
+
+    if sample >= MAX_SAMPLES {
+        return;
+    }
+
+    let mut depth = 0;
+
+    // an unregistered thread has an empty range, so only its instruction
+    // pointer is recorded
+    let (low, high) = STACK.try_with(|stack| stack.get()).unwrap_or((0, 0));
+
+    if let Some((ip, sp, mut fp)) = unsafe { registers(context) } {
+        samples.frames[sample * MAX_DEPTH].store(ip, Ordering::Relaxed);
+        depth = 1;
+
+        // each frame stores the caller's frame pointer followed by the return
+        // address. The chain must move strictly up the stack and stay within
+        // the thread's stack, so a frame which does not is the end of the
+        // chain and the walk stops there.
+        while depth < MAX_DEPTH
+            && fp >= sp
+            && fp >= low
+            && fp.saturating_add(2 * std::mem::size_of::<usize>()) <= high
+            && fp % std::mem::align_of::<usize>() == 0
+        {
+            let frame = fp as *const usize;
+            let (next, ip) = unsafe { (frame.read(), frame.add(1).read()) };
+
+            if ip == 0 {
+                break;
+            }
+
+            samples.frames[sample * MAX_DEPTH + depth].store(ip, Ordering::Relaxed);
+            depth += 1;
+
+            if next <= fp {
+                break;
+            }
+
+            fp = next;
+        }
+    }
+
+    samples.depths[sample].store(depth, Ordering::Relaxed);
+}
+
+/// Returns the instruction pointer, stack pointer, and frame pointer of the
+/// interrupted context.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+unsafe fn registers(context: *mut c_void) -> Option<(usize, usize, usize)> {
+    let context = &*(context as *const libc::ucontext_t);
+    let gregs = &context.uc_mcontext.gregs;
+
+    Some((
+        gregs[libc::REG_RIP as usize] as usize,
+        gregs[libc::REG_RSP as usize] as usize,
+        gregs[libc::REG_RBP as usize] as usize,
+    ))
+}
+
+/// Returns the instruction pointer, stack pointer, and frame pointer of the
+/// interrupted context.
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+unsafe fn registers(context: *mut c_void) -> Option<(usize, usize, usize)> {
+    let context = &*(context as *const libc::ucontext_t);
+    let mcontext = &context.uc_mcontext;
+
+    Some((
+        mcontext.pc as usize,
+        mcontext.sp as usize,
+        mcontext.regs[29] as usize,
+    ))
+}
+
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+unsafe fn registers(_context: *mut c_void) -> Option<(usize, usize, usize)> {
+    None
+}
+
+fn resolve(ip: usize) -> Vec<String> {
+    let mut names = Vec::new();
+
+    backtrace::resolve(ip as *mut c_void, |symbol| {
+        if let Some(name) = symbol.name() {
+            // semicolons separate frames in the folded format
+            names.push(format!("{name:#}").replace(';', ":"));
+        }
+    });
+
+    if names.is_empty() {
+        names.push(format!("{ip:#x}"));
+    }
+
+    names
+}
@@ -138,6 +138,7 @@ fn launch_publishers(
     let mut publisher_rt = Builder::new_multi_thread()
         .enable_all()
         .worker_threads(config.pubsub().unwrap().publisher_threads())
+        .on_thread_start(thread_start)
         .build()
         .expect("failed to initialize tokio runtime");
 
@@ -173,6 +174,7 @@ fn launch_subscribers(config: &Config, workload_components: &[Component]) -> Opt
     let mut subscriber_rt = Builder::new_multi_thread()
         .enable_all()
         .worker_threads(config.pubsub().unwrap().subscriber_threads())
+        .on_thread_start(thread_start)
         .build()
         .expect("failed to initialize tokio runtime");
 
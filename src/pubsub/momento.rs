@@ -224,7 +224,7 @@ async fn publisher_task(
                 PUBSUB_PUBLISH_OK.increment();
                 let _ = PUBSUB_PUBLISH_LATENCY.increment(latency);
             }
-            Err(ResponseError::Exception) => {
+            Err(ResponseError::Exception) | Err(ResponseError::Unauthorized) => {
                 PUBSUB_PUBLISH_EX.increment();
             }
             Err(ResponseError::Timeout) | Err(ResponseError::BackendTimeout) => {
@@ -1,5 +1,9 @@
+use super::Keyspace;
+use crate::metrics::{HMAC_FAIL, RECORD_INCOMPLETE, STALE_READ};
 use core::time::Duration;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 #[derive(Debug, PartialEq)]
@@ -8,9 +12,30 @@ pub enum ClientWorkItem {
     Request {
         request: ClientRequest,
         sequence: u64,
+        /// Whether the request is part of a background load.
+        background: bool,
+        /// Whether the request is the first issued in a burst, after the
+        /// idle period of the burst duty cycle.
+        burst_start: bool,
+        /// Whether the request is for a command whose latency is reported
+        /// separately from all other requests.
+        isolated: bool,
     },
 }
 
+impl ClientWorkItem {
+    /// Returns the bytes of the request payload, counting the key and value,
+    /// which are buffered while the work item is queued.
+    pub fn buffered_len(&self) -> usize {
+        match self {
+            Self::Request { request, .. } => {
+                request.key().map_or(0, |key| key.len()) + request.value_len().unwrap_or(0)
+            }
+            Self::Reconnect => 0,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Ping {}
 
@@ -24,6 +49,275 @@ pub struct Add {
 #[derive(Debug, PartialEq)]
 pub struct Get {
     pub key: Arc<[u8]>,
+    /// The value to write back to the key if the get results in a miss.
+    pub populate: Option<Populate>,
+    /// The expected length of the value, if it should be checked on a hit.
+    pub expected_len: Option<usize>,
+    /// The sequence number check for the value, if it should be checked on a
+    /// hit.
+    pub sequence: Option<SequenceCheck>,
+    /// The HMAC check for the value, if it should be checked on a hit.
+    pub hmac: Option<ValueHmac>,
+    /// The record layout of the value, if its fields should be checked on a
+    /// hit.
+    pub record: Option<RecordLayout>,
+}
+
+/// The latest sequence numbers written to and read from a key.
+#[derive(Debug, Default)]
+pub struct KeySequence {
+    written: AtomicU64,
+    observed: AtomicU64,
+}
+
+impl KeySequence {
+    /// Returns the next sequence number to write to the key.
+    pub fn next(&self) -> u64 {
+        self.written.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// Checks the sequence number embedded in a value read from a key.
+#[derive(Debug)]
+pub struct SequenceCheck {
+    pub sequence: Arc<KeySequence>,
+    /// The position of the big-endian sequence number within the value.
+    pub offset: usize,
+    pub bytes: usize,
+}
+
+impl SequenceCheck {
+    /// Records the sequence number in the value, counting a stale read if it
+    /// is older than a sequence number previously read for the key.
+    pub fn observe(&self, value: &[u8]) {
+        let Some(field) = value.get(self.offset..self.offset + self.bytes) else {
+            return;
+        };
+
+        let sequence = field
+            .iter()
+            .fold(0, |sequence, byte| (sequence << 8) | *byte as u64);
+
+        if sequence
+            < self
+                .sequence
+                .observed
+                .fetch_max(sequence, Ordering::Relaxed)
+        {
+            STALE_READ.increment();
+        }
+    }
+}
+
+impl PartialEq for SequenceCheck {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.sequence, &other.sequence)
+            && self.offset == other.offset
+            && self.bytes == other.bytes
+    }
+}
+
+/// Writes and verifies the HMAC-SHA256 held in the last bytes of a value. The
+/// HMAC covers the key and the rest of the value and is keyed by the run key.
+#[derive(Clone, Debug)]
+pub struct ValueHmac {
+    secret: Arc<[u8]>,
+}
+
+impl ValueHmac {
+    /// The length of the HMAC at the end of the value.
+    pub const LEN: usize = 32;
+
+    pub fn new(secret: Arc<[u8]>) -> Self {
+        Self { secret }
+    }
+
+    fn digest(&self, key: &[u8], body: &[u8]) -> [u8; Self::LEN] {
+        // keys longer than the block size are hashed first
+        let mut block = [0_u8; 64];
+
+        if self.secret.len() > block.len() {
+            block[..32].copy_from_slice(&Sha256::digest(&self.secret));
+        } else {
+            block[..self.secret.len()].copy_from_slice(&self.secret);
+        }
+
+        let mut inner = Sha256::new();
+        inner.update(block.map(|byte| byte ^ 0x36));
+        // the key length separates the key from the value
+        inner.update((key.len() as u64).to_be_bytes());
+        inner.update(key);
+        inner.update(body);
+
+        let mut outer = Sha256::new();
+        outer.update(block.map(|byte| byte ^ 0x5c));
+        outer.update(inner.finalize());
+
+        outer.finalize().into()
+    }
+
+    /// Writes the HMAC into the end of the value. Values which are too short
+    /// to hold the HMAC are left unchanged.
+    pub fn stamp(&self, key: &[u8], value: &mut [u8]) {
+        let Some(split) = value.len().checked_sub(Self::LEN) else {
+            return;
+        };
+
+        let (body, tag) = value.split_at_mut(split);
+        tag.copy_from_slice(&self.digest(key, body));
+    }
+
+    /// Checks the HMAC at the end of a value read from the key, counting a
+    /// failure if it does not match.
+    pub fn verify(&self, key: &[u8], value: &[u8]) {
+        let Some(split) = value.len().checked_sub(Self::LEN) else {
+            HMAC_FAIL.increment();
+            return;
+        };
+
+        let (body, tag) = value.split_at(split);
+
+        if self.digest(key, body) != tag {
+            HMAC_FAIL.increment();
+        }
+    }
+}
+
+impl PartialEq for ValueHmac {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.secret, &other.secret)
+    }
+}
+
+/// The layout of a structured record stored as a value. Each field is written
+/// as a big-endian length followed by the field bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordLayout {
+    fields: Arc<[usize]>,
+}
+
+impl RecordLayout {
+    /// The length of the prefix which holds the length of each field.
+    pub const PREFIX_LEN: usize = 4;
+
+    pub fn new(fields: &[usize]) -> Self {
+        Self {
+            fields: fields.into(),
+        }
+    }
+
+    /// Returns the length of a complete record.
+    pub fn size(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|field| Self::PREFIX_LEN + field)
+            .sum()
+    }
+
+    /// Writes the length prefix of each field into the value. Any fields which
+    /// do not fit in the value are left out.
+    pub fn frame(&self, value: &mut [u8]) {
+        let mut offset = 0;
+
+        for field in self.fields.iter() {
+            let Some(prefix) = value.get_mut(offset..offset + Self::PREFIX_LEN) else {
+                return;
+            };
+
+            prefix.copy_from_slice(&(*field as u32).to_be_bytes());
+            offset += Self::PREFIX_LEN + field;
+        }
+    }
+
+    /// Checks that each field of a record read from a key is present with its
+    /// size, counting an incomplete record if not.
+    pub fn verify(&self, value: &[u8]) {
+        let mut offset = 0;
+
+        for field in self.fields.iter() {
+            let len = value
+                .get(offset..offset + Self::PREFIX_LEN)
+                .map(|prefix| u32::from_be_bytes(prefix.try_into().unwrap()) as usize);
+
+            offset += Self::PREFIX_LEN + field;
+
+            if len != Some(*field) || value.len() < offset {
+                RECORD_INCOMPLETE.increment();
+                return;
+            }
+        }
+
+        if value.len() != offset {
+            RECORD_INCOMPLETE.increment();
+        }
+    }
+}
+
+/// A get for a value which was split across several keys by a chunked set.
+#[derive(Debug, PartialEq)]
+pub struct ChunkedGet {
+    pub key: Arc<[u8]>,
+    pub chunks: usize,
+    /// The expected total length of the chunks.
+    pub expected_len: Option<usize>,
+}
+
+/// A set which splits the value across several keys of at most `chunk_size`
+/// bytes each.
+#[derive(Debug, PartialEq)]
+pub struct ChunkedSet {
+    pub key: Arc<[u8]>,
+    pub value: Vec<u8>,
+    pub chunk_size: usize,
+    pub ttl: Option<Duration>,
+}
+
+/// A delete of all of the keys a chunked set split the value across.
+#[derive(Debug, PartialEq)]
+pub struct ChunkedDelete {
+    pub key: Arc<[u8]>,
+    pub chunks: usize,
+}
+
+/// Returns the key used for a single chunk of a chunked value.
+pub fn chunk_key(key: &[u8], chunk: usize) -> Vec<u8> {
+    [key, format!(":chunk:{chunk}").as_bytes()].concat()
+}
+
+/// Writes a value for a key which missed. The value is only generated once
+/// the miss is seen, using a thread local RNG, so hits cost nothing extra and
+/// the seeded sequence of the workload is unaffected.
+pub struct Populate {
+    keyspace: Arc<Keyspace>,
+    pub ttl: Option<Duration>,
+}
+
+impl Populate {
+    pub(super) fn new(keyspace: Arc<Keyspace>) -> Self {
+        Self {
+            ttl: keyspace.ttl(),
+            keyspace,
+        }
+    }
+
+    /// Generates the value to write for the key.
+    pub fn value(&self, key: &[u8]) -> Vec<u8> {
+        let mut value = self.keyspace.gen_value(&mut rand::thread_rng());
+        self.keyspace.stamp_hmac(key, &mut value);
+        value
+    }
+}
+
+impl std::fmt::Debug for Populate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Populate").field("ttl", &self.ttl).finish()
+    }
+}
+
+impl PartialEq for Populate {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.keyspace, &other.keyspace) && self.ttl == other.ttl
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -48,6 +342,64 @@ pub struct Set {
     pub key: Arc<[u8]>,
     pub value: Vec<u8>,
     pub ttl: Option<Duration>,
+    /// The length of the payload portion of the value, which excludes any
+    /// fixed value header.
+    pub logical_len: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ChurnWrite {
+    pub key: Arc<[u8]>,
+    pub value: Vec<u8>,
+    pub ttl: Option<Duration>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DeleteIf {
+    pub key: Arc<[u8]>,
+    pub condition: DeleteCondition,
+}
+
+/// The condition on the current value of a key for a `DeleteIf` to delete it.
+#[derive(Debug, PartialEq)]
+pub enum DeleteCondition {
+    Any,
+    Length(usize),
+    Header(Arc<[u8]>),
+}
+
+impl DeleteCondition {
+    /// Returns `true` if the value satisfies the condition.
+    pub fn matches(&self, value: &[u8]) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Length(len) => value.len() == *len,
+            Self::Header(header) => value.starts_with(header),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Touch {
+    pub key: Arc<[u8]>,
+    pub ttl: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TtlCheck {
+    pub key: Arc<[u8]>,
+    pub value: Vec<u8>,
+    pub ttl: Duration,
+    /// How long to wait after the write before reading the key back.
+    pub wait: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Update {
+    pub key: Arc<[u8]>,
+    /// The number of bytes of the value to overwrite.
+    pub mutation_bytes: usize,
+    pub ttl: Option<Duration>,
 }
 
 // Hash
@@ -216,10 +568,18 @@ pub enum ClientRequest {
     // Key-Value
     Add(Add),
     Get(Get),
+    ChunkedGet(ChunkedGet),
+    ChunkedSet(ChunkedSet),
+    ChunkedDelete(ChunkedDelete),
+    ChurnWrite(ChurnWrite),
     Delete(Delete),
+    DeleteIf(DeleteIf),
     MultiGet(MultiGet),
     Replace(Replace),
     Set(Set),
+    Touch(Touch),
+    TtlCheck(TtlCheck),
+    Update(Update),
 
     // Hash Commands
     HashExists(HashExists),
@@ -265,3 +625,110 @@ pub enum ClientRequest {
     SortedSetRemove(SortedSetRemove),
     SortedSetScore(SortedSetScore),
 }
+
+impl ClientRequest {
+    /// Returns the name of the operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ping(_) => "ping",
+            Self::Add(_) => "add",
+            Self::Get(_) => "get",
+            Self::ChunkedGet(_) => "chunked_get",
+            Self::ChunkedSet(_) => "chunked_set",
+            Self::ChunkedDelete(_) => "chunked_delete",
+            Self::ChurnWrite(_) => "churn_write",
+            Self::Delete(_) => "delete",
+            Self::DeleteIf(_) => "delete_if",
+            Self::MultiGet(_) => "multi_get",
+            Self::Replace(_) => "replace",
+            Self::Set(_) => "set",
+            Self::Touch(_) => "touch",
+            Self::TtlCheck(_) => "ttl_check",
+            Self::Update(_) => "update",
+            Self::HashExists(_) => "hash_exists",
+            Self::HashDelete(_) => "hash_delete",
+            Self::HashGet(_) => "hash_get",
+            Self::HashGetAll(_) => "hash_get_all",
+            Self::HashIncrement(_) => "hash_increment",
+            Self::HashSet(_) => "hash_set",
+            Self::ListFetch(_) => "list_fetch",
+            Self::ListLength(_) => "list_length",
+            Self::ListPopBack(_) => "list_pop_back",
+            Self::ListPopFront(_) => "list_pop_front",
+            Self::ListPushBack(_) => "list_push_back",
+            Self::ListPushFront(_) => "list_push_front",
+            Self::ListRange(_) => "list_range",
+            Self::ListRemove(_) => "list_remove",
+            Self::ListStore(_) => "list_store",
+            Self::SetAdd(_) => "set_add",
+            Self::SetMembers(_) => "set_members",
+            Self::SetRemove(_) => "set_remove",
+            Self::SortedSetAdd(_) => "sorted_set_add",
+            Self::SortedSetIncrement(_) => "sorted_set_increment",
+            Self::SortedSetRange(_) => "sorted_set_range",
+            Self::SortedSetRank(_) => "sorted_set_rank",
+            Self::SortedSetRemove(_) => "sorted_set_remove",
+            Self::SortedSetScore(_) => "sorted_set_score",
+            Self::Reconnect => "reconnect",
+        }
+    }
+
+    /// Returns the key of the request, for operations which act on a single
+    /// key.
+    pub fn key(&self) -> Option<&[u8]> {
+        match self {
+            Self::Add(Add { key, .. })
+            | Self::Get(Get { key, .. })
+            | Self::ChunkedGet(ChunkedGet { key, .. })
+            | Self::ChunkedSet(ChunkedSet { key, .. })
+            | Self::ChunkedDelete(ChunkedDelete { key, .. })
+            | Self::ChurnWrite(ChurnWrite { key, .. })
+            | Self::Delete(Delete { key, .. })
+            | Self::DeleteIf(DeleteIf { key, .. })
+            | Self::Replace(Replace { key, .. })
+            | Self::Set(Set { key, .. })
+            | Self::Touch(Touch { key, .. })
+            | Self::TtlCheck(TtlCheck { key, .. })
+            | Self::Update(Update { key, .. })
+            | Self::HashExists(HashExists { key, .. })
+            | Self::HashDelete(HashDelete { key, .. })
+            | Self::HashGet(HashGet { key, .. })
+            | Self::HashGetAll(HashGetAll { key, .. })
+            | Self::HashIncrement(HashIncrement { key, .. })
+            | Self::HashSet(HashSet { key, .. })
+            | Self::ListFetch(ListFetch { key, .. })
+            | Self::ListLength(ListLength { key, .. })
+            | Self::ListPopBack(ListPopBack { key, .. })
+            | Self::ListPopFront(ListPopFront { key, .. })
+            | Self::ListPushBack(ListPushBack { key, .. })
+            | Self::ListPushFront(ListPushFront { key, .. })
+            | Self::ListRange(ListRange { key, .. })
+            | Self::ListRemove(ListRemove { key, .. })
+            | Self::ListStore(ListStore { key, .. })
+            | Self::SetAdd(SetAdd { key, .. })
+            | Self::SetMembers(SetMembers { key, .. })
+            | Self::SetRemove(SetRemove { key, .. })
+            | Self::SortedSetAdd(SortedSetAdd { key, .. })
+            | Self::SortedSetIncrement(SortedSetIncrement { key, .. })
+            | Self::SortedSetRange(SortedSetRange { key, .. })
+            | Self::SortedSetRank(SortedSetRank { key, .. })
+            | Self::SortedSetRemove(SortedSetRemove { key, .. })
+            | Self::SortedSetScore(SortedSetScore { key, .. }) => Some(&key[..]),
+            _ => None,
+        }
+    }
+
+    /// Returns the length of the value written by the request, for operations
+    /// which write a single value.
+    pub fn value_len(&self) -> Option<usize> {
+        match self {
+            Self::Add(Add { value, .. })
+            | Self::ChunkedSet(ChunkedSet { value, .. })
+            | Self::ChurnWrite(ChurnWrite { value, .. })
+            | Self::Replace(Replace { value, .. })
+            | Self::Set(Set { value, .. })
+            | Self::TtlCheck(TtlCheck { value, .. }) => Some(value.len()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Key generators select the index of the key (or topic) used by each request.
+//! New distributions only need to implement `KeyGenerator` and be added to
+//! `key_generator()` to become selectable from the config.
+
+use super::*;
+use rand::distributions::Uniform;
+use std::sync::atomic::AtomicUsize;
+use zipf::ZipfDistribution;
+
+pub trait KeyGenerator: Send + Sync {
+    /// Returns the index of the next key.
+    fn next_key(&self, rng: &mut dyn RngCore) -> usize;
+}
+
+/// Returns a key generator for the configured distribution over `nkeys` keys.
+/// The seed is used by generators which shuffle the keys and the replay is the
+/// sequence of key indices used by the replay generator.
+pub fn key_generator(
+    distribution: config::Distribution,
+    nkeys: usize,
+    seed: u64,
+    replay: Option<Vec<usize>>,
+) -> Arc<dyn KeyGenerator> {
+    let nkeys = std::cmp::max(1, nkeys);
+
+    match distribution {
+        config::Distribution::Uniform => Arc::new(UniformKeys {
+            dist: Uniform::new(0, nkeys),
+        }),
+        config::Distribution::Zipf => Arc::new(ZipfKeys {
+            dist: ZipfDistribution::new(nkeys, 1.0).unwrap(),
+        }),
+        config::Distribution::Sequential => Arc::new(SequentialKeys {
+            nkeys,
+            position: AtomicUsize::new(0),
+        }),
+        config::Distribution::Permutation => Arc::new(PermutationKeys {
+            n: nkeys as u64,
+            seed,
+            position: AtomicU64::new(0),
+        }),
+        config::Distribution::Replay => {
+            let Some(indices) = replay.filter(|indices| !indices.is_empty()) else {
+                eprintln!("the `replay` key distribution requires a non-empty `key_replay_file`");
+                std::process::exit(2);
+            };
+
+            if let Some(index) = indices.iter().find(|index| **index >= nkeys) {
+                eprintln!("replayed key index {index} is out of range for {nkeys} keys");
+                std::process::exit(2);
+            }
+
+            Arc::new(ReplayKeys {
+                indices,
+                position: AtomicUsize::new(0),
+            })
+        }
+    }
+}
+
+/// Selects keys uniformly at random.
+struct UniformKeys {
+    dist: Uniform<usize>,
+}
+
+impl KeyGenerator for UniformKeys {
+    fn next_key(&self, rng: &mut dyn RngCore) -> usize {
+        self.dist.sample(rng)
+    }
+}
+
+/// Selects keys following a zipfian distribution with an exponent of 1.0.
+struct ZipfKeys {
+    dist: ZipfDistribution,
+}
+
+impl KeyGenerator for ZipfKeys {
+    fn next_key(&self, rng: &mut dyn RngCore) -> usize {
+        // the zipf distribution is over 1..=nkeys
+        self.dist.sample(rng) - 1
+    }
+}
+
+/// Visits the keys in order, wrapping around at the end of the keyspace. The
+/// position is shared by all of the workload threads.
+struct SequentialKeys {
+    nkeys: usize,
+    position: AtomicUsize,
+}
+
+impl KeyGenerator for SequentialKeys {
+    fn next_key(&self, _rng: &mut dyn RngCore) -> usize {
+        self.position.fetch_add(1, Ordering::Relaxed) % self.nkeys
+    }
+}
+
+/// Produces every index in `0..n` exactly once before repeating. Each pass
+/// uses the affine permutation `(a * i + c) mod n`, with `a` coprime to `n`,
+/// so no storage is needed and a single position can be shared by all of the
+/// workload threads. The parameters are re-drawn for each pass so that the
+/// order differs between passes.
+struct PermutationKeys {
+    n: u64,
+    seed: u64,
+    position: AtomicU64,
+}
+
+impl PermutationKeys {
+    /// Returns the multiplier and offset for a pass through the keyspace.
+    fn parameters(&self, pass: u64) -> (u64, u64) {
+        // splitmix64, seeded by the pass number
+        let mut state = self.seed ^ pass.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let mut next = || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+
+        let mut a = next() % self.n;
+
+        while gcd(a, self.n) != 1 {
+            a = (a + 1) % self.n;
+        }
+
+        (a, next() % self.n)
+    }
+}
+
+impl KeyGenerator for PermutationKeys {
+    fn next_key(&self, _rng: &mut dyn RngCore) -> usize {
+        let position = self.position.fetch_add(1, Ordering::Relaxed);
+
+        let (a, c) = self.parameters(position / self.n);
+
+        ((a as u128 * (position % self.n) as u128 + c as u128) % self.n as u128) as usize
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// Replays a recorded sequence of key indices, looping back to the start once
+/// the sequence is exhausted.
+struct ReplayKeys {
+    indices: Vec<usize>,
+    position: AtomicUsize,
+}
+
+impl KeyGenerator for ReplayKeys {
+    fn next_key(&self, _rng: &mut dyn RngCore) -> usize {
+        let position = self.position.fetch_add(1, Ordering::Relaxed);
+        self.indices[position % self.indices.len()]
+    }
+}
+
+/// Loads a sequence of key indices, one per line, for replay. Blank lines and
+/// lines starting with `#` are ignored.
+pub fn load_replay(path: &str) -> Vec<usize> {
+    let contents = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("failed to read key replay file: {path}\n{e}");
+        std::process::exit(1);
+    });
+
+    contents
+        .lines()
+        .enumerate()
+        .map(|(line, text)| (line, text.trim()))
+        .filter(|(_, text)| !text.is_empty() && !text.starts_with('#'))
+        .map(|(line, text)| {
+            text.parse().unwrap_or_else(|_| {
+                eprintln!("invalid key index: {path}:{}: {text}", line + 1);
+                std::process::exit(1);
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rng() -> Xoshiro512PlusPlus {
+        Xoshiro512PlusPlus::seed_from_u64(0)
+    }
+
+    #[test]
+    fn uniform() {
+        let keys = key_generator(config::Distribution::Uniform, 10, 0, None);
+        let mut rng = rng();
+
+        for _ in 0..1000 {
+            assert!(keys.next_key(&mut rng) < 10);
+        }
+    }
+
+    #[test]
+    fn zipf() {
+        let keys = key_generator(config::Distribution::Zipf, 10, 0, None);
+        let mut rng = rng();
+
+        let indices: Vec<usize> = (0..10_000).map(|_| keys.next_key(&mut rng)).collect();
+
+        assert!(indices.iter().all(|index| *index < 10));
+        assert!(indices.contains(&0));
+    }
+
+    #[test]
+    fn sequential() {
+        let keys = key_generator(config::Distribution::Sequential, 3, 0, None);
+        let mut rng = rng();
+
+        let indices: Vec<usize> = (0..7).map(|_| keys.next_key(&mut rng)).collect();
+
+        assert_eq!(indices, vec![0, 1, 2, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn permutation() {
+        let keys = key_generator(config::Distribution::Permutation, 100, 42, None);
+        let mut rng = rng();
+
+        let mut passes = Vec::new();
+
+        for _ in 0..2 {
+            let pass: Vec<usize> = (0..100).map(|_| keys.next_key(&mut rng)).collect();
+
+            // every index is produced exactly once per pass
+            let mut sorted = pass.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..100).collect::<Vec<usize>>());
+
+            passes.push(pass);
+        }
+
+        assert_ne!(passes[0], passes[1]);
+    }
+
+    #[test]
+    fn replay() {
+        let keys = key_generator(config::Distribution::Replay, 10, 0, Some(vec![4, 2, 9]));
+        let mut rng = rng();
+
+        let indices: Vec<usize> = (0..5).map(|_| keys.next_key(&mut rng)).collect();
+
+        assert_eq!(indices, vec![4, 2, 9, 4, 2]);
+    }
+}
@@ -1,29 +1,95 @@
 use super::*;
-use config::{Command, RampCompletionAction, RampType, ValueKind, Verb};
+use config::{BatchOrder, Command, RampCompletionAction, RampType, ValueKind, Verb};
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use rand::distributions::{Alphanumeric, Uniform};
+use rand::distributions::Alphanumeric;
 use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng, RngCore, SeedableRng};
 use rand_distr::Distribution as RandomDistribution;
-use rand_distr::WeightedAliasIndex;
+use rand_distr::{Exp, LogNormal, WeightedAliasIndex};
 use rand_xoshiro::{Seed512, Xoshiro512PlusPlus};
 use ratelimit::Ratelimiter;
-use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::io::{Result, Write};
+use std::net::SocketAddr;
 use std::sync::atomic::AtomicU64;
-use std::sync::Arc;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::Instant;
 use tokio::runtime::Runtime;
-use zipf::ZipfDistribution;
 
 pub mod client;
+mod keys;
 mod publisher;
 
 pub use client::{ClientRequest, ClientWorkItem};
+use keys::{key_generator, load_replay, KeyGenerator};
 pub use publisher::PublisherWorkItem;
 
 static SEQUENCE_NUMBER: AtomicU64 = AtomicU64::new(0);
 
+/// Set when a background keyspace is configured.
+pub static BACKGROUND_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Set while the background load is on.
+pub static BACKGROUND_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set when any command is configured to have its latency isolated.
+pub static ISOLATION_ENABLED: AtomicBool = AtomicBool::new(false);
+
+// the secret for value HMACs, shared by all of the keyspaces
+static HMAC_SECRET: OnceLock<Arc<[u8]>> = OnceLock::new();
+
+// the number of recently written keys each workload thread tracks for
+// targeting deletes
+const RECENT_WRITES: usize = 1024;
+
+// the number of written keys each workload thread tracks per keyspace when
+// writes must precede reads. Once full, the oldest keys are forgotten and are
+// written again before their next read.
+const ORDERED_WRITES: usize = 1 << 20;
+
+// used to give each keyspace an identifier for its per-thread state
+static KEYSPACE_ID: AtomicUsize = AtomicUsize::new(0);
+
+// the set of written keys along with the order they were written in
+type OrderedKeys = (HashSet<Arc<[u8]>>, VecDeque<Arc<[u8]>>);
+
+thread_local! {
+    // the keys which this workload thread has generated writes for in each
+    // keyspace, in the order they were written, used when writes must precede
+    // reads
+    static WRITTEN_KEYS: RefCell<HashMap<usize, OrderedKeys>> = RefCell::new(HashMap::new());
+
+    // the keys this workload thread has most recently generated writes for,
+    // used to target deletes at keys which are likely to exist
+    static RECENT_KEYS: RefCell<VecDeque<Arc<[u8]>>> = RefCell::new(VecDeque::new());
+}
+
+// used to track the time between consecutive requests being issued across all
+// of the workload generator threads
+static ISSUE_EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+static LAST_ISSUE_NS: AtomicU64 = AtomicU64::new(0);
+
+// wakes the generator threads which are waiting for the buffered bytes to drop
+// below the cap, and the number of threads which are waiting
+static BUFFER_RELEASED: (Mutex<()>, Condvar) = (Mutex::new(()), Condvar::new());
+static BUFFER_WAITERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Removes the payload of a completed request from the buffered bytes, waking
+/// any generator thread which is waiting for room in the buffer.
+pub fn release_buffered(len: usize) {
+    TASK_MEM_BYTES.sub(len as i64);
+
+    if BUFFER_WAITERS.load(Ordering::Relaxed) > 0 {
+        BUFFER_RELEASED.1.notify_all();
+    }
+}
+
+// the number of bursts of the duty cycle which have started in this run, used
+// to mark the first request of each burst
+static BURSTS_STARTED: AtomicU64 = AtomicU64::new(0);
+
 pub fn launch_workload(
     generator: Generator,
     config: &Config,
@@ -36,6 +102,7 @@ pub fn launch_workload(
     let workload_rt = Builder::new_multi_thread()
         .enable_all()
         .worker_threads(1)
+        .on_thread_start(thread_start)
         .build()
         .expect("failed to initialize tokio runtime");
 
@@ -43,11 +110,19 @@ pub fn launch_workload(
     // generate unique seeds for each workload thread.
     let mut rng = Xoshiro512PlusPlus::from_seed(config.general().initial_seed());
 
+    let threads = config.workload().threads();
+    let shared_ratelimiter = if generator.per_thread_ratelimit {
+        generator.ratelimiter()
+    } else {
+        None
+    };
+
     // spawn the request generators on a blocking threads
-    for _ in 0..config.workload().threads() {
+    for _ in 0..threads {
         let client_sender = client_sender.clone();
         let pubsub_sender = pubsub_sender.clone();
         let generator = generator.clone();
+        let shared_ratelimiter = shared_ratelimiter.clone();
 
         // generate the seed for this workload thread
         let mut seed = [0; 64];
@@ -58,12 +133,37 @@ pub fn launch_workload(
             // requests in a different sequence
             let mut rng = Xoshiro512PlusPlus::from_seed(Seed512(seed));
 
+            // each thread paces itself when using per-thread ratelimiting
+            let mut ratelimiter =
+                shared_ratelimiter.map(|shared| ThreadRatelimiter::new(shared, threads));
+
             while RUNNING.load(Ordering::Relaxed) {
+                if let Some(ref mut ratelimiter) = ratelimiter {
+                    ratelimiter.wait();
+                }
+
                 generator.generate(&client_sender, &pubsub_sender, &mut rng);
             }
         });
     }
 
+    // each background keyspace is driven by its own thread
+    for background in generator.background.clone() {
+        let client_sender = client_sender.clone();
+        let generator = generator.clone();
+
+        let mut seed = [0; 64];
+        rng.fill_bytes(&mut seed);
+
+        workload_rt.spawn_blocking(move || {
+            let mut rng = Xoshiro512PlusPlus::from_seed(Seed512(seed));
+
+            generator.generate_background(&background, &client_sender, &mut rng);
+        });
+    }
+
+    workload_rt.spawn(dns_refresh(client_sender.clone(), config.clone()));
+
     let c = config.clone();
     workload_rt.spawn_blocking(move || reconnect(client_sender, c));
 
@@ -73,8 +173,55 @@ pub fn launch_workload(
 #[derive(Clone)]
 pub struct Generator {
     ratelimiter: Option<Arc<Ratelimiter>>,
+    // when set, the workload threads enforce their share of the ratelimit
+    // instead of waiting on the shared ratelimiter
+    per_thread_ratelimit: bool,
     components: Vec<Component>,
     component_dist: WeightedAliasIndex<usize>,
+    // keyspaces which drive a background load at their own rate instead of
+    // being part of the weighted mix
+    background: Vec<Background>,
+    burst: Option<Burst>,
+    // the cap on the request payload bytes buffered for the client tasks
+    max_buffered_bytes: Option<u64>,
+}
+
+/// An on/off duty cycle for the workload. The cycle starts when the generator
+/// is created.
+#[derive(Clone)]
+struct Burst {
+    on: Duration,
+    off: Duration,
+    start: Instant,
+}
+
+impl Burst {
+    /// Blocks while the duty cycle is in an idle period. Returns the number of
+    /// the current burst, or `None` if the test ended while idle.
+    fn wait(&self) -> Option<u64> {
+        let period = self.on + self.off;
+
+        while RUNNING.load(Ordering::Relaxed) {
+            let elapsed = self.start.elapsed();
+            let position = Duration::from_nanos((elapsed.as_nanos() % period.as_nanos()) as u64);
+
+            if position < self.on {
+                return Some((elapsed.as_nanos() / period.as_nanos()) as u64);
+            }
+
+            std::thread::sleep(std::cmp::min(period - position, Duration::from_millis(100)));
+        }
+
+        None
+    }
+}
+
+/// A keyspace which drives a background load at a fixed rate.
+#[derive(Clone)]
+struct Background {
+    keyspace: Arc<Keyspace>,
+    rate: u64,
+    interval: Option<Duration>,
 }
 
 impl Generator {
@@ -102,8 +249,32 @@ impl Generator {
         let mut components = Vec::new();
         let mut component_weights = Vec::new();
 
+        let mut background = Vec::new();
+
         for keyspace in config.workload().keyspaces() {
-            components.push(Component::Keyspace(Keyspace::new(config, keyspace)));
+            if let Some(rate) = keyspace.background_rate() {
+                if rate == 0 {
+                    eprintln!("keyspace `background_rate` must be greater than zero");
+                    std::process::exit(2);
+                }
+
+                if keyspace.background_interval() == Some(Duration::ZERO) {
+                    eprintln!("keyspace `background_interval` must be greater than zero");
+                    std::process::exit(2);
+                }
+
+                background.push(Background {
+                    keyspace: Arc::new(Keyspace::new(config, keyspace)),
+                    rate,
+                    interval: keyspace.background_interval(),
+                });
+
+                continue;
+            }
+
+            components.push(Component::Keyspace(Arc::new(Keyspace::new(
+                config, keyspace,
+            ))));
             component_weights.push(keyspace.weight());
         }
 
@@ -117,10 +288,44 @@ impl Generator {
             std::process::exit(1);
         }
 
+        BACKGROUND_ENABLED.store(!background.is_empty(), Ordering::Relaxed);
+
+        ISOLATION_ENABLED.store(
+            config
+                .workload()
+                .keyspaces()
+                .iter()
+                .any(|keyspace| keyspace.commands().iter().any(|command| command.isolate())),
+            Ordering::Relaxed,
+        );
+
+        let per_thread_ratelimit =
+            ratelimiter.is_some() && config.workload().ratelimit().per_thread();
+
+        let burst = config
+            .workload()
+            .ratelimit()
+            .burst()
+            .map(|(on, off)| Burst {
+                on,
+                off,
+                start: Instant::now(),
+            });
+
+        // the bursts are numbered from the start of each run, and the first
+        // burst is not marked as it does not follow an idle period
+        BURSTS_STARTED.store(1, Ordering::Relaxed);
+
         Self {
             ratelimiter,
+            per_thread_ratelimit,
             components,
             component_dist: WeightedAliasIndex::new(component_weights).unwrap(),
+            background,
+            burst,
+            max_buffered_bytes: config
+                .client()
+                .and_then(|client| client.max_buffered_bytes()),
         }
     }
 
@@ -128,13 +333,137 @@ impl Generator {
         self.ratelimiter.clone()
     }
 
+    /// Writes the keys from the `preload_keys_file` of each keyspace and waits
+    /// for the writes to complete. The preload is not ratelimited and runs
+    /// before any measurement begins, so its results are reported separately.
+    pub fn preload(&self, config: &Config, client_sender: &Sender<ClientWorkItem>) {
+        let keyspaces: Vec<&Keyspace> = self
+            .components
+            .iter()
+            .filter_map(|component| match component {
+                Component::Keyspace(keyspace) if keyspace.preload.is_some() => {
+                    Some(keyspace.as_ref())
+                }
+                _ => None,
+            })
+            .collect();
+
+        if keyspaces.is_empty() || config.client().is_none() {
+            return;
+        }
+
+        let completed = || {
+            RESPONSE_OK.value()
+                + RESPONSE_EX.value()
+                + RESPONSE_TIMEOUT.value()
+                + RESPONSE_RATELIMITED.value()
+        };
+
+        let ok = RESPONSE_OK.value();
+        let ex = RESPONSE_EX.value() + RESPONSE_RATELIMITED.value();
+        let timeout = RESPONSE_TIMEOUT.value();
+        let target = completed();
+
+        let mut rng = Xoshiro512PlusPlus::from_seed(config.general().initial_seed());
+        let mut sent = 0;
+
+        output!("Preload: writing keys");
+
+        let start = Instant::now();
+
+        for keyspace in keyspaces {
+            for (key, value) in keyspace.preload.as_ref().unwrap().iter() {
+                let mut value = value
+                    .clone()
+                    .unwrap_or_else(|| keyspace.gen_value(&mut rng));
+
+                keyspace.stamp_hmac(key, &mut value);
+
+                // values are only split when they span more than one chunk,
+                // matching the keys which the generated requests use
+                let request = if keyspace.chunks().is_some() {
+                    ClientRequest::ChunkedSet(client::ChunkedSet {
+                        key: key.clone(),
+                        value,
+                        chunk_size: keyspace.chunk_size.unwrap(),
+                        ttl: keyspace.ttl(),
+                    })
+                } else {
+                    ClientRequest::Set(client::Set {
+                        key: key.clone(),
+                        logical_len: keyspace.logical_len(&value),
+                        value,
+                        ttl: keyspace.ttl(),
+                    })
+                };
+
+                let item = ClientWorkItem::Request {
+                    request,
+                    sequence: SEQUENCE_NUMBER.fetch_add(1, Ordering::Relaxed),
+                    background: false,
+                    burst_start: false,
+                    isolated: false,
+                };
+
+                TASK_MEM_BYTES.add(item.buffered_len() as i64);
+
+                if client_sender.send_blocking(item).is_err() {
+                    eprintln!("failed to send preload request");
+                    std::process::exit(1);
+                }
+
+                PRELOAD.increment();
+                sent += 1;
+            }
+        }
+
+        // wait for the outstanding writes, giving up if no progress is made
+        // within a request timeout
+        let stall = config
+            .client()
+            .map(|client| client.request_timeout() * 2)
+            .unwrap_or(Duration::from_secs(1));
+        let mut progress = (completed(), Instant::now());
+
+        while completed() - target < sent {
+            std::thread::sleep(Duration::from_millis(10));
+
+            let now = completed();
+            if now != progress.0 {
+                progress = (now, Instant::now());
+            } else if progress.1.elapsed() > stall {
+                warn!(
+                    "preload stalled, {} writes are outstanding",
+                    sent - (now - target)
+                );
+                break;
+            }
+        }
+
+        output!(
+            "Preload: {sent} keys in {:.2} s Ok: {} Errors: {} Timeouts: {}",
+            start.elapsed().as_secs_f64(),
+            RESPONSE_OK.value() - ok,
+            RESPONSE_EX.value() + RESPONSE_RATELIMITED.value() - ex,
+            RESPONSE_TIMEOUT.value() - timeout,
+        );
+    }
+
     pub fn generate(
         &self,
         client_sender: &Sender<ClientWorkItem>,
         pubsub_sender: &Sender<PublisherWorkItem>,
         rng: &mut dyn RngCore,
     ) {
-        if let Some(ref ratelimiter) = self.ratelimiter {
+        let burst = match &self.burst {
+            Some(burst) => match burst.wait() {
+                Some(burst) => Some(burst),
+                None => return,
+            },
+            None => None,
+        };
+
+        if let (Some(ref ratelimiter), false) = (&self.ratelimiter, self.per_thread_ratelimit) {
             loop {
                 RATELIMIT_DROPPED.set(ratelimiter.dropped());
 
@@ -146,12 +475,31 @@ impl Generator {
             }
         }
 
+        // record the gap since the previous request was issued by any thread
+        let now = ISSUE_EPOCH.elapsed().as_nanos() as u64;
+        let previous = LAST_ISSUE_NS.swap(now, Ordering::Relaxed);
+        if previous != 0 {
+            let _ = INTERARRIVAL.increment(now.saturating_sub(previous));
+        }
+
         match &self.components[self.component_dist.sample(rng)] {
             Component::Keyspace(keyspace) => {
-                if client_sender
-                    .try_send(self.generate_request(keyspace, rng))
-                    .is_err()
-                {
+                let mut item = self.generate_request(keyspace, rng);
+
+                // the first request of each burst follows the idle period
+                if let Some(burst) = burst {
+                    if BURSTS_STARTED.fetch_max(burst + 1, Ordering::Relaxed) <= burst {
+                        BURST.increment();
+
+                        if let ClientWorkItem::Request { burst_start, .. } = &mut item {
+                            *burst_start = true;
+                        }
+                    }
+                }
+
+                self.wait_for_buffer();
+
+                if !self.send_request(client_sender, item) {
                     REQUEST_DROPPED.increment();
                 }
             }
@@ -166,8 +514,154 @@ impl Generator {
         }
     }
 
+    /// Waits while the request payloads buffered for the client tasks exceed
+    /// the configured cap, applying backpressure to the workload.
+    fn wait_for_buffer(&self) {
+        let Some(max) = self.max_buffered_bytes else {
+            return;
+        };
+
+        if (TASK_MEM_BYTES.value() as u64) < max {
+            return;
+        }
+
+        BUFFER_WAITERS.fetch_add(1, Ordering::Relaxed);
+
+        let mut lock = BUFFER_RELEASED.0.lock().unwrap();
+
+        while RUNNING.load(Ordering::Relaxed) && TASK_MEM_BYTES.value() as u64 >= max {
+            // the timeout covers a release between the check and the wait, and
+            // the end of the test
+            lock = BUFFER_RELEASED
+                .1
+                .wait_timeout(lock, Duration::from_millis(10))
+                .unwrap()
+                .0;
+        }
+
+        drop(lock);
+
+        BUFFER_WAITERS.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Queues a request for the client tasks, counting its payload towards the
+    /// buffered bytes. Returns `false` if the queue is full.
+    fn send_request(&self, client_sender: &Sender<ClientWorkItem>, item: ClientWorkItem) -> bool {
+        let len = item.buffered_len() as i64;
+
+        TASK_MEM_BYTES.add(len);
+
+        if client_sender.try_send(item).is_err() {
+            TASK_MEM_BYTES.sub(len);
+            return false;
+        }
+
+        true
+    }
+
+    /// Writes the first `count` requests of the workload to the file at `path`
+    /// without issuing any of them. Each line holds the operation, the key (or
+    /// topic), and the value (or message) length, with `-` for any which do
+    /// not apply to the operation.
+    pub fn emit(&self, config: &Config, path: &str, count: usize) {
+        let mut rng = Xoshiro512PlusPlus::from_seed(config.general().initial_seed());
+
+        let file = std::fs::File::create(path).unwrap_or_else(|e| {
+            eprintln!("failed to create workload file: {path}\n{e}");
+            std::process::exit(1);
+        });
+
+        let mut writer = std::io::BufWriter::new(file);
+
+        for _ in 0..count {
+            let line = match &self.components[self.component_dist.sample(&mut rng)] {
+                Component::Keyspace(keyspace) => match self.generate_request(keyspace, &mut rng) {
+                    ClientWorkItem::Request { request, .. } => format!(
+                        "{} {} {}",
+                        request.name(),
+                        request
+                            .key()
+                            .map(|key| key.escape_ascii().to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                        request
+                            .value_len()
+                            .map(|len| len.to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                    ClientWorkItem::Reconnect => "reconnect - -".to_string(),
+                },
+                Component::Topics(topics) => match self.generate_pubsub(topics, &mut rng) {
+                    PublisherWorkItem::Publish { topic, message, .. } => {
+                        format!("publish {topic} {}", message.len())
+                    }
+                },
+            };
+
+            if let Err(e) = writeln!(writer, "{line}") {
+                eprintln!("failed to write workload file: {path}\n{e}");
+                std::process::exit(1);
+            }
+        }
+
+        if let Err(e) = writer.flush() {
+            eprintln!("failed to write workload file: {path}\n{e}");
+            std::process::exit(1);
+        }
+
+        output!("Workload: wrote {count} requests to {path}");
+    }
+
+    /// Issues requests for a background keyspace at its configured rate until
+    /// the test ends. With an interval, the load starts off and is toggled on
+    /// and off each interval.
+    fn generate_background(
+        &self,
+        background: &Background,
+        client_sender: &Sender<ClientWorkItem>,
+        rng: &mut dyn RngCore,
+    ) {
+        let period = Duration::from_nanos(1_000_000_000 / background.rate);
+        let start = Instant::now();
+        let mut next = start;
+
+        while RUNNING.load(Ordering::Relaxed) {
+            let active = match background.interval {
+                Some(interval) => (start.elapsed().as_nanos() / interval.as_nanos()) % 2 == 1,
+                None => true,
+            };
+
+            BACKGROUND_ACTIVE.store(active, Ordering::Relaxed);
+
+            if !active {
+                std::thread::sleep(Duration::from_millis(10));
+                next = Instant::now();
+                continue;
+            }
+
+            let now = Instant::now();
+            if next > now {
+                std::thread::sleep(next - now);
+            }
+            next += period;
+
+            let mut item = self.generate_request(&background.keyspace, rng);
+
+            if let ClientWorkItem::Request { background, .. } = &mut item {
+                *background = true;
+            }
+
+            self.wait_for_buffer();
+
+            if !self.send_request(client_sender, item) {
+                REQUEST_DROPPED.increment();
+            }
+        }
+
+        BACKGROUND_ACTIVE.store(false, Ordering::Relaxed);
+    }
+
     fn generate_pubsub(&self, topics: &Topics, rng: &mut dyn RngCore) -> PublisherWorkItem {
-        let topic_index = topics.topic_dist.sample(rng);
+        let topic_index = topics.topic_dist.next_key(rng);
         let topic = topics.topics[topic_index].clone();
 
         let mut m = vec![0_u8; topics.message_len];
@@ -198,37 +692,144 @@ impl Generator {
         }
     }
 
-    fn generate_request(&self, keyspace: &Keyspace, rng: &mut dyn RngCore) -> ClientWorkItem {
-        let command = &keyspace.commands[keyspace.command_dist.sample(rng)];
+    fn generate_request(&self, keyspace: &Arc<Keyspace>, rng: &mut dyn RngCore) -> ClientWorkItem {
+        // the key class determines both the keys and the value length
+        let keyspace = match &keyspace.key_class_dist {
+            Some(dist) => {
+                let class = dist.sample(rng);
+                keyspace.key_class_requests[class].fetch_add(1, Ordering::Relaxed);
+                &keyspace.key_classes[class]
+            }
+            None => keyspace,
+        };
+
+        let index = keyspace.command_dist.sample(rng);
+        let command = &keyspace.commands[index];
+
+        // some commands use their own view of the keyspace
+        let keyspace = match keyspace.command_keyspaces.get(index) {
+            Some(Some(command_keyspace)) => command_keyspace,
+            _ => keyspace,
+        };
+
+        // reads may target a separate set of keys
+        let keyspace = match &keyspace.read_keyspace {
+            Some(read_keyspace) if command.verb().is_read() => read_keyspace,
+            _ => keyspace,
+        };
 
         let request = match command.verb() {
-            Verb::Add => ClientRequest::Add(client::Add {
+            Verb::Add => {
+                let key = keyspace.sample(rng);
+                let mut value = keyspace.gen_value(rng);
+                keyspace.stamp_hmac(&key, &mut value);
+                ClientRequest::Add(client::Add {
+                    key,
+                    value,
+                    ttl: keyspace.ttl(),
+                })
+            }
+            Verb::Get if keyspace.chunks().is_some() => {
+                ClientRequest::ChunkedGet(client::ChunkedGet {
+                    key: keyspace.sample(rng),
+                    chunks: keyspace.chunks().unwrap(),
+                    expected_len: keyspace.expected_vlen(),
+                })
+            }
+            Verb::Get => {
+                let key = keyspace.sample(rng);
+                ClientRequest::Get(client::Get {
+                    populate: keyspace
+                        .populate_on_miss
+                        .then(|| client::Populate::new(keyspace.clone())),
+                    expected_len: if keyspace.verify_value_length {
+                        keyspace.expected_vlen()
+                    } else {
+                        None
+                    },
+                    sequence: keyspace.sequence_check(&key),
+                    hmac: keyspace.hmac.clone(),
+                    record: keyspace.record.clone(),
+                    key,
+                })
+            }
+            Verb::Set if keyspace.chunks().is_some() => {
+                ClientRequest::ChunkedSet(client::ChunkedSet {
+                    key: keyspace.sample(rng),
+                    value: keyspace.gen_value(rng),
+                    chunk_size: keyspace.chunk_size.unwrap(),
+                    ttl: keyspace.ttl(),
+                })
+            }
+            Verb::Set => {
+                // the key is sampled before the value is generated so that a
+                // seeded run draws from the rng in the same order as before
+                let key = keyspace.sample(rng);
+                let mut value = keyspace.gen_value(rng);
+                keyspace.stamp_sequence(&key, &mut value);
+                keyspace.stamp_hmac(&key, &mut value);
+                ClientRequest::Set(client::Set {
+                    key,
+                    logical_len: keyspace.logical_len(&value),
+                    value,
+                    ttl: keyspace.ttl(),
+                })
+            }
+            Verb::Delete if keyspace.chunks().is_some() => {
+                ClientRequest::ChunkedDelete(client::ChunkedDelete {
+                    key: keyspace.sample(rng),
+                    chunks: keyspace.chunks().unwrap(),
+                })
+            }
+            Verb::Delete => ClientRequest::Delete(client::Delete {
                 key: keyspace.sample(rng),
-                value: keyspace.gen_value(rng),
-                ttl: keyspace.ttl(),
             }),
-            Verb::Get => ClientRequest::Get(client::Get {
+            Verb::DeleteIf => ClientRequest::DeleteIf(client::DeleteIf {
                 key: keyspace.sample(rng),
+                condition: keyspace.delete_condition(command.expected_value()),
             }),
-            Verb::Set => ClientRequest::Set(client::Set {
+            Verb::TtlCheck => {
+                // the keyspace ttl is validated to be set for this verb
+                let ttl = keyspace.ttl().unwrap();
+                ClientRequest::TtlCheck(client::TtlCheck {
+                    key: keyspace.sample(rng),
+                    value: keyspace.gen_value(rng),
+                    ttl,
+                    wait: command.wait().unwrap_or(ttl) + command.grace(),
+                })
+            }
+            Verb::Update => ClientRequest::Update(client::Update {
                 key: keyspace.sample(rng),
-                value: keyspace.gen_value(rng),
+                mutation_bytes: command.mutation_bytes().unwrap_or(8),
                 ttl: keyspace.ttl(),
             }),
-            Verb::Delete => ClientRequest::Delete(client::Delete {
-                key: keyspace.sample(rng),
-            }),
-            Verb::Replace => ClientRequest::Replace(client::Replace {
+            Verb::ChurnWrite => ClientRequest::ChurnWrite(client::ChurnWrite {
                 key: keyspace.sample(rng),
                 value: keyspace.gen_value(rng),
                 ttl: keyspace.ttl(),
             }),
+            Verb::Touch => ClientRequest::Touch(client::Touch {
+                key: keyspace.sample(rng),
+                // a ttl is validated to be set for this verb
+                ttl: command.ttl().or(keyspace.ttl()).unwrap(),
+            }),
+            Verb::Replace => {
+                let key = keyspace.sample(rng);
+                let mut value = keyspace.gen_value(rng);
+                keyspace.stamp_hmac(&key, &mut value);
+                ClientRequest::Replace(client::Replace {
+                    key,
+                    value,
+                    ttl: keyspace.ttl(),
+                })
+            }
             Verb::HashGet => {
                 let cardinality = command.cardinality();
                 let mut fields = Vec::with_capacity(cardinality);
                 for _ in 0..cardinality {
                     fields.push(keyspace.sample_inner(rng));
                 }
+                order_batch(command.batch_order(), &mut fields, |key| key, rng);
 
                 ClientRequest::HashGet(client::HashGet {
                     key: keyspace.sample(rng),
@@ -244,6 +845,7 @@ impl Generator {
                 for _ in 0..cardinality {
                     fields.push(keyspace.sample_inner(rng));
                 }
+                order_batch(command.batch_order(), &mut fields, |key| key, rng);
 
                 ClientRequest::HashDelete(client::HashDelete {
                     key: keyspace.sample(rng),
@@ -277,6 +879,7 @@ impl Generator {
                 for _ in 0..cardinality {
                     elements.push(keyspace.sample_inner(rng));
                 }
+                order_batch(command.batch_order(), &mut elements, |key| key, rng);
                 ClientRequest::ListPushFront(client::ListPushFront {
                     key: keyspace.sample(rng),
                     elements,
@@ -290,6 +893,7 @@ impl Generator {
                 for _ in 0..cardinality {
                     elements.push(keyspace.sample_inner(rng));
                 }
+                order_batch(command.batch_order(), &mut elements, |key| key, rng);
                 ClientRequest::ListPushBack(client::ListPushBack {
                     key: keyspace.sample(rng),
                     elements,
@@ -319,7 +923,8 @@ impl Generator {
                 while members.len() < command.cardinality() {
                     members.insert(keyspace.sample_inner(rng));
                 }
-                let members = members.drain().collect();
+                let mut members: Vec<_> = members.drain().collect();
+                order_batch(command.batch_order(), &mut members, |key| key, rng);
                 ClientRequest::SetAdd(client::SetAdd {
                     key: keyspace.sample(rng),
                     members,
@@ -334,7 +939,8 @@ impl Generator {
                 while members.len() < command.cardinality() {
                     members.insert(keyspace.sample_inner(rng));
                 }
-                let members = members.drain().collect();
+                let mut members: Vec<_> = members.drain().collect();
+                order_batch(command.batch_order(), &mut members, |key| key, rng);
                 ClientRequest::SetRemove(client::SetRemove {
                     key: keyspace.sample(rng),
                     members,
@@ -345,7 +951,8 @@ impl Generator {
                 while members.len() < command.cardinality() {
                     members.insert(keyspace.sample_inner(rng));
                 }
-                let members = members.drain().map(|m| (m, rng.gen())).collect();
+                let mut members: Vec<_> = members.drain().map(|m| (m, rng.gen())).collect();
+                order_batch(command.batch_order(), &mut members, |(key, _)| key, rng);
                 ClientRequest::SortedSetAdd(client::SortedSetAdd {
                     key: keyspace.sample(rng),
                     members,
@@ -363,7 +970,8 @@ impl Generator {
                 while members.len() < command.cardinality() {
                     members.insert(keyspace.sample_inner(rng));
                 }
-                let members = members.drain().collect();
+                let mut members: Vec<_> = members.drain().collect();
+                order_batch(command.batch_order(), &mut members, |key| key, rng);
                 ClientRequest::SortedSetRemove(client::SortedSetRemove {
                     key: keyspace.sample(rng),
                     members,
@@ -382,7 +990,8 @@ impl Generator {
                 while members.len() < command.cardinality() {
                     members.insert(keyspace.sample_inner(rng));
                 }
-                let members = members.drain().collect();
+                let mut members: Vec<_> = members.drain().collect();
+                order_batch(command.batch_order(), &mut members, |key| key, rng);
                 ClientRequest::SortedSetScore(client::SortedSetScore {
                     key: keyspace.sample(rng),
                     members,
@@ -394,12 +1003,128 @@ impl Generator {
             }),
         };
 
+        let request = if keyspace.write_before_read {
+            Self::order_request(keyspace, request, rng)
+        } else {
+            request
+        };
+
+        let request = match keyspace.delete_recent_bias {
+            Some(bias) => Self::target_delete(request, bias, rng),
+            None => request,
+        };
+
         ClientWorkItem::Request {
             request,
             sequence: SEQUENCE_NUMBER.fetch_add(1, Ordering::Relaxed),
+            background: false,
+            burst_start: false,
+            isolated: command.isolate(),
         }
     }
 
+    /// Ensures that a get is only issued for a key once this thread has issued
+    /// a write for it in the same keyspace, replacing the get with a set
+    /// otherwise.
+    ///
+    /// The ordering is of generation, not execution. Requests from one thread
+    /// are spread across the client tasks, so the write is only guaranteed to
+    /// complete before the read with a single workload thread and a single
+    /// client task.
+    fn order_request(
+        keyspace: &Keyspace,
+        request: ClientRequest,
+        rng: &mut dyn RngCore,
+    ) -> ClientRequest {
+        WRITTEN_KEYS.with(|written| {
+            let mut written = written.borrow_mut();
+            let (written, order) = written.entry(keyspace.id).or_default();
+
+            let mut insert = |key: &Arc<[u8]>| {
+                if written.insert(key.clone()) {
+                    order.push_back(key.clone());
+
+                    if order.len() > ORDERED_WRITES {
+                        if let Some(oldest) = order.pop_front() {
+                            written.remove(&oldest);
+                        }
+                    }
+
+                    true
+                } else {
+                    false
+                }
+            };
+
+            match request {
+                ClientRequest::Get(get) if insert(&get.key) => {
+                    ORDERED_WRITE.increment();
+
+                    let value = keyspace.gen_value(rng);
+
+                    ClientRequest::Set(client::Set {
+                        key: get.key,
+                        logical_len: keyspace.logical_len(&value),
+                        value,
+                        ttl: keyspace.ttl(),
+                    })
+                }
+                ClientRequest::Set(ref r) => {
+                    insert(&r.key);
+                    request
+                }
+                ClientRequest::Add(ref r) => {
+                    insert(&r.key);
+                    request
+                }
+                ClientRequest::Replace(ref r) => {
+                    insert(&r.key);
+                    request
+                }
+                _ => request,
+            }
+        })
+    }
+
+    /// Tracks the keys recently written by this thread and, with probability
+    /// `bias`, retargets a delete to one of those keys so that the delete
+    /// removes a key which is likely to exist.
+    fn target_delete(request: ClientRequest, bias: f64, rng: &mut dyn RngCore) -> ClientRequest {
+        RECENT_KEYS.with(|recent| {
+            let mut recent = recent.borrow_mut();
+
+            let written = match &request {
+                ClientRequest::Set(r) => Some(r.key.clone()),
+                ClientRequest::Add(r) => Some(r.key.clone()),
+                ClientRequest::Replace(r) => Some(r.key.clone()),
+                _ => None,
+            };
+
+            if let Some(key) = written {
+                if recent.len() >= RECENT_WRITES {
+                    recent.pop_front();
+                }
+                recent.push_back(key);
+
+                return request;
+            }
+
+            match request {
+                ClientRequest::Delete(_) if !recent.is_empty() && rng.gen_bool(bias) => {
+                    RECENT_DELETE.increment();
+
+                    // the key is removed from the recent writes since it will
+                    // no longer exist
+                    let index = rng.gen_range(0..recent.len());
+                    let key = recent.swap_remove_back(index).unwrap();
+
+                    ClientRequest::Delete(client::Delete { key })
+                }
+                _ => request,
+            }
+        })
+    }
+
     pub fn components(&self) -> &[Component] {
         &self.components
     }
@@ -407,7 +1132,7 @@ impl Generator {
 
 #[derive(Clone)]
 pub enum Component {
-    Keyspace(Keyspace),
+    Keyspace(Arc<Keyspace>),
     Topics(Topics),
 }
 
@@ -416,7 +1141,7 @@ pub struct Topics {
     topics: Vec<Arc<String>>,
     partitions: usize,
     replications: usize,
-    topic_dist: Distribution,
+    topic_dist: Arc<dyn KeyGenerator>,
     key_len: usize,
     message_len: usize,
     message_random_bytes: usize,
@@ -438,11 +1163,14 @@ impl Topics {
         let key_len = topics.key_len();
         let subscriber_poolsize = topics.subscriber_poolsize();
         let subscriber_concurrency = topics.subscriber_concurrency();
-        let topic_dist = match topics.topic_distribution() {
-            config::Distribution::Uniform => Distribution::Uniform(Uniform::new(0, ntopics)),
-            config::Distribution::Zipf => {
-                Distribution::Zipf(ZipfDistribution::new(ntopics, 1.0).unwrap())
-            }
+        if topics.topic_distribution() == config::Distribution::Replay {
+            eprintln!("the `replay` distribution is not supported for topics");
+            std::process::exit(2);
+        }
+
+        let topic_dist = {
+            let mut rng = Xoshiro512PlusPlus::from_seed(config.general().initial_seed());
+            key_generator(topics.topic_distribution(), ntopics, rng.next_u64(), None)
         };
         let topic_names: Vec<Arc<String>>;
         // if the given topic_names has the matched format, we use topic names there
@@ -518,213 +1246,1645 @@ impl Topics {
 #[derive(Clone)]
 pub struct Keyspace {
     keys: Vec<Arc<[u8]>>,
-    key_dist: Distribution,
+    key_dist: Arc<dyn KeyGenerator>,
     commands: Vec<Command>,
     command_dist: WeightedAliasIndex<usize>,
     inner_keys: Vec<Arc<[u8]>>,
-    inner_key_dist: Distribution,
+    inner_key_dist: Arc<dyn KeyGenerator>,
     vlen: usize,
+    // whether a `vlen` was configured, as a `vlen` of zero is also valid
+    vlen_set: bool,
+    // samples the value lengths, relative to the `vlen`, when they are not
+    // fixed
+    vlen_sampler: Option<VlenSampler>,
+    vlen_max: Option<usize>,
     vkind: ValueKind,
     value_random_bytes: usize,
     ttl: Option<Duration>,
+    populate_on_miss: bool,
+    // the shard for each key, empty unless the keyspace is sharded
+    key_shards: Vec<usize>,
+    shard_requests: Arc<Vec<AtomicU64>>,
+    max_value_bytes: Option<usize>,
+    // an optional view of this keyspace with a disjoint set of keys which is
+    // used for reads
+    read_keyspace: Option<Arc<Keyspace>>,
+    // views of this keyspace for commands which have their own key prefix or
+    // value length, indexed by command
+    command_keyspaces: Vec<Option<Arc<Keyspace>>>,
+    hotspot: Option<Hotspot>,
+    growth: Option<Growth>,
+    auto_size: Option<AutoSize>,
+    // identifies the keyspace, and any views of it, for per-thread state
+    id: usize,
+    write_before_read: bool,
+    delete_recent_bias: Option<f64>,
+    verify_value_length: bool,
+    chunk_size: Option<usize>,
+    // the keys, and any values, which are written before the test begins
+    preload: Option<Arc<Vec<(Arc<[u8]>, Option<Vec<u8>>)>>>,
+    // a fixed header which is written at the start of each value
+    value_header: Option<Arc<[u8]>>,
+    // the sequence number state for each key and the size of the sequence
+    // number written to each value, when sequence numbers are enabled
+    sequences: Option<Arc<HashMap<Arc<[u8]>, Arc<client::KeySequence>>>>,
+    sequence_bytes: usize,
+    // writes and verifies the HMAC at the end of each value, when enabled
+    hmac: Option<client::ValueHmac>,
+    // the layout of the fields of each value, when values are records
+    record: Option<client::RecordLayout>,
+    // values loaded from the files in the value corpus
+    corpus: Option<Arc<Vec<Arc<[u8]>>>>,
+    // views of this keyspace for each key class, with the class prefix and
+    // value length applied
+    key_classes: Vec<Arc<Keyspace>>,
+    key_class_names: Vec<String>,
+    key_class_dist: Option<WeightedAliasIndex<usize>>,
+    key_class_requests: Arc<Vec<AtomicU64>>,
+    // the compression ratio of each compressibility class and the number of
+    // random bytes needed to match it at the current value length
+    compression_ratios: Vec<f64>,
+    compression_class_random_bytes: Vec<usize>,
+    compression_class_names: Vec<String>,
+    compression_class_dist: Option<WeightedAliasIndex<usize>>,
+    compression_class_values: Arc<Vec<AtomicU64>>,
 }
 
-#[derive(Clone)]
-pub enum Distribution {
-    Uniform(rand::distributions::Uniform<usize>),
-    Zipf(zipf::ZipfDistribution),
+/// Samples a multiple of the `vlen` with a mean of one, so that the mean value
+/// length is preserved by views of the keyspace with a different `vlen`.
+#[derive(Clone, Copy)]
+enum VlenSampler {
+    Exponential(Exp<f64>),
+    Lognormal(LogNormal<f64>),
 }
 
-impl Distribution {
-    pub fn sample(&self, rng: &mut dyn RngCore) -> usize {
+impl VlenSampler {
+    fn new(distribution: config::VlenDistribution, sigma: f64) -> Option<Self> {
+        match distribution {
+            config::VlenDistribution::Fixed => None,
+            config::VlenDistribution::Exponential => {
+                Some(Self::Exponential(Exp::new(1.0).unwrap()))
+            }
+            config::VlenDistribution::Lognormal => {
+                if !sigma.is_finite() || sigma <= 0.0 {
+                    eprintln!("`vlen_sigma` must be greater than zero");
+                    std::process::exit(2);
+                }
+
+                // a mean of exp(mu + sigma^2 / 2) = 1
+                Some(Self::Lognormal(
+                    LogNormal::new(-sigma * sigma / 2.0, sigma).unwrap(),
+                ))
+            }
+        }
+    }
+
+    fn sample(&self, rng: &mut dyn RngCore) -> f64 {
         match self {
-            Self::Uniform(dist) => dist.sample(rng),
-            Self::Zipf(dist) => dist.sample(rng),
+            Self::Exponential(dist) => dist.sample(rng),
+            Self::Lognormal(dist) => dist.sample(rng),
         }
     }
 }
 
-impl Keyspace {
-    pub fn new(config: &Config, keyspace: &config::Keyspace) -> Self {
-        let value_random_bytes = estimate_random_bytes_needed(
-            keyspace.vlen().unwrap_or(0),
-            keyspace.compression_ratio(),
-        );
+/// Shifts the selected key index over time to move the hot keys.
+#[derive(Clone, Copy)]
+struct Hotspot {
+    start: Instant,
+    interval: Duration,
+    shift: usize,
+}
 
-        // nkeys must be >= 1
-        let nkeys = std::cmp::max(1, keyspace.nkeys());
-        let klen = keyspace.klen();
+/// Limits the keys which may be selected to a linearly growing prefix of the
+/// keyspace.
+#[derive(Clone, Copy)]
+struct Growth {
+    start: Instant,
+    duration: Duration,
+    start_nkeys: usize,
+}
 
-        // initialize a PRNG with the default initial seed
-        let mut rng = Xoshiro512PlusPlus::from_seed(config.general().initial_seed());
+impl Growth {
+    fn active(&self, nkeys: usize) -> usize {
+        let progress = if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.start.elapsed().as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+        };
 
-        // generate the seed for key PRNG
-        let mut raw_seed = [0_u8; 64];
-        rng.fill_bytes(&mut raw_seed);
-        let key_seed = Seed512(raw_seed);
+        let active = self.start_nkeys + ((nkeys - self.start_nkeys) as f64 * progress) as usize;
 
-        // generate the seed for inner key PRNG
-        let mut raw_seed = [0_u8; 64];
-        rng.fill_bytes(&mut raw_seed);
-        let inner_key_seed = Seed512(raw_seed);
+        // only touch the shared gauge when the keyspace has grown
+        if KEYSPACE_ACTIVE_KEYS.value() != active as i64 {
+            KEYSPACE_ACTIVE_KEYS.set(active as i64);
+        }
 
-        // we use a predictable seed to generate the keys in the keyspace
-        let mut rng = Xoshiro512PlusPlus::from_seed(key_seed);
-        let mut keys = HashSet::with_capacity(nkeys);
+        active
+    }
+}
+
+// the fewest gets in a second of the probe phase for the observed hit ratio to
+// be used to resize the keyspace
+const AUTO_SIZE_MIN_GETS: u64 = 100;
+
+/// Limits the keys which may be selected to a prefix of the keyspace which is
+/// sized during a probe phase so that gets hit at the target ratio.
+#[derive(Clone)]
+struct AutoSize {
+    start: Instant,
+    duration: Duration,
+    target: f64,
+    min_nkeys: usize,
+    state: Arc<AutoSizeState>,
+}
+
+/// The active keys and the hit and miss counts at the previous adjustment,
+/// shared by all of the views of the keyspace.
+#[derive(Default)]
+struct AutoSizeState {
+    active: AtomicUsize,
+    second: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    settled: AtomicBool,
+}
+
+impl AutoSize {
+    fn active(&self, nkeys: usize) -> usize {
+        let state = &self.state;
+        let elapsed = self.start.elapsed();
+
+        if elapsed >= self.duration {
+            if !state.settled.swap(true, Ordering::Relaxed) {
+                output!(
+                    "Keyspace: sized to {} active keys for a target hit ratio of {:.2} %",
+                    state.active.load(Ordering::Relaxed),
+                    100.0 * self.target
+                );
+            }
+
+            return state.active.load(Ordering::Relaxed);
+        }
+
+        // the first sample in each second of the probe adjusts the keyspace
+        let second = elapsed.as_secs() + 1;
+
+        if state.second.swap(second, Ordering::Relaxed) != second {
+            let hits = RESPONSE_HIT.value();
+            let misses = RESPONSE_MISS.value();
+
+            let hit_delta = hits.saturating_sub(state.hits.swap(hits, Ordering::Relaxed));
+            let miss_delta = misses.saturating_sub(state.misses.swap(misses, Ordering::Relaxed));
+
+            if hit_delta + miss_delta >= AUTO_SIZE_MIN_GETS {
+                let ratio = hit_delta as f64 / (hit_delta + miss_delta) as f64;
+
+                // fewer keys raise the hit ratio, so scale the keys by the
+                // ratio of the observed and target hit ratios, limiting each
+                // step to halving or doubling the keyspace
+                let scale = (ratio / self.target).clamp(0.5, 2.0);
+                let active = state.active.load(Ordering::Relaxed);
+                let resized = ((active as f64 * scale) as usize).clamp(self.min_nkeys, nkeys);
+
+                state.active.store(resized, Ordering::Relaxed);
+                KEYSPACE_ACTIVE_KEYS.set(resized as i64);
+            }
+        }
+
+        state.active.load(Ordering::Relaxed)
+    }
+}
+
+impl Hotspot {
+    fn offset(&self, nkeys: usize) -> usize {
+        let epoch = self.start.elapsed().as_nanos() / self.interval.as_nanos();
+
+        // only touch the shared gauge when the hotspot has moved
+        if HOTSPOT_SHIFT.value() != epoch as i64 {
+            HOTSPOT_SHIFT.set(epoch as i64);
+        }
+
+        ((epoch as usize % nkeys) * (self.shift % nkeys)) % nkeys
+    }
+}
+
+impl Keyspace {
+    pub fn new(config: &Config, keyspace: &config::Keyspace) -> Self {
+        let value_random_bytes = estimate_random_bytes_needed(
+            keyspace.vlen().unwrap_or(0),
+            keyspace.compression_ratio(),
+        );
+
+        // nkeys must be >= 1
+        let nkeys = std::cmp::max(1, keyspace.nkeys());
+        let klen = keyspace.klen();
+
+        // initialize a PRNG with the default initial seed
+        let mut rng = Xoshiro512PlusPlus::from_seed(config.general().initial_seed());
+
+        // generate the seed for key PRNG
+        let mut raw_seed = [0_u8; 64];
+        rng.fill_bytes(&mut raw_seed);
+        let key_seed = Seed512(raw_seed);
+
+        // generate the seed for inner key PRNG
+        let mut raw_seed = [0_u8; 64];
+        rng.fill_bytes(&mut raw_seed);
+        let inner_key_seed = Seed512(raw_seed);
+
+        // generate the seed for the read key PRNG
+        let mut raw_seed = [0_u8; 64];
+        rng.fill_bytes(&mut raw_seed);
+        let read_key_seed = Seed512(raw_seed);
+
+        let key_prefix = keyspace.key_prefix().as_bytes();
+
+        let shards = keyspace.shards().unwrap_or(1);
+
+        if shards == 0 || shards > nkeys {
+            eprintln!("keyspace `shards` must be between 1 and `nkeys`");
+            std::process::exit(2);
+        }
+
+        // each shard receives an equal share of the keys, with any remainder
+        // spread across the first shards
+        let mut shard_capacity: Vec<usize> = (0..shards)
+            .map(|shard| nkeys / shards + usize::from(shard < nkeys % shards))
+            .collect();
+
+        // we use a predictable seed to generate the keys in the keyspace
+        let mut rng = Xoshiro512PlusPlus::from_seed(key_seed);
+        let mut keys = HashSet::with_capacity(nkeys);
         while keys.len() < nkeys {
+            let mut key = key_prefix.to_vec();
+            key.extend((&mut rng).sample_iter(&Alphanumeric).take(klen));
+
+            // reject keys which would land on a shard that is already full
+            let shard = shard_for_key(&key, shards);
+            if shard_capacity[shard] == 0 || keys.contains(&key) {
+                continue;
+            }
+            shard_capacity[shard] -= 1;
+
+            let _ = keys.insert(key);
+        }
+        let keys: Vec<Arc<[u8]>> = keys.drain().map(|k| k.into()).collect();
+
+        let corpus = keyspace
+            .value_corpus()
+            .map(|path| load_corpus(path, keyspace.value_corpus_max_bytes()));
+
+        // the keys may instead be the names of the files in the corpus
+        let (keys, nkeys) = match &corpus {
+            Some((names, _)) if keyspace.value_corpus_keys() => {
+                let keys: Vec<Arc<[u8]>> = names
+                    .iter()
+                    .map(|name| [key_prefix, name.as_bytes()].concat().into())
+                    .collect();
+                let nkeys = keys.len();
+                (keys, nkeys)
+            }
+            _ => (keys, nkeys),
+        };
+
+        // the keys may instead be those saved by an earlier run, so that
+        // separate processes use identical keys
+        let (keys, nkeys) = match keyspace.keys_load_file() {
+            Some(_) if keyspace.value_corpus_keys() => {
+                eprintln!("keyspace `keys_load_file` cannot be used with `value_corpus_keys`");
+                std::process::exit(2);
+            }
+            Some(path) => {
+                let keys = load_keys(path);
+                let nkeys = keys.len();
+                (keys, nkeys)
+            }
+            None => (keys, nkeys),
+        };
+
+        if let Some(path) = keyspace.keys_save_file() {
+            save_keys(path, &keys);
+        }
+
+        let key_shards = if keyspace.shards().is_some() {
+            keys.iter().map(|k| shard_for_key(k, shards)).collect()
+        } else {
+            Vec::new()
+        };
+        let shard_requests = Arc::new((0..shards).map(|_| AtomicU64::new(0)).collect());
+        let key_dist = key_generator(
+            keyspace.key_distribution(),
+            nkeys,
+            rng.next_u64(),
+            keyspace.key_replay_file().map(load_replay),
+        );
+
+        // optionally, generate a disjoint set of keys for reads
+        let read_keys = keyspace.read_nkeys().map(|read_nkeys| {
+            let read_nkeys = std::cmp::max(1, read_nkeys);
+
+            if keyspace.key_distribution() == config::Distribution::Replay {
+                eprintln!("the `replay` key distribution cannot be used with `read_nkeys`");
+                std::process::exit(2);
+            }
+
+            if keyspace.read_key_prefix() == keyspace.key_prefix() {
+                eprintln!("keyspace `read_key_prefix` must differ from the `key_prefix`");
+                std::process::exit(2);
+            }
+
+            let read_key_prefix = keyspace.read_key_prefix().as_bytes();
+
+            let mut rng = Xoshiro512PlusPlus::from_seed(read_key_seed);
+            let mut keys = HashSet::with_capacity(read_nkeys);
+            while keys.len() < read_nkeys {
+                let mut key = read_key_prefix.to_vec();
+                key.extend((&mut rng).sample_iter(&Alphanumeric).take(klen));
+                let _ = keys.insert(key);
+            }
+            let keys: Vec<Arc<[u8]>> = keys.drain().map(|k| k.into()).collect();
+
+            let key_dist = key_generator(
+                keyspace.key_distribution(),
+                read_nkeys,
+                rng.next_u64(),
+                None,
+            );
+
+            (keys, key_dist)
+        });
+
+        // reads may instead target keys loaded from a file, such as the keys
+        // already in the store
+        let read_keys = match keyspace.read_keys_file() {
+            Some(_) if keyspace.read_nkeys().is_some() => {
+                eprintln!("keyspace `read_keys_file` cannot be used with `read_nkeys`");
+                std::process::exit(2);
+            }
+            Some(_) if keyspace.key_distribution() == config::Distribution::Replay => {
+                eprintln!("the `replay` key distribution cannot be used with `read_keys_file`");
+                std::process::exit(2);
+            }
+            Some(path) => {
+                let keys = load_keys(path);
+                let key_dist = key_generator(
+                    keyspace.key_distribution(),
+                    keys.len(),
+                    rng.next_u64(),
+                    None,
+                );
+
+                Some((keys, key_dist))
+            }
+            None => read_keys,
+        };
+
+        let nkeys = keyspace.inner_keys_nkeys().unwrap_or(1);
+        let klen = keyspace.inner_keys_klen().unwrap_or(1);
+
+        // we use a predictable seed to generate the keys in the keyspace
+        let mut rng = Xoshiro512PlusPlus::from_seed(inner_key_seed);
+        let mut inner_keys = HashSet::with_capacity(nkeys);
+        while inner_keys.len() < nkeys {
             let key = (&mut rng)
                 .sample_iter(&Alphanumeric)
                 .take(klen)
                 .collect::<Vec<u8>>();
-            let _ = keys.insert(key);
+            let _ = inner_keys.insert(key);
+        }
+        let inner_keys: Vec<Arc<[u8]>> = inner_keys.drain().map(|k| k.into()).collect();
+        let inner_key_dist = key_generator(config::Distribution::Uniform, nkeys, 0, None);
+
+        let mut commands = Vec::new();
+        let mut command_weights = Vec::new();
+
+        if keyspace.commands().is_empty() {
+            eprintln!("keyspace must specify at least one command");
+            std::process::exit(2);
         }
-        let keys = keys.drain().map(|k| k.into()).collect();
-        let key_dist = match keyspace.key_distribution() {
-            config::Distribution::Uniform => Distribution::Uniform(Uniform::new(0, nkeys)),
-            config::Distribution::Zipf => {
-                Distribution::Zipf(ZipfDistribution::new(nkeys, 1.0).unwrap())
+
+        for command in keyspace.commands() {
+            commands.push(command.clone());
+            command_weights.push(command.weight());
+
+            // validate that the keyspace is adaquately specified for the given
+            // verb
+
+            // commands that set generated values need a `vlen`
+            if keyspace.vlen().is_none()
+                && keyspace.value_corpus().is_none()
+                && keyspace.key_classes().is_empty()
+                && command.vlen().is_none()
+                && keyspace.vkind().sized()
+                && matches!(
+                    command.verb(),
+                    Verb::Set | Verb::HashSet | Verb::TtlCheck | Verb::ChurnWrite
+                )
+            {
+                eprintln!(
+                    "verb: {:?} requires that the keyspace has a `vlen` set when `vkind` is `bytes`, `json`, or `protobuf`",
+                    command.verb()
+                );
+                std::process::exit(2);
+            }
+
+            // populating on miss generates values for gets
+            if keyspace.populate_on_miss()
+                && keyspace.vlen().is_none()
+                && keyspace.value_corpus().is_none()
+                && keyspace.key_classes().is_empty()
+                && keyspace.vkind().sized()
+                && command.verb() == Verb::Get
+            {
+                eprintln!(
+                    "`populate_on_miss` requires that the keyspace has a `vlen` set when `vkind` is `bytes`, `json`, or `protobuf`"
+                );
+                std::process::exit(2);
+            }
+
+            // cardinality must always be > 0
+            if command.cardinality() == 0 {
+                eprintln!("cardinality must not be zero",);
+                std::process::exit(2);
+            }
+
+            // not all commands support cardinality > 1
+            if command.cardinality() > 1 && !command.verb().supports_cardinality() {
+                eprintln!(
+                    "verb: {:?} requires that `cardinality` is set to `1`",
+                    command.verb()
+                );
+                std::process::exit(2);
+            }
+
+            if command.start().is_some() && !command.verb().supports_start() {
+                eprintln!(
+                    "verb: {:?} does not support the `start` argument",
+                    command.verb()
+                );
+            }
+
+            if command.end().is_some() && !command.verb().supports_end() {
+                eprintln!(
+                    "verb: {:?} does not support the `end` argument",
+                    command.verb()
+                );
+            }
+
+            if command.by_score() && !command.verb().supports_by_score() {
+                eprintln!(
+                    "verb: {:?} does not support the `by_score` option",
+                    command.verb()
+                );
+            }
+
+            if (command.wait().is_some() || command.grace() > Duration::ZERO)
+                && !command.verb().supports_wait()
+            {
+                eprintln!(
+                    "verb: {:?} does not support the `wait` or `grace` arguments",
+                    command.verb()
+                );
+            }
+
+            if command.expected_value() != config::ExpectedValue::default()
+                && !command.verb().supports_expected_value()
+            {
+                eprintln!(
+                    "verb: {:?} does not support the `expected_value` argument",
+                    command.verb()
+                );
+            }
+
+            if command.ttl().is_some() && !command.verb().supports_ttl() {
+                eprintln!(
+                    "verb: {:?} does not support the `ttl` argument",
+                    command.verb()
+                );
+            }
+
+            if command.mutation_bytes().is_some() && !command.verb().supports_mutation_bytes() {
+                eprintln!(
+                    "verb: {:?} does not support the `mutation_bytes` argument",
+                    command.verb()
+                );
+            }
+
+            if command.batch_order() != BatchOrder::Sampled
+                && !command.verb().supports_batch_order()
+            {
+                eprintln!(
+                    "verb: {:?} does not support the `batch_order` argument",
+                    command.verb()
+                );
+            }
+
+            if command.overrides_vlen() && !command.verb().supports_vlen() {
+                eprintln!(
+                    "verb: {:?} does not generate values and does not support the `vlen` arguments",
+                    command.verb()
+                );
+            }
+
+            if command.vlen_max() == Some(0) {
+                eprintln!("command `vlen_max` must be >= 1");
+                std::process::exit(2);
+            }
+
+            if command.mutation_bytes() == Some(0) {
+                eprintln!("mutation_bytes must be >= 1");
+                std::process::exit(2);
+            }
+
+            if command.verb() == Verb::TtlCheck && keyspace.ttl().is_none() {
+                eprintln!(
+                    "verb: {:?} requires that the keyspace has a `ttl` set",
+                    command.verb()
+                );
+                std::process::exit(2);
+            }
+
+            if command.verb() == Verb::Touch && command.ttl().is_none() && keyspace.ttl().is_none()
+            {
+                eprintln!(
+                    "verb: {:?} requires a `ttl` for the command or the keyspace",
+                    command.verb()
+                );
+                std::process::exit(2);
+            }
+
+            if command.truncate().is_some() {
+                // truncate must be >= 1
+                if command.truncate().unwrap() == 0 {
+                    eprintln!("truncate must be >= 1",);
+                    std::process::exit(2);
+                }
+
+                // not all commands support truncate
+                if !command.verb().supports_truncate() {
+                    eprintln!("verb: {:?} does not support truncate", command.verb());
+                    std::process::exit(2);
+                }
+            }
+
+            if command.verb().needs_inner_key()
+                && (keyspace.inner_keys_nkeys().is_none() || keyspace.inner_keys_klen().is_none())
+            {
+                eprintln!(
+                    "verb: {:?} requires that `inner_key_klen` and `inner_key_nkeys` are set",
+                    command.verb()
+                );
+                std::process::exit(2);
             }
+        }
+
+        let command_dist = WeightedAliasIndex::new(command_weights).unwrap();
+
+        if let (Some(vlen), Some(max)) = (keyspace.vlen(), keyspace.max_value_bytes()) {
+            if vlen > max {
+                warn!(
+                    "keyspace `vlen` of {vlen} exceeds `max_value_bytes`, values will be clamped to {max} bytes"
+                );
+            }
+        }
+
+        let value_header = keyspace.value_header();
+
+        if keyspace.write_before_read() {
+            if keyspace.read_nkeys().is_some() || keyspace.read_keys_file().is_some() {
+                eprintln!(
+                    "`write_before_read` cannot be used with `read_nkeys` or `read_keys_file`"
+                );
+                std::process::exit(2);
+            }
+
+            if keyspace.vlen().is_none()
+                && keyspace.value_corpus().is_none()
+                && keyspace.key_classes().is_empty()
+                && keyspace.vkind().sized()
+            {
+                eprintln!(
+                    "`write_before_read` requires that the keyspace has a `vlen` set when `vkind` is `bytes`, `json`, or `protobuf`"
+                );
+                std::process::exit(2);
+            }
+        }
+
+        if keyspace
+            .delete_recent_bias()
+            .is_some_and(|bias| !(0.0..=1.0).contains(&bias))
+        {
+            eprintln!("keyspace `delete_recent_bias` must be in the range 0.0 - 1.0");
+            std::process::exit(2);
+        }
+
+        // a command with its own value length writes values of a different
+        // length than the gets for the same keys would expect
+        if keyspace.verify_value_length()
+            && keyspace
+                .commands()
+                .iter()
+                .any(|command| command.overrides_vlen())
+        {
+            eprintln!("`verify_value_length` cannot be used with per-command `vlen`");
+            std::process::exit(2);
+        }
+
+        if let Some(chunk_size) = keyspace.chunk_size() {
+            if chunk_size == 0 {
+                eprintln!("keyspace `chunk_size` must be >= 1");
+                std::process::exit(2);
+            }
+
+            // the number of chunks read by a get is determined by the value
+            // length, so it must be fixed
+            if keyspace.vkind() != ValueKind::Bytes
+                || keyspace.value_corpus().is_some()
+                || keyspace
+                    .commands()
+                    .iter()
+                    .any(|command| command.overrides_vlen())
+            {
+                eprintln!(
+                    "keyspace `chunk_size` requires fixed length `bytes` values and cannot be used with a value corpus or per-command `vlen`"
+                );
+                std::process::exit(2);
+            }
+        }
+
+        if let Some(bytes) = keyspace.value_sequence_bytes() {
+            if !(1..=8).contains(&bytes) {
+                eprintln!("keyspace `value_sequence_bytes` must be in the range 1 - 8");
+                std::process::exit(2);
+            }
+
+            let header_len = value_header.as_ref().map_or(0, |header| header.len());
+
+            if keyspace.vkind() != ValueKind::Bytes
+                || keyspace.value_corpus().is_some()
+                || keyspace.chunk_size().is_some()
+                || keyspace.vlen().unwrap_or(0) < header_len + bytes
+            {
+                eprintln!(
+                    "keyspace `value_sequence_bytes` requires `bytes` values with room for the sequence number after any `value_header` and cannot be used with a value corpus or `chunk_size`"
+                );
+                std::process::exit(2);
+            }
+
+            // the keys of each class are not tracked
+            if !keyspace.key_classes().is_empty() {
+                eprintln!("keyspace `value_sequence_bytes` cannot be used with `key_classes`");
+                std::process::exit(2);
+            }
+
+            // sequence numbers are assigned as requests are generated, so the
+            // sets must also be sent in that order
+            let in_order = config.workload().threads() == 1
+                && config
+                    .client()
+                    .is_none_or(|client| client.poolsize() == 1 && client.concurrency() == 1);
+
+            if !in_order {
+                eprintln!(
+                    "keyspace `value_sequence_bytes` requires a single workload thread and a client `poolsize` and `concurrency` of 1"
+                );
+                std::process::exit(2);
+            }
+        }
+
+        if let Some(target) = keyspace.target_hit_ratio() {
+            if !(target > 0.0 && target <= 1.0) {
+                eprintln!("keyspace `target_hit_ratio` must be in the range (0.0, 1.0]");
+                std::process::exit(2);
+            }
+
+            if keyspace.growth_start_nkeys().is_some() {
+                eprintln!(
+                    "keyspace `target_hit_ratio` cannot be combined with `growth_start_nkeys`"
+                );
+                std::process::exit(2);
+            }
+
+            if keyspace.target_hit_ratio_min_nkeys() > keyspace.nkeys() {
+                eprintln!("keyspace `target_hit_ratio_min_nkeys` must not be greater than `nkeys`");
+                std::process::exit(2);
+            }
+
+            // the observed hit ratio is that of all gets, so it only
+            // describes this keyspace if no other keyspace issues gets
+            if config
+                .workload()
+                .keyspaces()
+                .iter()
+                .filter(|other| !std::ptr::eq(*other, keyspace))
+                .any(|other| {
+                    other
+                        .commands()
+                        .iter()
+                        .any(|command| command.verb() == Verb::Get)
+                })
+            {
+                eprintln!(
+                    "keyspace `target_hit_ratio` requires that no other keyspace issues gets"
+                );
+                std::process::exit(2);
+            }
+        }
+
+        if !keyspace.record_fields().is_empty() {
+            let len = client::RecordLayout::new(keyspace.record_fields()).size();
+
+            if keyspace.vkind() != ValueKind::Bytes
+                || keyspace.value_corpus().is_some()
+                || keyspace.chunk_size().is_some()
+                || value_header.is_some()
+                || keyspace.value_sequence_bytes().is_some()
+                || keyspace.value_hmac()
+                || VlenSampler::new(keyspace.vlen_distribution(), keyspace.vlen_sigma()).is_some()
+                || keyspace
+                    .commands()
+                    .iter()
+                    .any(|command| command.overrides_vlen())
+            {
+                eprintln!(
+                    "keyspace `record_fields` requires `bytes` values and cannot be used with a value corpus, `chunk_size`, `value_header`, `value_sequence_bytes`, `value_hmac`, `vlen_distribution`, or per-command `vlen`"
+                );
+                std::process::exit(2);
+            }
+
+            if keyspace.vlen() != Some(len) {
+                eprintln!(
+                    "keyspace `record_fields` requires a `vlen` of {len}, the sum of the field sizes plus {} bytes for each field",
+                    client::RecordLayout::PREFIX_LEN
+                );
+                std::process::exit(2);
+            }
+        }
+
+        if keyspace.value_hmac() {
+            let header_len = value_header.as_ref().map_or(0, |header| header.len());
+            let sequence_len = keyspace.value_sequence_bytes().unwrap_or(0);
+            let min_vlen = header_len + sequence_len + client::ValueHmac::LEN;
+
+            // each of the value lengths which may be used must have room for
+            // the HMAC
+            let fits = match keyspace.vlen() {
+                Some(vlen) => vlen >= min_vlen,
+                None => !keyspace.key_classes().is_empty(),
+            } && keyspace
+                .key_classes()
+                .iter()
+                .all(|class| class.vlen() >= min_vlen)
+                && keyspace
+                    .commands()
+                    .iter()
+                    .all(|command| command.vlen().is_none_or(|vlen| vlen >= min_vlen));
+
+            if keyspace.vkind() != ValueKind::Bytes
+                || keyspace.value_corpus().is_some()
+                || keyspace.chunk_size().is_some()
+                || !fits
+            {
+                eprintln!(
+                    "keyspace `value_hmac` requires `bytes` values with room for the 32 byte HMAC after any `value_header` and sequence number and cannot be used with a value corpus or `chunk_size`"
+                );
+                std::process::exit(2);
+            }
+
+            // sampled value lengths may be too short to hold the HMAC
+            if VlenSampler::new(keyspace.vlen_distribution(), keyspace.vlen_sigma()).is_some()
+                || keyspace.commands().iter().any(|command| {
+                    command.vlen_distribution().is_some() || command.vlen_sigma().is_some()
+                })
+            {
+                eprintln!("keyspace `value_hmac` cannot be used with `vlen_distribution`");
+                std::process::exit(2);
+            }
+
+            // updates rewrite part of the value without stamping a new HMAC
+            if keyspace
+                .commands()
+                .iter()
+                .any(|command| command.verb() == Verb::Update)
+            {
+                eprintln!("keyspace `value_hmac` cannot be used with `update` commands");
+                std::process::exit(2);
+            }
+        }
+
+        // structured values are generated from scratch, so there is nothing
+        // to place a header or random bytes into
+        if matches!(keyspace.vkind(), ValueKind::Json | ValueKind::Protobuf)
+            && (value_header.is_some()
+                || keyspace.value_corpus().is_some()
+                || keyspace.compression_ratio() > 1.0
+                || !keyspace.compression_classes().is_empty())
+        {
+            eprintln!(
+                "`json` and `protobuf` values cannot be used with a `value_header`, value corpus, or compression ratio"
+            );
+            std::process::exit(2);
+        }
+
+        let vlen_sampler = VlenSampler::new(keyspace.vlen_distribution(), keyspace.vlen_sigma());
+
+        if vlen_sampler.is_some() {
+            if keyspace.vkind() != ValueKind::Bytes
+                || keyspace.vlen().unwrap_or(0) == 0
+                || keyspace.value_corpus().is_some()
+            {
+                eprintln!(
+                    "keyspace `vlen_distribution` requires `bytes` values with a non-zero `vlen` and cannot be used with a value corpus"
+                );
+                std::process::exit(2);
+            }
+
+            if keyspace.verify_value_length() || keyspace.chunk_size().is_some() {
+                eprintln!(
+                    "keyspace `vlen_distribution` cannot be used with `verify_value_length` or `chunk_size`"
+                );
+                std::process::exit(2);
+            }
+        }
+
+        if keyspace.vlen_max() == Some(0) {
+            eprintln!("keyspace `vlen_max` must be >= 1");
+            std::process::exit(2);
+        }
+
+        // the header is written into every generated value, so each of the
+        // value lengths which may be used must have room for it
+        if let Some(header) = value_header.as_ref() {
+            if keyspace.vkind() == ValueKind::Bytes && corpus.is_none() {
+                match keyspace.vlen() {
+                    Some(vlen) if header.len() > vlen => {
+                        eprintln!("value_header must not be longer than the keyspace `vlen`");
+                        std::process::exit(2);
+                    }
+                    None if keyspace.key_classes().is_empty() => {
+                        eprintln!("value_header requires that the keyspace has a `vlen` set");
+                        std::process::exit(2);
+                    }
+                    _ => {}
+                }
+
+                if keyspace
+                    .key_classes()
+                    .iter()
+                    .any(|class| header.len() > class.vlen())
+                {
+                    eprintln!("value_header must not be longer than the `vlen` of any key class");
+                    std::process::exit(2);
+                }
+
+                if keyspace
+                    .commands()
+                    .iter()
+                    .any(|command| command.vlen().is_some_and(|vlen| header.len() > vlen))
+                {
+                    eprintln!("value_header must not be longer than any per-command `vlen`");
+                    std::process::exit(2);
+                }
+            }
+        }
+
+        let key_count = keys.len();
+
+        let sequences = keyspace.value_sequence_bytes().map(|_| {
+            Arc::new(
+                keys.iter()
+                    .map(|key| (key.clone(), Arc::default()))
+                    .collect(),
+            )
+        });
+
+        let mut result = Self {
+            keys,
+            key_dist,
+            commands,
+            command_dist,
+            inner_keys,
+            inner_key_dist,
+            vlen: keyspace.vlen().unwrap_or(0),
+            vlen_set: keyspace.vlen().is_some(),
+            vlen_sampler,
+            vlen_max: keyspace.vlen_max(),
+            vkind: keyspace.vkind(),
+            value_random_bytes,
+            ttl: keyspace.ttl(),
+            populate_on_miss: keyspace.populate_on_miss(),
+            key_shards,
+            shard_requests,
+            max_value_bytes: keyspace.max_value_bytes(),
+            read_keyspace: None,
+            command_keyspaces: Vec::new(),
+            sequences,
+            sequence_bytes: keyspace.value_sequence_bytes().unwrap_or(0),
+            hmac: keyspace
+                .value_hmac()
+                .then(|| client::ValueHmac::new(hmac_secret(config))),
+            record: (!keyspace.record_fields().is_empty())
+                .then(|| client::RecordLayout::new(keyspace.record_fields())),
+            value_header: value_header.map(|header| header.into()),
+            corpus: corpus.map(|(_, values)| Arc::new(values)),
+            key_classes: Vec::new(),
+            key_class_names: Vec::new(),
+            key_class_dist: None,
+            key_class_requests: Arc::new(Vec::new()),
+            compression_ratios: Vec::new(),
+            compression_class_random_bytes: Vec::new(),
+            compression_class_names: Vec::new(),
+            compression_class_dist: None,
+            compression_class_values: Arc::new(Vec::new()),
+            id: KEYSPACE_ID.fetch_add(1, Ordering::Relaxed),
+            write_before_read: keyspace.write_before_read(),
+            delete_recent_bias: keyspace.delete_recent_bias(),
+            verify_value_length: keyspace.verify_value_length(),
+            chunk_size: keyspace.chunk_size(),
+            preload: keyspace
+                .preload_keys_file()
+                .map(|path| Arc::new(load_preload(path))),
+            growth: keyspace.growth_start_nkeys().map(|start_nkeys| Growth {
+                start: Instant::now(),
+                duration: keyspace
+                    .growth_duration()
+                    .unwrap_or_else(|| config.general().duration()),
+                start_nkeys: start_nkeys.clamp(1, key_count),
+            }),
+            hotspot: keyspace.hotspot_shift_interval().map(|interval| Hotspot {
+                start: Instant::now(),
+                interval,
+                shift: keyspace.hotspot_shift_keys(),
+            }),
+            auto_size: keyspace.target_hit_ratio().map(|target| AutoSize {
+                start: Instant::now(),
+                duration: keyspace.target_hit_ratio_duration(),
+                target,
+                min_nkeys: keyspace.target_hit_ratio_min_nkeys().clamp(1, key_count),
+                state: Arc::new(AutoSizeState {
+                    active: AtomicUsize::new(key_count),
+                    ..Default::default()
+                }),
+            }),
+        };
+
+        if !keyspace.compression_classes().is_empty() {
+            let mut weights = Vec::new();
+
+            for class in keyspace.compression_classes() {
+                result.compression_ratios.push(class.compression_ratio());
+                result
+                    .compression_class_names
+                    .push(class.name().to_string());
+                weights.push(class.weight());
+            }
+
+            result.set_vlen(result.vlen, keyspace.compression_ratio());
+
+            result.compression_class_dist =
+                Some(WeightedAliasIndex::new(weights).unwrap_or_else(|e| {
+                    eprintln!("invalid compression class weights: {e}");
+                    std::process::exit(2);
+                }));
+            result.compression_class_values = Arc::new(
+                (0..result.compression_ratios.len())
+                    .map(|_| AtomicU64::new(0))
+                    .collect(),
+            );
+        }
+
+        // key classes use a copy of this keyspace with the class prefix and
+        // value length applied
+        if !keyspace.key_classes().is_empty() {
+            if keyspace.read_nkeys().is_some() || keyspace.read_keys_file().is_some() {
+                eprintln!(
+                    "keyspace `key_classes` cannot be used with `read_nkeys` or `read_keys_file`"
+                );
+                std::process::exit(2);
+            }
+
+            if result
+                .commands
+                .iter()
+                .any(|c| c.key_prefix().is_some() || c.overrides_vlen())
+            {
+                eprintln!(
+                    "keyspace `key_classes` cannot be used with per-command `key_prefix` or `vlen`"
+                );
+                std::process::exit(2);
+            }
+
+            let mut weights = Vec::new();
+
+            for class in keyspace.key_classes() {
+                if class.vlen() == 0 {
+                    eprintln!("key class `vlen` must be >= 1: {}", class.prefix());
+                    std::process::exit(2);
+                }
+
+                let mut class_keyspace = result.clone();
+
+                class_keyspace.keys = result
+                    .keys
+                    .iter()
+                    .map(|key| [class.prefix().as_bytes(), &key[..]].concat().into())
+                    .collect();
+                class_keyspace.key_shards = Vec::new();
+                class_keyspace.set_vlen(class.vlen(), keyspace.compression_ratio());
+
+                result.key_classes.push(Arc::new(class_keyspace));
+                result.key_class_names.push(class.prefix().to_string());
+                weights.push(class.weight());
+            }
+
+            result.key_class_dist = Some(WeightedAliasIndex::new(weights).unwrap_or_else(|e| {
+                eprintln!("invalid key class weights: {e}");
+                std::process::exit(2);
+            }));
+            result.key_class_requests = Arc::new(
+                (0..result.key_classes.len())
+                    .map(|_| AtomicU64::new(0))
+                    .collect(),
+            );
+        }
+
+        // the expected value of a conditional delete is derived from the
+        // values of the keyspace
+        for command in result
+            .commands
+            .iter()
+            .filter(|command| command.verb() == Verb::DeleteIf)
+        {
+            match command.expected_value() {
+                config::ExpectedValue::Length if result.expected_vlen().is_none() => {
+                    eprintln!(
+                        "verb: DeleteIf with `expected_value` `length` requires a fixed `vlen` and `vkind` `bytes`"
+                    );
+                    std::process::exit(2);
+                }
+                config::ExpectedValue::Header if result.value_header.is_none() => {
+                    eprintln!(
+                        "verb: DeleteIf with `expected_value` `header` requires a keyspace `value_header`"
+                    );
+                    std::process::exit(2);
+                }
+                _ => {}
+            }
+        }
+
+        // reads use a copy of this keyspace with the read keys swapped in
+        if let Some((keys, key_dist)) = read_keys {
+            let mut read_keyspace = result.clone();
+            read_keyspace.keys = keys;
+            read_keyspace.key_dist = key_dist;
+            read_keyspace.key_shards = Vec::new();
+
+            result.read_keyspace = Some(Arc::new(read_keyspace));
+        }
+
+        // commands with a key prefix or value length use a copy of the keyspace
+        // with those applied
+        let mut command_keyspaces = Vec::with_capacity(result.commands.len());
+        for command in &result.commands {
+            if command.key_prefix().is_none() && !command.overrides_vlen() {
+                command_keyspaces.push(None);
+                continue;
+            }
+
+            let mut command_keyspace = result.clone();
+
+            if let Some(prefix) = command.key_prefix() {
+                command_keyspace.keys = result
+                    .keys
+                    .iter()
+                    .map(|key| [prefix.as_bytes(), &key[..]].concat().into())
+                    .collect();
+                command_keyspace.key_shards = Vec::new();
+            }
+
+            if let Some(vlen) = command.vlen() {
+                command_keyspace.set_vlen(vlen, keyspace.compression_ratio());
+            }
+
+            if command.vlen_distribution().is_some() || command.vlen_sigma().is_some() {
+                command_keyspace.vlen_sampler = VlenSampler::new(
+                    command
+                        .vlen_distribution()
+                        .unwrap_or(keyspace.vlen_distribution()),
+                    command.vlen_sigma().unwrap_or(keyspace.vlen_sigma()),
+                );
+
+                if command_keyspace.vlen_sampler.is_some()
+                    && (command_keyspace.vkind != ValueKind::Bytes
+                        || command_keyspace.vlen == 0
+                        || keyspace.value_corpus().is_some())
+                {
+                    eprintln!(
+                        "command `vlen_distribution` requires `bytes` values with a non-zero `vlen` and cannot be used with a value corpus"
+                    );
+                    std::process::exit(2);
+                }
+            }
+
+            if let Some(max) = command.vlen_max() {
+                command_keyspace.vlen_max = Some(max);
+            }
+
+            command_keyspaces.push(Some(Arc::new(command_keyspace)));
+        }
+        result.command_keyspaces = command_keyspaces;
+
+        result
+    }
+
+    /// Sets the value length and updates the number of random bytes needed to
+    /// match the compression ratio of the keyspace and of each compressibility
+    /// class.
+    fn set_vlen(&mut self, vlen: usize, compression_ratio: f64) {
+        self.vlen = vlen;
+        self.vlen_set = true;
+        self.value_random_bytes = estimate_random_bytes_needed(vlen, compression_ratio);
+        self.compression_class_random_bytes = self
+            .compression_ratios
+            .iter()
+            .map(|ratio| estimate_random_bytes_needed(vlen, *ratio))
+            .collect();
+    }
+
+    pub fn sample(&self, rng: &mut dyn RngCore) -> Arc<[u8]> {
+        let mut index = self.key_dist.next_key(rng);
+        if let Some(growth) = self.growth {
+            // scale the index so the distribution is preserved over the active
+            // keys
+            index = index * growth.active(self.keys.len()) / self.keys.len();
+        }
+        if let Some(auto_size) = &self.auto_size {
+            index = index * auto_size.active(self.keys.len()) / self.keys.len();
+        }
+        if let Some(hotspot) = self.hotspot {
+            index = (index + hotspot.offset(self.keys.len())) % self.keys.len();
+        }
+        if let Some(shard) = self.key_shards.get(index) {
+            self.shard_requests[*shard].fetch_add(1, Ordering::Relaxed);
+        }
+        let key = self.keys[index].clone();
+        let _ = KEY_BYTES.increment(key.len() as u64);
+        key
+    }
+
+    /// Returns the number of requests for each key class by prefix, if the
+    /// keyspace has key classes.
+    pub fn key_class_requests(&self) -> Option<Vec<(&str, u64)>> {
+        if self.key_classes.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.key_class_names
+                .iter()
+                .zip(self.key_class_requests.iter())
+                .map(|(name, count)| (name.as_str(), count.load(Ordering::Relaxed)))
+                .collect(),
+        )
+    }
+
+    /// Returns the number of values generated for each compressibility class by
+    /// name, if the keyspace has compressibility classes.
+    pub fn compression_class_values(&self) -> Option<Vec<(&str, u64)>> {
+        if self.compression_ratios.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.compression_class_names
+                .iter()
+                .zip(self.compression_class_values.iter())
+                .map(|(name, count)| (name.as_str(), count.load(Ordering::Relaxed)))
+                .collect(),
+        )
+    }
+
+    /// Returns the number of requests sent to each shard, if the keyspace was
+    /// generated with shards.
+    pub fn shard_requests(&self) -> Option<Vec<u64>> {
+        if self.key_shards.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.shard_requests
+                .iter()
+                .map(|count| count.load(Ordering::Relaxed))
+                .collect(),
+        )
+    }
+
+    pub fn sample_inner(&self, rng: &mut dyn RngCore) -> Arc<[u8]> {
+        let index = self.inner_key_dist.next_key(rng);
+        self.inner_keys[index].clone()
+    }
+
+    pub fn gen_value(&self, rng: &mut dyn RngCore) -> Vec<u8> {
+        let value = match self.vkind {
+            ValueKind::I64 => format!("{}", rng.gen::<i64>()).into_bytes(),
+            ValueKind::Bytes if self.corpus.is_some() => {
+                let corpus = self.corpus.as_ref().unwrap();
+                corpus[rng.gen_range(0..corpus.len())].to_vec()
+            }
+            ValueKind::Bytes => {
+                let vlen = self.clamped_vlen(rng);
+
+                let mut buf = vec![0_u8; vlen];
+
+                // the header is part of the value length and precedes the
+                // random portion of the value
+                let header_len = match &self.value_header {
+                    Some(header) => {
+                        let len = std::cmp::min(header.len(), vlen);
+                        buf[0..len].copy_from_slice(&header[0..len]);
+                        len
+                    }
+                    None => 0,
+                };
+
+                let random_bytes = match &self.compression_class_dist {
+                    Some(dist) => {
+                        let class = dist.sample(rng);
+                        self.compression_class_values[class].fetch_add(1, Ordering::Relaxed);
+                        self.compression_class_random_bytes[class]
+                    }
+                    None => self.value_random_bytes,
+                };
+
+                // the random bytes are scaled with sampled value lengths to
+                // keep the compression ratio
+                let random_bytes = if self.vlen_sampler.is_some() {
+                    (random_bytes as u128 * vlen as u128 / self.vlen as u128) as usize
+                } else {
+                    random_bytes
+                };
+
+                let random_end = std::cmp::min(header_len + random_bytes, vlen);
+                rng.fill(&mut buf[header_len..random_end]);
+
+                if let Some(record) = &self.record {
+                    record.frame(&mut buf);
+                }
+
+                buf
+            }
+            ValueKind::Json => json_value(rng, self.clamped_vlen(rng)),
+            ValueKind::Protobuf => protobuf_value(rng, self.clamped_vlen(rng)),
+        };
+
+        let _ = VALUE_BYTES.increment(value.len() as u64);
+
+        value
+    }
+
+    // samples the value length, limited by any `max_value_bytes`
+    fn clamped_vlen(&self, rng: &mut dyn RngCore) -> usize {
+        let vlen = self.sample_vlen(rng);
+
+        match self.max_value_bytes {
+            Some(max) if vlen > max => {
+                VALUE_CLAMPED.increment();
+                max
+            }
+            _ => vlen,
+        }
+    }
+
+    // the sequence number follows the value header
+    fn sequence_offset(&self) -> usize {
+        self.value_header.as_ref().map_or(0, |header| header.len())
+    }
+
+    /// Writes the next sequence number for the key into the value, if
+    /// sequence numbers are enabled.
+    fn stamp_sequence(&self, key: &[u8], value: &mut [u8]) {
+        let Some(sequence) = self.sequences.as_ref().and_then(|s| s.get(key)) else {
+            return;
+        };
+
+        let offset = self.sequence_offset();
+
+        // sampled value lengths may be too short to hold the sequence number
+        let Some(field) = value.get_mut(offset..offset + self.sequence_bytes) else {
+            return;
+        };
+
+        let next = sequence.next();
+
+        for (i, byte) in field.iter_mut().enumerate() {
+            *byte = (next >> (8 * (self.sequence_bytes - 1 - i))) as u8;
+        }
+    }
+
+    /// Writes the HMAC for the key into the end of the value, if value HMACs
+    /// are enabled. This must follow any other change to the value.
+    fn stamp_hmac(&self, key: &[u8], value: &mut [u8]) {
+        if let Some(hmac) = &self.hmac {
+            hmac.stamp(key, value);
+        }
+    }
+
+    /// Returns the check for the sequence number in values read from the key,
+    /// if sequence numbers are enabled.
+    fn sequence_check(&self, key: &[u8]) -> Option<client::SequenceCheck> {
+        let sequence = self.sequences.as_ref()?.get(key)?;
+
+        Some(client::SequenceCheck {
+            sequence: sequence.clone(),
+            offset: self.sequence_offset(),
+            bytes: self.sequence_bytes,
+        })
+    }
+
+    /// Returns the length of the next generated value.
+    fn sample_vlen(&self, rng: &mut dyn RngCore) -> usize {
+        let Some(sampler) = &self.vlen_sampler else {
+            return self.vlen;
         };
 
-        let nkeys = keyspace.inner_keys_nkeys().unwrap_or(1);
-        let klen = keyspace.inner_keys_klen().unwrap_or(1);
+        // a sample which rounds to zero is clamped so that the value is
+        // never empty
+        let vlen = std::cmp::max((sampler.sample(rng) * self.vlen as f64).round() as usize, 1);
 
-        // we use a predictable seed to generate the keys in the keyspace
-        let mut rng = Xoshiro512PlusPlus::from_seed(inner_key_seed);
-        let mut inner_keys = HashSet::with_capacity(nkeys);
-        while inner_keys.len() < nkeys {
-            let key = (&mut rng)
-                .sample_iter(&Alphanumeric)
-                .take(klen)
-                .collect::<Vec<u8>>();
-            let _ = inner_keys.insert(key);
+        match self.vlen_max {
+            Some(max) => std::cmp::min(vlen, max),
+            None => vlen,
         }
-        let inner_keys: Vec<Arc<[u8]>> = inner_keys.drain().map(|k| k.into()).collect();
-        let inner_key_dist = Distribution::Uniform(Uniform::new(0, nkeys));
+    }
 
-        let mut commands = Vec::new();
-        let mut command_weights = Vec::new();
+    /// Returns the length of the payload portion of a generated value, which
+    /// excludes the value header.
+    pub fn logical_len(&self, value: &[u8]) -> usize {
+        match &self.value_header {
+            Some(header) if self.corpus.is_none() && self.vkind == ValueKind::Bytes => {
+                value.len() - std::cmp::min(header.len(), value.len())
+            }
+            _ => value.len(),
+        }
+    }
 
-        for command in keyspace.commands() {
-            commands.push(*command);
-            command_weights.push(command.weight());
+    /// Returns the number of chunks each value is split into, if values in
+    /// this keyspace are chunked.
+    fn chunks(&self) -> Option<usize> {
+        let chunk_size = self.chunk_size?;
+        let vlen = self.expected_vlen()?;
 
-            // validate that the keyspace is adaquately specified for the given
-            // verb
+        if vlen <= chunk_size {
+            return None;
+        }
 
-            // commands that set generated values need a `vlen`
-            if keyspace.vlen().is_none()
-                && keyspace.vkind() == ValueKind::Bytes
-                && matches!(command.verb(), Verb::Set | Verb::HashSet)
-            {
-                eprintln!(
-                    "verb: {:?} requires that the keyspace has a `vlen` set when `vkind` is `bytes`",
-                    command.verb()
-                );
-                std::process::exit(2);
-            }
+        Some(vlen.div_ceil(chunk_size))
+    }
 
-            // cardinality must always be > 0
-            if command.cardinality() == 0 {
-                eprintln!("cardinality must not be zero",);
-                std::process::exit(2);
-            }
+    /// Returns the length of every generated value, if it is fixed for this
+    /// keyspace.
+    fn expected_vlen(&self) -> Option<usize> {
+        if !self.vlen_set
+            || self.vkind != ValueKind::Bytes
+            || self.corpus.is_some()
+            || self.vlen_sampler.is_some()
+        {
+            return None;
+        }
 
-            // not all commands support cardinality > 1
-            if command.cardinality() > 1 && !command.verb().supports_cardinality() {
-                eprintln!(
-                    "verb: {:?} requires that `cardinality` is set to `1`",
-                    command.verb()
-                );
-                std::process::exit(2);
-            }
+        match self.max_value_bytes {
+            Some(max) => Some(std::cmp::min(self.vlen, max)),
+            None => Some(self.vlen),
+        }
+    }
 
-            if command.start().is_some() && !command.verb().supports_start() {
-                eprintln!(
-                    "verb: {:?} does not support the `start` argument",
-                    command.verb()
-                );
-            }
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
 
-            if command.end().is_some() && !command.verb().supports_end() {
-                eprintln!(
-                    "verb: {:?} does not support the `end` argument",
-                    command.verb()
-                );
+    /// Returns the condition for a `delete_if` with the expected value. The
+    /// keyspace is validated to support the expected value.
+    fn delete_condition(&self, expected: config::ExpectedValue) -> client::DeleteCondition {
+        match expected {
+            config::ExpectedValue::Any => client::DeleteCondition::Any,
+            config::ExpectedValue::Length => {
+                client::DeleteCondition::Length(self.expected_vlen().unwrap())
             }
-
-            if command.by_score() && !command.verb().supports_by_score() {
-                eprintln!(
-                    "verb: {:?} does not support the `by_score` option",
-                    command.verb()
-                );
+            config::ExpectedValue::Header => {
+                client::DeleteCondition::Header(self.value_header.clone().unwrap())
             }
+        }
+    }
+}
 
-            if command.truncate().is_some() {
-                // truncate must be >= 1
-                if command.truncate().unwrap() == 0 {
-                    eprintln!("truncate must be >= 1",);
-                    std::process::exit(2);
-                }
+/// A ratelimiter owned by a single workload thread which enforces that
+/// thread's share of the workload ratelimit. The shared ratelimiter is not
+/// waited on, but its parameters are tracked so that the share is recomputed
+/// when the ratelimit changes, such as during a ramp.
+struct ThreadRatelimiter {
+    shared: Arc<Ratelimiter>,
+    ratelimiter: Ratelimiter,
+    threads: u64,
+    params: (u64, Duration),
+}
 
-                // not all commands support truncate
-                if !command.verb().supports_truncate() {
-                    eprintln!("verb: {:?} does not support truncate", command.verb());
-                    std::process::exit(2);
-                }
-            }
+impl ThreadRatelimiter {
+    fn new(shared: Arc<Ratelimiter>, threads: usize) -> Self {
+        let threads = std::cmp::max(1, threads) as u64;
+        let params = (shared.refill_amount(), shared.refill_interval());
+        let (amount, interval, capacity) = Self::share(params, threads);
 
-            if command.verb().needs_inner_key()
-                && (keyspace.inner_keys_nkeys().is_none() || keyspace.inner_keys_klen().is_none())
-            {
-                eprintln!(
-                    "verb: {:?} requires that `inner_key_klen` and `inner_key_nkeys` are set",
-                    command.verb()
-                );
-                std::process::exit(2);
-            }
+        let ratelimiter = Ratelimiter::builder(amount, interval)
+            .max_tokens(capacity)
+            .build()
+            .expect("failed to initialize ratelimiter");
+
+        Self {
+            shared,
+            ratelimiter,
+            threads,
+            params,
         }
+    }
 
-        let command_dist = WeightedAliasIndex::new(command_weights).unwrap();
+    /// Calculates the refill amount, interval, and capacity for this thread's
+    /// share of the shared ratelimiter.
+    fn share(params: (u64, Duration), threads: u64) -> (u64, Duration, u64) {
+        let (amount, interval) = params;
+        let rate = (amount as f64 * 1_000_000_000.0 / interval.as_nanos() as f64) as u64;
 
-        Self {
-            keys,
-            key_dist,
-            commands,
-            command_dist,
-            inner_keys,
-            inner_key_dist,
-            vlen: keyspace.vlen().unwrap_or(0),
-            vkind: keyspace.vkind(),
-            value_random_bytes,
-            ttl: keyspace.ttl(),
+        let rate = std::cmp::max(1, rate / threads);
+        let amount = (rate as f64 / 1_000_000.0).ceil() as u64;
+
+        // even though we might not have nanosecond level clock resolution,
+        // by using a nanosecond level duration, we achieve more accurate
+        // ratelimits.
+        let interval = Duration::from_nanos(1_000_000_000 / (rate / amount));
+
+        // keep the bursts small so the aggregate rate stays smooth
+        let capacity = amount;
+
+        (amount, interval, capacity)
+    }
+
+    fn wait(&mut self) {
+        let params = (self.shared.refill_amount(), self.shared.refill_interval());
+
+        if params != self.params {
+            let (amount, interval, capacity) = Self::share(params, self.threads);
+
+            self.ratelimiter
+                .set_max_tokens(capacity)
+                .expect("failed to set max tokens");
+            self.ratelimiter
+                .set_refill_interval(interval)
+                .expect("failed to set refill interval");
+            self.ratelimiter
+                .set_refill_amount(amount)
+                .expect("failed to set refill amount");
+
+            self.params = params;
+        }
+
+        while let Err(delay) = self.ratelimiter.try_wait() {
+            std::thread::sleep(std::cmp::min(delay, Duration::from_micros(100)));
         }
     }
+}
 
-    pub fn sample(&self, rng: &mut dyn RngCore) -> Arc<[u8]> {
-        let index = self.key_dist.sample(rng);
-        self.keys[index].clone()
+/// Loads the files in a directory as a value corpus, returning the file names
+/// and the contents. The total size of the contents is limited to `max_bytes`.
+fn load_corpus(path: &str, max_bytes: usize) -> (Vec<String>, Vec<Arc<[u8]>>) {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("error reading value corpus: {path}\n{e}");
+            std::process::exit(1);
+        }
+    };
+
+    // sort the files so that the corpus is consistent between runs
+    let mut files: Vec<std::path::PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    let mut remaining = max_bytes;
+
+    for file in files {
+        if remaining == 0 {
+            warn!("value corpus reached the limit of {max_bytes} bytes, remaining files skipped");
+            break;
+        }
+
+        // read at most the remaining budget so large files are not loaded in
+        // their entirety
+        let mut value = Vec::new();
+        let result = std::fs::File::open(&file).and_then(|f| {
+            let mut f = std::io::Read::take(f, remaining as u64);
+            std::io::Read::read_to_end(&mut f, &mut value)
+        });
+
+        if let Err(e) = result {
+            eprintln!("error reading value corpus file: {}\n{e}", file.display());
+            std::process::exit(1);
+        }
+
+        remaining -= value.len();
+
+        let name = file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        names.push(name);
+        values.push(value.into());
     }
 
-    pub fn sample_inner(&self, rng: &mut dyn RngCore) -> Arc<[u8]> {
-        let index = self.inner_key_dist.sample(rng);
-        self.inner_keys[index].clone()
+    if values.is_empty() {
+        eprintln!("value corpus is empty: {path}");
+        std::process::exit(1);
     }
 
-    pub fn gen_value(&self, rng: &mut dyn RngCore) -> Vec<u8> {
-        match self.vkind {
-            ValueKind::I64 => format!("{}", rng.gen::<i64>()).into_bytes(),
-            ValueKind::Bytes => {
-                let mut buf = vec![0_u8; self.vlen];
-                rng.fill(&mut buf[0..self.value_random_bytes]);
-                buf
+    info!(
+        "loaded {} values from the value corpus: {path}",
+        values.len()
+    );
+
+    (names, values)
+}
+
+/// Loads the keys to preload, one per line, each optionally followed by a
+/// value after the first whitespace. Blank lines and lines starting with `#`
+/// are ignored.
+fn load_preload(path: &str) -> Vec<(Arc<[u8]>, Option<Vec<u8>>)> {
+    let contents = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("failed to read preload keys file: {path}\n{e}");
+        std::process::exit(1);
+    });
+
+    let entries: Vec<(Arc<[u8]>, Option<Vec<u8>>)> = contents
+        .lines()
+        .map(|text| text.trim())
+        .filter(|text| !text.is_empty() && !text.starts_with('#'))
+        .map(|text| match text.split_once(char::is_whitespace) {
+            Some((key, value)) => {
+                let value = value.trim().as_bytes().to_vec();
+                (key.as_bytes().into(), Some(value))
             }
-        }
+            None => (text.as_bytes().into(), None),
+        })
+        .collect();
+
+    if entries.is_empty() {
+        eprintln!("preload keys file is empty: {path}");
+        std::process::exit(1);
     }
 
-    pub fn ttl(&self) -> Option<Duration> {
-        self.ttl
+    entries
+}
+
+/// Writes the keys, one per line and in the order of their indices, so that a
+/// later run can load an identical keyspace.
+fn save_keys(path: &str, keys: &[Arc<[u8]>]) {
+    let mut contents = Vec::with_capacity(keys.iter().map(|key| key.len() + 1).sum());
+
+    for key in keys {
+        contents.extend_from_slice(key);
+        contents.push(b'\n');
+    }
+
+    if let Err(e) = std::fs::write(path, contents) {
+        eprintln!("failed to write keys file: {path}\n{e}");
+        std::process::exit(1);
+    }
+}
+
+/// Loads the keys written by `save_keys`. Blank lines and lines starting with
+/// `#` are ignored.
+fn load_keys(path: &str) -> Vec<Arc<[u8]>> {
+    let contents = std::fs::read(path).unwrap_or_else(|e| {
+        eprintln!("failed to read keys file: {path}\n{e}");
+        std::process::exit(1);
+    });
+
+    let keys: Vec<Arc<[u8]>> = contents
+        .split(|byte| *byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.is_empty() && !line.starts_with(b"#"))
+        .map(|line| line.into())
+        .collect();
+
+    if keys.is_empty() {
+        eprintln!("keys file is empty: {path}");
+        std::process::exit(1);
     }
+
+    keys
 }
 
 pub async fn reconnect(work_sender: Sender<ClientWorkItem>, config: Config) -> Result<()> {
@@ -772,12 +2932,69 @@ pub async fn reconnect(work_sender: Sender<ClientWorkItem>, config: Config) -> R
     Ok(())
 }
 
+/// Periodically re-resolves the endpoints. When the resolved addresses change,
+/// a reconnect is queued for each client task so that the connections move to
+/// the new addresses.
+pub async fn dns_refresh(work_sender: Sender<ClientWorkItem>, config: Config) {
+    if config.client().is_none() {
+        return;
+    }
+
+    let Some(interval) = config.target().dns_refresh_interval() else {
+        return;
+    };
+
+    let mut addresses = resolve_endpoints(config.target().endpoints()).await;
+
+    while RUNNING.load(Ordering::Relaxed) {
+        tokio::time::sleep(interval).await;
+
+        let current = resolve_endpoints(config.target().endpoints()).await;
+
+        // a failed lookup is treated as transient, the existing connections
+        // are kept until the endpoint resolves again
+        if current.iter().any(|addresses| addresses.is_empty()) || current == addresses {
+            continue;
+        }
+
+        info!("endpoint addresses changed, reconnecting");
+        DNS_CHANGE.increment();
+
+        addresses = current;
+
+        for _ in 0..clients::client_tasks(&config) {
+            if work_sender.send(ClientWorkItem::Reconnect).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn resolve_endpoints(endpoints: &[String]) -> Vec<BTreeSet<SocketAddr>> {
+    let mut resolved = Vec::with_capacity(endpoints.len());
+
+    for endpoint in endpoints {
+        let addresses = match tokio::net::lookup_host(endpoint.as_str()).await {
+            Ok(addresses) => addresses.collect(),
+            Err(e) => {
+                debug!("failed to resolve endpoint: {endpoint}: {e}");
+                BTreeSet::new()
+            }
+        };
+
+        resolved.push(addresses);
+    }
+
+    resolved
+}
+
 #[derive(Clone)]
 pub struct Ratelimit {
-    limits: Vec<u64>,
-    interval: Duration,
+    /// The rate and the duration for which it is held, for each step.
+    limits: Vec<(u64, Duration)>,
     ramp_completion_action: RampCompletionAction,
     current_idx: usize,
+    stepped: bool,
 }
 
 impl Ratelimit {
@@ -788,21 +3005,30 @@ impl Ratelimit {
             return None;
         }
 
-        // Unwrapping values is safe since the structure has already been
-        // validated for dynamic ratelimit parameters
-        let start: u64 = ratelimit_config.start().unwrap().into();
-        let end = ratelimit_config.end().unwrap();
-        let step = ratelimit_config.step().unwrap() as usize;
-        let interval = ratelimit_config.interval().unwrap();
         let ramp_type = ratelimit_config.ramp_type();
         let ramp_completion_action = ratelimit_config.ramp_completion_action();
+        let stepped = !ratelimit_config.steps().is_empty();
 
-        // Store all the ratelimits to test in a vector
-        let nsteps = ((end - start) as usize / step) + 1;
-        let mut limits: Vec<u64> = Vec::with_capacity(nsteps);
-        for i in (start..end + 1).step_by(step) {
-            limits.push(i);
-        }
+        let mut limits: Vec<(u64, Duration)> = if stepped {
+            ratelimit_config
+                .steps()
+                .iter()
+                .map(|step| (step.rate(), step.duration()))
+                .collect()
+        } else {
+            // Unwrapping values is safe since the structure has already been
+            // validated for dynamic ratelimit parameters
+            let start: u64 = ratelimit_config.start().unwrap().into();
+            let end = ratelimit_config.end().unwrap();
+            let step = ratelimit_config.step().unwrap() as usize;
+            let interval = ratelimit_config.interval().unwrap();
+
+            // Store all the ratelimits to test in a vector
+            (start..end + 1)
+                .step_by(step)
+                .map(|limit| (limit, interval))
+                .collect()
+        };
 
         // Shuffle the order of ratelimits if specified
         if ramp_type == RampType::Shuffled {
@@ -819,17 +3045,20 @@ impl Ratelimit {
 
         Some(Ratelimit {
             limits,
-            interval,
             ramp_completion_action,
             current_idx: 0,
+            stepped,
         })
     }
 
-    pub fn interval(&self) -> Duration {
-        self.interval
+    /// Returns `true` if this is a stepped rate schedule, in which case the
+    /// metrics for each step are reported.
+    pub fn stepped(&self) -> bool {
+        self.stepped
     }
 
-    pub fn next_ratelimit(&mut self) -> u64 {
+    /// Returns the next ratelimit and the duration for which it is held.
+    pub fn next_ratelimit(&mut self) -> (u64, Duration) {
         let limit = self.limits[self.current_idx];
         self.current_idx += 1;
 
@@ -850,6 +3079,133 @@ impl Ratelimit {
     }
 }
 
+/// Lowers the ratelimit linearly from its current value at `start` to zero at
+/// `stop`. Once the ramp-down begins the dynamic ratelimit controller no longer
+/// adjusts the ratelimit.
+pub async fn ramp_down(
+    ratelimiter: Arc<Ratelimiter>,
+    start: tokio::time::Instant,
+    stop: tokio::time::Instant,
+) {
+    tokio::time::sleep_until(start).await;
+
+    if !RUNNING.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let initial = RATELIMIT_CURR.value().max(1) as f64;
+
+    RAMP_DOWN_FROM.store(initial as u64, Ordering::Relaxed);
+    RAMP_DOWN.store(true, Ordering::Relaxed);
+    let window = (stop - start).as_secs_f64();
+
+    output!(
+        "Ramp down: lowering the ratelimit from {} over {:.0} s",
+        initial as u64,
+        window
+    );
+
+    // the ratelimit is updated several times a second so that the decline is
+    // smooth, the ratelimiter cannot be set to zero so it bottoms out at one
+    while RUNNING.load(Ordering::Relaxed) {
+        let now = tokio::time::Instant::now();
+
+        if now >= stop {
+            break;
+        }
+
+        let remaining = (stop - now).as_secs_f64() / window;
+        let rate = std::cmp::max(1, (initial * remaining) as u64);
+
+        let _ = admin::handlers::update_ratelimit(rate, Some(ratelimiter.clone())).await;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Generates a JSON object of alternating string and integer fields which is
+/// approximately `len` bytes long. The structure adds overhead, so the value
+/// is never shorter than an empty object and may fall a few bytes short of
+/// `len`. The actual lengths are recorded in the value length histogram.
+fn json_value(rng: &mut dyn RngCore, len: usize) -> Vec<u8> {
+    let mut value = Vec::with_capacity(len);
+    value.push(b'{');
+
+    for field in 0_usize.. {
+        let name = format!("{}\"f{field}\":", if field > 0 { "," } else { "" });
+
+        // leave room for the closing brace
+        let remaining = len.saturating_sub(value.len() + name.len() + 1);
+
+        if field % 2 == 1 && remaining >= 10 {
+            value.extend_from_slice(name.as_bytes());
+            value.extend_from_slice(rng.gen::<u32>().to_string().as_bytes());
+        } else if remaining >= 3 {
+            // strings have at most 64 characters, further fields fill out the
+            // rest of the value
+            let chars = std::cmp::min(remaining - 2, 64);
+            value.extend_from_slice(name.as_bytes());
+            value.push(b'"');
+            value.extend((&mut *rng).sample_iter(&Alphanumeric).take(chars));
+            value.push(b'"');
+        } else {
+            break;
+        }
+    }
+
+    value.push(b'}');
+    value
+}
+
+/// Generates a protobuf-shaped message which is approximately `len` bytes long.
+/// Every third field is a varint and the others are length-delimited fields of
+/// random bytes. Field numbers cycle through 1 - 15 so each tag is one byte.
+fn protobuf_value(rng: &mut dyn RngCore, len: usize) -> Vec<u8> {
+    let mut value = Vec::with_capacity(len);
+
+    for field in 0_usize.. {
+        let remaining = len - value.len();
+        let number = (field % 15 + 1) as u8;
+
+        if field % 3 == 2 && remaining >= 6 {
+            // a varint of up to five bytes
+            value.push(number << 3);
+
+            let mut varint = rng.gen::<u32>();
+
+            while varint >= 0x80 {
+                value.push((varint as u8) | 0x80);
+                varint >>= 7;
+            }
+
+            value.push(varint as u8);
+        } else if remaining >= 2 {
+            // the length is limited so that it is a single byte varint
+            let bytes = std::cmp::min(remaining - 2, 127);
+            value.push((number << 3) | 2);
+            value.push(bytes as u8);
+
+            let start = value.len();
+            value.resize(start + bytes, 0);
+            rng.fill_bytes(&mut value[start..]);
+        } else {
+            break;
+        }
+    }
+
+    value
+}
+
+/// Determine the shard for a key using the FNV-1a hash.
+fn shard_for_key(key: &[u8], shards: usize) -> usize {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in key {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (hash % shards as u64) as usize
+}
+
 fn estimate_random_bytes_needed(length: usize, compression_ratio: f64) -> usize {
     // if compression ratio is low, all bytes should be random
     if compression_ratio <= 1.0 {
@@ -891,3 +3247,93 @@ fn estimate_random_bytes_needed(length: usize, compression_ratio: f64) -> usize
 
     best
 }
+
+/// Returns the secret for value HMACs. The secret is taken from the config or
+/// generated on first use, and is logged so that a later run can verify the
+/// values written by this one.
+fn hmac_secret(config: &Config) -> Arc<[u8]> {
+    HMAC_SECRET
+        .get_or_init(|| {
+            let secret = match config.general().value_hmac_key() {
+                Some(secret) => secret.to_string(),
+                None => {
+                    let mut bytes = [0_u8; 32];
+                    thread_rng().fill(&mut bytes);
+                    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+                }
+            };
+
+            output!("Value HMAC key: {secret}");
+
+            secret.into_bytes().into()
+        })
+        .clone()
+}
+
+/// Orders the fields or members of a multi-field command, using `key` to get
+/// the key bytes of each item.
+fn order_batch<T>(
+    order: BatchOrder,
+    items: &mut [T],
+    key: impl Fn(&T) -> &Arc<[u8]>,
+    rng: &mut dyn RngCore,
+) {
+    match order {
+        BatchOrder::Sampled => {}
+        BatchOrder::Sorted => items.sort_by(|a, b| key(a).cmp(key(b))),
+        BatchOrder::Shuffled => items.shuffle(rng),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyspace(vlen: usize) -> Keyspace {
+        let config: Config = toml::from_str(&format!(
+            r#"
+            [general]
+            protocol = "null"
+            interval = 1
+            duration = 60
+
+            [debug]
+            log_level = "info"
+
+            [target]
+            endpoints = []
+
+            [client]
+            threads = 1
+            poolsize = 1
+            concurrency = 1
+            connect_timeout = 10000
+            request_timeout = 1000
+
+            [workload]
+            threads = 1
+
+            [[workload.keyspace]]
+            nkeys = 16
+            klen = 8
+            vlen = {vlen}
+            commands = [{{ verb = "set", weight = 1 }}]
+            "#
+        ))
+        .unwrap();
+
+        Keyspace::new(&config, &config.workload().keyspaces()[0])
+    }
+
+    #[test]
+    fn zero_length_values() {
+        let keyspace = keyspace(0);
+        let mut rng = Xoshiro512PlusPlus::seed_from_u64(0);
+
+        let value = keyspace.gen_value(&mut rng);
+
+        assert!(value.is_empty());
+        assert_eq!(keyspace.logical_len(&value), 0);
+        assert_eq!(keyspace.expected_vlen(), Some(0));
+    }
+}